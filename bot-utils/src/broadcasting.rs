@@ -32,7 +32,7 @@ use tokio::task::JoinHandle;
 use tokio::time::{Instant, MissedTickBehavior, interval, sleep, sleep_until};
 use tracing::instrument;
 
-use super::ChatId;
+use super::{ChatId, Recipient};
 use crate::response::RequestError;
 
 const BROADCASTS_PER_SECOND: f32 = 30.;
@@ -45,11 +45,11 @@ enum ChatStatus<U> {
     OutOfSync,
     Stopped,
     ShuttingDown,
-    MigratedTo(ChatId),
+    MigratedTo(Recipient),
 }
 
-fn delay(chat_id: ChatId) -> Duration {
-    if chat_id < 0 {
+fn delay(recipient: Recipient) -> Duration {
+    if recipient.chat_id < 0 {
         MESSAGE_INTERVAL_GROUP
     } else {
         MESSAGE_INTERVAL_CHAT
@@ -90,20 +90,27 @@ pub trait Backend: Send + Sync + Sized + 'static {
     /// Returns a stream that first yields the id of the latest update as soon as possible, and then
     /// yields whenever there are new updates with a later UpdateId. When it returns None, a soft shutdown
     /// is initiated.
-    fn receive_updates(&self)
-    -> impl Stream<Item = (Self::UpdateId, Vec<ChatId>)> + Send + 'static;
+    fn receive_updates(
+        &self,
+    ) -> impl Stream<Item = (Self::UpdateId, Vec<Recipient>)> + Send + 'static;
 
-    fn next_update(&self, chat: ChatId) -> ret_ty![NextUpdate<Self>];
+    fn next_update(&self, recipient: Recipient) -> ret_ty![NextUpdate<Self>];
 
-    fn send(&self, chat: ChatId, message: &Self::Message) -> ret_ty![(), frankenstein::Error];
+    fn send(
+        &self,
+        recipient: Recipient,
+        message: &Self::Message,
+    ) -> ret_ty![(), frankenstein::Error];
 
-    fn acknowledge(&self, chat: ChatId, update: Self::UpdateId) -> ret_ty![bool];
+    fn acknowledge(&self, recipient: Recipient, update: Self::UpdateId) -> ret_ty![bool];
 
-    fn unacknowledge(&self, chat: ChatId, update: Self::UpdateId) -> ret_ty![bool];
+    fn unacknowledge(&self, recipient: Recipient, update: Self::UpdateId) -> ret_ty![bool];
 
+    /// Moves the subscriptions of a group that was upgraded to a supergroup
     fn migrate_chat(&self, old: ChatId, new: ChatId) -> ret_ty![bool];
 
-    fn remove_chat(&self, id: ChatId) -> ret_ty![bool];
+    /// Removes the subscription of the chat or topic
+    fn remove_chat(&self, recipient: Recipient) -> ret_ty![bool];
 }
 
 struct SharedDependencies<B: Backend> {
@@ -120,9 +127,9 @@ fn backoff_strategy() -> impl Iterator<Item = Duration> {
     })
 }
 
-/// A message that is scheduled to be sent to a certain chat
+/// A message that is scheduled to be sent to a certain chat or topic
 struct ScheduledMessage<B: Backend> {
-    pub chat_id: ChatId,
+    pub recipient: Recipient,
     pub update: B::UpdateId,
     pub message: B::Message,
 }
@@ -131,7 +138,7 @@ impl<B: Backend> ScheduledMessage<B> {
     async fn unacknowledge(&self, shared: &SharedDependencies<B>) -> Result<bool, B::Error> {
         let r = shared
             .backend
-            .unacknowledge(self.chat_id, self.update)
+            .unacknowledge(self.recipient, self.update)
             .await?;
         if !r {
             tracing::warn!("Failed to unacknowledge message!");
@@ -172,18 +179,30 @@ impl<B: Backend> ScheduledMessage<B> {
                 ChatStatus::ShuttingDown
             }
             Err(RequestError::BotBlocked) => {
-                shared.backend.remove_chat(self.chat_id).await?;
+                shared.backend.remove_chat(self.recipient).await?;
                 tracing::info!("Bot is unable to send to this chat, subscription was removed!");
                 ChatStatus::Stopped
             }
+            Err(RequestError::TopicClosed) => {
+                tracing::info!("Topic is closed, message is skipped");
+                ChatStatus::Processed(self.update)
+            }
+            Err(RequestError::TopicDeleted) => {
+                shared.backend.remove_chat(self.recipient).await?;
+                tracing::info!("Topic was deleted, subscription was removed!");
+                ChatStatus::Stopped
+            }
             Err(RequestError::ChatMigrated(new_chat_id)) => {
                 _ = self.unacknowledge(shared).await?;
                 shared
                     .backend
-                    .migrate_chat(self.chat_id, new_chat_id)
+                    .migrate_chat(self.recipient.chat_id, new_chat_id)
                     .await?;
                 tracing::info!("Chat has been migrated to {new_chat_id}!");
-                ChatStatus::MigratedTo(new_chat_id)
+                ChatStatus::MigratedTo(Recipient {
+                    chat_id: new_chat_id,
+                    thread_id: self.recipient.thread_id,
+                })
             }
             Err(RequestError::RetryAfter(dur)) => retry_with_backoff!(dur),
             Err(RequestError::ClientError) => {
@@ -204,7 +223,7 @@ impl<B: Backend> ScheduledMessage<B> {
     }
 
     /// Sends a message. Will retry a number of times if it fails
-    #[tracing::instrument(skip_all, fields(chat_id=self.chat_id, update_id=?self.update))]
+    #[tracing::instrument(skip_all, fields(recipient=%self.recipient, update_id=?self.update))]
     async fn send_message(
         &self,
         shared: &SharedDependencies<B>,
@@ -217,14 +236,14 @@ impl<B: Backend> ScheduledMessage<B> {
             *message_sent = false;
             let ack = shared
                 .backend
-                .acknowledge(self.chat_id, self.update)
+                .acknowledge(self.recipient, self.update)
                 .await?;
             if !ack {
                 tracing::warn!("Failed to acknowledged message!");
                 return Ok(ChatStatus::OutOfSync);
             }
             tracing::trace!("Message was acknowledged, trying to send it!");
-            let response = shared.backend.send(self.chat_id, &self.message).await;
+            let response = shared.backend.send(self.recipient, &self.message).await;
             *message_sent = true;
 
             match self
@@ -248,23 +267,29 @@ impl<B: Backend> ScheduledMessage<B> {
 #[instrument(skip(shared), ret(level = "debug"))]
 async fn process_next_update<B: Backend>(
     shared: &SharedDependencies<B>,
-    chat_id: ChatId,
+    recipient: Recipient,
 ) -> Result<ChatStatus<B::UpdateId>, B::Error> {
     tracing::debug!("Processing next update");
     let started = Instant::now();
 
-    let (update, message) = match shared.backend.next_update(chat_id).await? {
+    let (update, message) = match shared.backend.next_update(recipient).await? {
         NextUpdate::Ready { id, msg: next } => (id, next),
         NextUpdate::Skipped { id } => return Ok(ChatStatus::Processed(id)),
         NextUpdate::OutOfSync => return Ok(ChatStatus::OutOfSync),
         NextUpdate::Pending { previous: last } => return Ok(ChatStatus::Processed(last)),
-        NextUpdate::Migrated { to } => return Ok(ChatStatus::MigratedTo(to)),
+        NextUpdate::Migrated { to } => {
+            let to = Recipient {
+                chat_id: to,
+                thread_id: recipient.thread_id,
+            };
+            return Ok(ChatStatus::MigratedTo(to));
+        }
         NextUpdate::Stopped => return Ok(ChatStatus::Stopped),
     };
 
     // pass the message to the sender task
     let scheduled = ScheduledMessage {
-        chat_id,
+        recipient,
         update,
         message,
     };
@@ -275,7 +300,7 @@ async fn process_next_update<B: Backend>(
         Ok((r, true)) => {
             // message has been sent, apply a delay for rate limiting
             tracing::debug!("Applying delay for rate limiting");
-            sleep_until(started + delay(chat_id)).await;
+            sleep_until(started + delay(recipient)).await;
             r
         }
         Ok((r, false)) => {
@@ -328,65 +353,65 @@ enum ShutdownSignal {
     Hard,
 }
 
-struct BroadcastManager<'a, B: Backend, Fut, F: Fn(&'a SharedDependencies<B>, ChatId) -> Fut> {
+struct BroadcastManager<'a, B: Backend, Fut, F: Fn(&'a SharedDependencies<B>, Recipient) -> Fut> {
     shared: &'a SharedDependencies<B>,
     latest_entry_id: Option<B::UpdateId>,
-    states: HashMap<ChatId, ProcessingState>,
+    states: HashMap<Recipient, ProcessingState>,
     process_next_message: F,
     processing: FuturesUnordered<Fut>,
 }
 
-impl<'a, B: Backend, Fut, F: Fn(&'a SharedDependencies<B>, ChatId) -> Fut>
+impl<'a, B: Backend, Fut, F: Fn(&'a SharedDependencies<B>, Recipient) -> Fut>
     BroadcastManager<'a, B, Fut, F>
 {
     /// should be called if there's possibly a new message for this chat
-    fn trigger_chat(&mut self, chat_id: ChatId) {
-        match self.states.entry(chat_id) {
+    fn trigger_chat(&mut self, recipient: Recipient) {
+        match self.states.entry(recipient) {
             Entry::Occupied(mut entry) => {
-                tracing::debug!("Triggered chat {chat_id} already running");
+                tracing::debug!("Triggered chat {recipient} already running");
                 entry.get_mut().triggered_while_running = true;
             }
             Entry::Vacant(entry) => {
                 self.processing
-                    .push((self.process_next_message)(self.shared, chat_id));
+                    .push((self.process_next_message)(self.shared, recipient));
                 entry.insert(ProcessingState::default());
             }
         }
     }
 
     /// triggers all active chats after a new message has arrived
-    fn on_message_scheduled(&mut self, id: B::UpdateId, active_chats: Vec<ChatId>) {
+    fn on_message_scheduled(&mut self, id: B::UpdateId, active_chats: Vec<Recipient>) {
         tracing::info!(
             active_chats = active_chats.len(),
             "Latest scheduled message: {id:?}"
         );
         self.latest_entry_id = Some(id);
 
-        for chat_id in active_chats {
-            self.trigger_chat(chat_id);
+        for recipient in active_chats {
+            self.trigger_chat(recipient);
         }
     }
 
     fn on_processing_finished(
         &mut self,
-        chat_id: ChatId,
+        recipient: Recipient,
         result: Result<ChatStatus<B::UpdateId>, B::Error>,
     ) {
         let restart = self
             .states
-            .remove(&chat_id)
+            .remove(&recipient)
             .map(|s| s.triggered_while_running)
             .unwrap_or_else(|| {
-                tracing::warn!(chat_id, "ProcessingState is missing unexpectedly");
+                tracing::warn!(%recipient, "ProcessingState is missing unexpectedly");
                 true // restart task to be on the safe site
             });
         match result {
             Ok(ChatStatus::Processed(stream_id)) => {
                 if Some(stream_id) < self.latest_entry_id {
-                    self.trigger_chat(chat_id);
+                    self.trigger_chat(recipient);
                 }
             }
-            Ok(ChatStatus::OutOfSync) => self.trigger_chat(chat_id),
+            Ok(ChatStatus::OutOfSync) => self.trigger_chat(recipient),
             Ok(ChatStatus::Stopped) => {
                 if restart {
                     // It's possible that a user unsubscribes and then quickly re-subscribes.
@@ -395,10 +420,10 @@ impl<'a, B: Backend, Fut, F: Fn(&'a SharedDependencies<B>, ChatId) -> Fut>
                     // was re-triggered during that time. Ignoring these `ChatStopped` results is
                     // harmless and helps avoid missing messages.
 
-                    self.trigger_chat(chat_id);
+                    self.trigger_chat(recipient);
                 }
             }
-            Ok(ChatStatus::MigratedTo(chat_id)) => self.trigger_chat(chat_id),
+            Ok(ChatStatus::MigratedTo(recipient)) => self.trigger_chat(recipient),
             Ok(ChatStatus::ShuttingDown) => (),
             Err(e) => tracing::error!(error=%e, "Processing chat failed"),
         }
@@ -420,9 +445,9 @@ async fn broadcast_task(backend: impl Backend, mut shutdown_rx: mpsc::Receiver<S
         shared: &shared,
         latest_entry_id: None,
         states: HashMap::new(),
-        process_next_message: |shared, chat_id| async move {
-            let result = process_next_update(shared, chat_id).await;
-            (chat_id, result)
+        process_next_message: |shared, recipient| async move {
+            let result = process_next_update(shared, recipient).await;
+            (recipient, result)
         },
         processing: FuturesUnordered::new(),
     };
@@ -453,8 +478,8 @@ async fn broadcast_task(backend: impl Backend, mut shutdown_rx: mpsc::Receiver<S
                     soft_shutdown = true;
                 }
             },
-            Some((chat_id, result)) = manager.processing.next(), if !manager.processing.is_empty() => {
-                manager.on_processing_finished(chat_id, result);
+            Some((recipient, result)) = manager.processing.next(), if !manager.processing.is_empty() => {
+                manager.on_processing_finished(recipient, result);
             }
         }
     }
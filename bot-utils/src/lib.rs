@@ -1,4 +1,9 @@
+use std::fmt::{self, Display};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
 use frankenstein::types::ChatMember;
+use serde::{Deserialize, Serialize};
 
 pub mod broadcasting;
 pub mod command;
@@ -39,6 +44,81 @@ macro_rules! respond {
 
 pub type ChatId = i64;
 
+/// Where messages are sent to: a chat, or a topic of a forum supergroup
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "RecipientRepr", try_from = "RecipientRepr")]
+pub struct Recipient {
+    pub chat_id: ChatId,
+    pub thread_id: Option<i32>,
+}
+
+impl Recipient {
+    pub fn topic(chat_id: ChatId, thread_id: i32) -> Self {
+        Self {
+            chat_id,
+            thread_id: Some(thread_id),
+        }
+    }
+}
+
+impl From<ChatId> for Recipient {
+    fn from(chat_id: ChatId) -> Self {
+        Self {
+            chat_id,
+            thread_id: None,
+        }
+    }
+}
+
+/// Formatted as `chat_id:thread_id` for topics, and as the chat id otherwise
+impl Display for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.thread_id {
+            Some(thread_id) => write!(f, "{}:{thread_id}", self.chat_id),
+            None => write!(f, "{}", self.chat_id),
+        }
+    }
+}
+
+impl FromStr for Recipient {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((chat_id, thread_id)) => Ok(Self::topic(chat_id.parse()?, thread_id.parse()?)),
+            None => Ok(Self::from(s.parse::<ChatId>()?)),
+        }
+    }
+}
+
+/// Chats are serialized as plain chat ids, as they were before topics were supported
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecipientRepr {
+    Chat(ChatId),
+    Topic(String),
+}
+
+impl From<Recipient> for RecipientRepr {
+    fn from(recipient: Recipient) -> Self {
+        match recipient.thread_id {
+            Some(_) => Self::Topic(recipient.to_string()),
+            None => Self::Chat(recipient.chat_id),
+        }
+    }
+}
+
+impl TryFrom<RecipientRepr> for Recipient {
+    type Error = ParseIntError;
+
+    fn try_from(repr: RecipientRepr) -> Result<Self, Self::Error> {
+        match repr {
+            RecipientRepr::Chat(chat_id) => Ok(Self::from(chat_id)),
+            RecipientRepr::Topic(s) => s.parse(),
+        }
+    }
+}
+
 pub fn can_send_messages(c: &ChatMember) -> bool {
     match c {
         ChatMember::Administrator(member) => member.can_post_messages.unwrap_or(true),
@@ -48,3 +128,28 @@ pub fn can_send_messages(c: &ChatMember) -> bool {
         _ => true,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Recipient;
+
+    #[test]
+    fn test_recipient() {
+        let chat = Recipient::from(-1001234);
+        let topic = Recipient::topic(-1001234, 56);
+
+        assert_eq!(chat.to_string(), "-1001234");
+        assert_eq!(topic.to_string(), "-1001234:56");
+        assert_eq!("-1001234".parse(), Ok(chat));
+        assert_eq!("-1001234:56".parse(), Ok(topic));
+        assert!("-1001234:".parse::<Recipient>().is_err());
+
+        assert_eq!(serde_json::to_string(&chat).unwrap(), "-1001234");
+        assert_eq!(serde_json::to_string(&topic).unwrap(), "\"-1001234:56\"");
+        assert_eq!(serde_json::from_str::<Recipient>("-1001234").unwrap(), chat);
+        assert_eq!(
+            serde_json::from_str::<Recipient>("\"-1001234:56\"").unwrap(),
+            topic
+        );
+    }
+}
@@ -8,6 +8,9 @@ pub enum RequestError {
     InvalidToken,
     ChatMigrated(i64),
     BotBlocked,
+    /// the topic is closed, but might be reopened
+    TopicClosed,
+    TopicDeleted,
     RetryAfter(Duration),
     ClientError,
     Other,
@@ -15,9 +18,8 @@ pub enum RequestError {
 
 /// error messages that imply we're not allowed to send messages
 /// to this chat in the future.
-const TELEGRAM_ERRORS: [&str; 14] = [
+const TELEGRAM_ERRORS: [&str; 13] = [
     "Bad Request: CHAT_WRITE_FORBIDDEN",
-    "Bad Request: TOPIC_CLOSED",
     "Bad Request: chat not found",
     "Bad Request: have no rights to send a message",
     "Bad Request: not enough rights to send text messages to the chat",
@@ -32,6 +34,12 @@ const TELEGRAM_ERRORS: [&str; 14] = [
     "Forbidden: user is deactivated",
 ];
 
+/// error messages that imply the topic doesn't exist anymore
+const TOPIC_DELETED_ERRORS: [&str; 2] = [
+    "Bad Request: TOPIC_DELETED",
+    "Bad Request: message thread not found",
+];
+
 pub fn map_error(e: &frankenstein::Error) -> RequestError {
     let Error::Api(api_error) = e else {
         return RequestError::Other;
@@ -56,6 +64,16 @@ pub fn map_error(e: &frankenstein::Error) -> RequestError {
             RequestError::BotBlocked
         }
 
+        ErrorResponse { description, .. } if description == "Bad Request: TOPIC_CLOSED" => {
+            RequestError::TopicClosed
+        }
+
+        ErrorResponse { description, .. }
+            if TOPIC_DELETED_ERRORS.contains(&description.as_str()) =>
+        {
+            RequestError::TopicDeleted
+        }
+
         ErrorResponse {
             parameters:
                 Some(ResponseParameters {
@@ -4,6 +4,7 @@ use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 
 use frankenstein::AsyncTelegramApi;
+use frankenstein::inline_mode::InlineQuery;
 use frankenstein::methods::GetUpdatesParams;
 use frankenstein::types::{
     AllowedUpdate, CallbackQuery, ChatMemberUpdated, MaybeInaccessibleMessage, Message,
@@ -32,6 +33,10 @@ pub trait UpdateHandler: Clone + Send + 'static {
     fn handle_callback_query(self, update: Box<CallbackQuery>) -> impl Future<Output = ()> + Send {
         async {}
     }
+
+    fn handle_inline_query(self, query: InlineQuery) -> impl Future<Output = ()> + Send {
+        async {}
+    }
 }
 
 fn cleanup(last_cleanup: &mut Instant, mutexes: &mut Mutexes) {
@@ -52,12 +57,15 @@ fn handle_update(
     join_set: &mut JoinSet<()>,
     update: Update,
 ) {
-    let chat = match &update.content {
-        UpdateContent::Message(msg) => &*msg.chat,
-        UpdateContent::MyChatMember(member) => &member.chat,
+    // inline queries aren't sent in a chat, so they are serialized per user, just like
+    // the user's private chat
+    let chat_id = match &update.content {
+        UpdateContent::Message(msg) => msg.chat.id,
+        UpdateContent::MyChatMember(member) => member.chat.id,
+        UpdateContent::InlineQuery(query) => query.from.id as i64,
         UpdateContent::CallbackQuery(query) => match &query.message {
-            Some(MaybeInaccessibleMessage::InaccessibleMessage(m)) => &m.chat,
-            Some(MaybeInaccessibleMessage::Message(m)) => &m.chat,
+            Some(MaybeInaccessibleMessage::InaccessibleMessage(m)) => m.chat.id,
+            Some(MaybeInaccessibleMessage::Message(m)) => m.chat.id,
             None => {
                 tracing::warn!(
                     id = update.update_id,
@@ -73,14 +81,14 @@ fn handle_update(
         }
     };
 
-    let span = tracing::error_span!("update", id = update.update_id, chat = chat.id).entered();
+    let span = tracing::error_span!("update", id = update.update_id, chat = chat_id).entered();
 
     let mutex = mutexes
-        .get(&chat.id)
+        .get(&chat_id)
         .and_then(|weak| weak.upgrade())
         .unwrap_or_else(|| {
             let mutex = Default::default();
-            mutexes.insert(chat.id, Arc::downgrade(&mutex));
+            mutexes.insert(chat_id, Arc::downgrade(&mutex));
             mutex
         });
 
@@ -107,6 +115,7 @@ fn handle_update(
             UpdateContent::CallbackQuery(q) => {
                 handler.handle_callback_query(q).await;
             }
+            UpdateContent::InlineQuery(q) => handler.handle_inline_query(q).await,
             _ => tracing::warn!("Unreachable code reached!"),
         }
     };
@@ -0,0 +1,152 @@
+//! Allris 4 specific parts: its id scheme and the information scraped from the papers' web pages
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use reqwest::Client;
+use url::Url;
+
+use super::Error;
+use super::html::{WebsiteData, scrape_website};
+use super::oparl::{self, Meeting, Paper};
+use super::source::{PaperDetails, Source, basic_tags};
+use crate::types::Tag;
+
+/// Represents the url to an Allris instance
+#[derive(Debug, Clone)]
+pub struct AllrisUrl {
+    // invariant: the url has a trailing backslash
+    url: Url,
+}
+
+impl AllrisUrl {
+    pub fn parse(input: &str) -> Result<Self, url::ParseError> {
+        let mut url = Url::parse(input)?;
+
+        let path = url.path();
+        if !path.ends_with("/") {
+            url.set_path(&format!("{path}/"));
+        }
+
+        Ok(Self { url })
+    }
+
+    pub fn as_url(&self) -> &Url {
+        &self.url
+    }
+
+    fn endpoint(&self, endpoint: &str) -> Url {
+        self.url.join(endpoint).unwrap()
+    }
+}
+
+fn generate_tags(paper: &Paper, data: &WebsiteData) -> Vec<(Tag, String)> {
+    use Tag::*;
+
+    let mut tags = basic_tags(paper);
+
+    let WebsiteData {
+        unterstuetzer,
+        amt,
+        gremien,
+        beteiligt,
+        ..
+    } = data;
+
+    for verfasser in unterstuetzer {
+        tags.push((Verfasser, verfasser.clone()));
+    }
+
+    if let Some(amt) = amt {
+        tags.push((Federführend, amt.clone()));
+        tags.push((Beteiligt, amt.clone()));
+    }
+
+    for amt in beteiligt {
+        tags.push((Beteiligt, amt.clone()))
+    }
+
+    for gremium in gremien {
+        tags.push((Gremium, gremium.0.clone()));
+    }
+
+    tags
+}
+
+/// An Allris 4 instance, whose OParl api is complemented by scraping the web pages
+#[derive(Debug, Clone)]
+pub struct Allris {
+    url: AllrisUrl,
+}
+
+impl Allris {
+    pub fn new(url: AllrisUrl) -> Self {
+        Self { url }
+    }
+}
+
+impl Source for Allris {
+    fn object_id(&self, url: &Url) -> Option<String> {
+        // the Allris-internal id, e.g. the volfdnr of a paper
+        url.query_pairs()
+            .find(|(q, _)| q == "id")
+            .map(|(_, id)| id.into_owned())
+    }
+
+    async fn paper_lists(&self, _: &Client) -> Result<Vec<Url>, Error> {
+        Ok(vec![self.url.endpoint("oparl/papers")])
+    }
+
+    fn meetings(
+        &self,
+        client: &Client,
+        since: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<Meeting, Error>> + Send + 'static {
+        oparl::get_meetings(client, &self.url.endpoint("oparl/meetings"), since)
+    }
+
+    async fn paper_details(&self, client: &Client, paper: &Paper) -> PaperDetails {
+        let data = match &paper.web {
+            Some(url) => scrape_website(client, url).await.unwrap_or_else(|e| {
+                log::warn!("Couldn't scrape website: {e}");
+                WebsiteData::default()
+            }),
+            None => WebsiteData::default(),
+        };
+
+        let tags = generate_tags(paper, &data);
+
+        let WebsiteData {
+            verfasser,
+            amt,
+            gremien,
+            already_discussed,
+            ..
+        } = data;
+
+        let author = match (paper.paper_type.as_deref(), verfasser, amt) {
+            (Some("Anregungen und Beschwerden" | "Einwohnerfrage" | "Informationsbrief"), _, _) => {
+                // author is meaningless here, it's always the same Amt.
+                None
+            }
+            (Some("Stellungnahme der Verwaltung"), _, Some(amt)) => Some(amt),
+            (_, Some(verfasser), _) => Some(verfasser),
+            (_, None, Some(amt)) => Some(amt),
+            _ => None,
+        };
+
+        PaperDetails {
+            tags,
+            author,
+            gremien,
+            already_discussed,
+        }
+    }
+
+    fn website(&self) -> Option<&Url> {
+        Some(self.url.as_url())
+    }
+
+    fn web_label(&self) -> &'static str {
+        "🌐 Allris"
+    }
+}
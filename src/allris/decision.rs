@@ -0,0 +1,202 @@
+use frankenstein::methods::SendMessageParams;
+use frankenstein::types::{InlineKeyboardMarkup, LinkPreviewOptions, ReplyMarkup};
+use reqwest::Client;
+use telegram_message_builder::{WriteToMessage, bold, from_fn, italic};
+use url::Url;
+
+use super::meeting::{format_date, get_gremien};
+use super::oparl::{AgendaItem, Meeting};
+use super::{Error, Instance, link_button};
+use crate::database::{DatabaseConnection, KnownItems, StreamId};
+use crate::types::{Audience, Message, MessageKind, Tag, TrackedPaper};
+
+/// Maps the free-text result of an agenda item to a short label
+fn classify(result: &str) -> (&'static str, &'static str) {
+    let result = result.to_lowercase();
+    let contains_any = |words: &[&str]| words.iter().any(|w| result.contains(w));
+
+    if contains_any(&["vertagt", "zurückgestellt", "geschoben"]) {
+        ("⏸️", "Vertagt")
+    } else if contains_any(&["zurückgezogen"]) {
+        ("↩️", "Zurückgezogen")
+    } else if contains_any(&["abgelehnt"]) {
+        ("❌", "Abgelehnt")
+    } else if contains_any(&["verwiesen", "überwiesen"]) {
+        ("➡️", "Verwiesen")
+    } else if contains_any(&["kenntnis"]) {
+        ("ℹ️", "Zur Kenntnis genommen")
+    } else if contains_any(&["beschlossen", "angenommen", "zugestimmt", "empfohlen"]) {
+        ("✅", "Beschlossen")
+    } else {
+        ("🗳️", "Beraten")
+    }
+}
+
+fn generate_notification(
+    instance: &Instance,
+    original: StreamId,
+    paper: &TrackedPaper,
+    meeting: &Meeting,
+    gremien: &[(String, Option<Url>)],
+    item: &AgendaItem,
+    result: &str,
+) -> Option<Message> {
+    let (emoji, label) = classify(result);
+
+    let message = from_fn(|msg| {
+        write!(msg, "{emoji} {label}: ")?;
+        msg.writeln(bold(&paper.title))?;
+
+        let gremien = gremien
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match (gremien.is_empty(), &meeting.start) {
+            (false, Some(start)) => write!(msg, "\n🏛️ {gremien} am {}", format_date(start))?,
+            (false, None) => write!(msg, "\n🏛️ {gremien}")?,
+            (true, Some(start)) => write!(msg, "\n🏛️ Sitzung am {}", format_date(start))?,
+            (true, None) => (),
+        }
+
+        if let Some(dsnr) = &paper.reference {
+            write!(msg, "\n📎 Ds.-Nr. {dsnr}")?;
+        }
+
+        msg.write("\n💬 ")?;
+        msg.write(italic(result))
+    })
+    .to_message();
+
+    let (text, entities) = match message {
+        Ok(m) => m,
+        Err(telegram_message_builder::Error::MessageTooLong) => {
+            log::warn!(
+                "Result notification for \"{}\" would be too long!",
+                paper.title
+            );
+            return None;
+        }
+    };
+
+    let mut buttons = vec![];
+    buttons.extend(
+        item.resolution_file
+            .as_ref()
+            .map(|file| link_button("📄 Beschluss", &file.access_url)),
+    );
+    buttons.extend(
+        meeting
+            .web
+            .as_ref()
+            .map(|url| link_button("🌐 Sitzung", url)),
+    );
+    let keyboard = InlineKeyboardMarkup::builder()
+        .inline_keyboard(vec![buttons])
+        .build();
+    let request = SendMessageParams::builder()
+        .chat_id(0)
+        .text(text)
+        .entities(entities)
+        .link_preview_options(LinkPreviewOptions::builder().is_disabled(true).build())
+        .reply_markup(ReplyMarkup::InlineKeyboardMarkup(keyboard))
+        .build();
+
+    let mut tags = vec![(Tag::Title, paper.title.clone())];
+    tags.extend(paper.reference.clone().map(|dsnr| (Tag::Dsnr, dsnr)));
+
+    Some(Message {
+        request,
+        tags,
+        kind: MessageKind::Decision,
+        audience: Audience::RecipientsOf(original),
+        instance: Some(instance.name.clone()),
+        fulltext: None,
+    })
+}
+
+/// Sends follow-up notifications to the recipients of tracked papers, once a result
+/// of one of their consultations is published.
+pub async fn send_notifications(
+    db: &mut DatabaseConnection,
+    instance: &Instance,
+    http_client: &Client,
+    meetings: &[Meeting],
+) -> Result<(), Error> {
+    for meeting in meetings {
+        let mut gremien = None;
+
+        for item in &meeting.agenda_item {
+            let (Some(consultation), Some(result)) = (&item.consultation, &item.result) else {
+                continue;
+            };
+
+            let result = result.trim();
+            if result.is_empty() {
+                continue;
+            }
+
+            // a result might be changed later on, which deserves another notification
+            let known_id = format!("{}:{result}", item.id);
+            if db
+                .is_known_item(&instance.name, KnownItems::Results, &known_id)
+                .await?
+            {
+                continue;
+            }
+
+            let Some((original, paper)) =
+                db.get_tracked_consultation(consultation.as_str()).await?
+            else {
+                continue;
+            };
+
+            let gremien = match &gremien {
+                Some(gremien) => gremien,
+                None => gremien.insert(get_gremien(http_client, meeting).await),
+            };
+
+            if let Some(message) =
+                generate_notification(instance, original, &paper, meeting, gremien, item, result)
+            {
+                db.schedule_broadcast(
+                    &instance.name,
+                    KnownItems::Results,
+                    &known_id,
+                    &message,
+                    None,
+                )
+                .await?;
+            } else {
+                db.add_known_item(&instance.name, KnownItems::Results, &known_id)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::classify;
+
+    #[test]
+    fn test_classify() {
+        let test_cases = [
+            ("Beschlossen", "Beschlossen"),
+            ("einstimmig angenommen", "Beschlossen"),
+            ("Vertagt in die nächste Sitzung", "Vertagt"),
+            ("mehrheitlich abgelehnt", "Abgelehnt"),
+            ("Zur Kenntnis genommen", "Zur Kenntnis genommen"),
+            ("in den Ausschuss verwiesen", "Verwiesen"),
+            ("vom Antragsteller zurückgezogen", "Zurückgezogen"),
+            ("ohne Votum", "Beraten"),
+        ];
+
+        for (input, expected) in test_cases {
+            assert_eq!(classify(input).1, expected, "{input}");
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::oparl::{File, Paper};
+
+/// The parts of a paper that are compared to detect relevant changes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub title: Option<String>,
+    pub reference: Option<String>,
+    pub paper_type: Option<String>,
+    pub main_file: Option<Url>,
+    pub auxiliary_files: Vec<(Url, Option<String>)>,
+    pub organizations: Vec<String>,
+}
+
+fn file_label(url: &Url, name: &Option<String>) -> String {
+    match name {
+        Some(name) => name.clone(),
+        None => url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or(url.as_str())
+            .to_string(),
+    }
+}
+
+fn added<'a, T: PartialEq>(old: &'a [T], new: &'a [T]) -> impl Iterator<Item = &'a T> {
+    new.iter().filter(|x| !old.contains(x))
+}
+
+impl Fingerprint {
+    pub fn new(paper: &Paper, organizations: Vec<String>) -> Self {
+        let file = |f: &File| (f.access_url.clone(), f.name.clone());
+
+        Self {
+            title: paper.name.clone(),
+            reference: paper.reference.clone(),
+            paper_type: paper.paper_type.clone(),
+            main_file: paper.main_file.as_ref().map(|f| f.access_url.clone()),
+            auxiliary_files: paper.auxiliary_file.iter().map(file).collect(),
+            organizations,
+        }
+    }
+
+    /// Returns a human-readable description of the changes from `old` to `self`
+    pub fn changes_since(&self, old: &Fingerprint) -> Vec<String> {
+        let mut changes = vec![];
+
+        let mut changed_field = |label: &str, old: &Option<String>, new: &Option<String>| {
+            if old != new {
+                let change = match (old, new) {
+                    (Some(old), Some(new)) => format!("{label} geändert: „{old}“ → „{new}“"),
+                    (None, Some(new)) => format!("{label} hinzugefügt: „{new}“"),
+                    (Some(old), None) => format!("{label} entfernt: „{old}“"),
+                    (None, None) => unreachable!(),
+                };
+                changes.push(change);
+            }
+        };
+
+        changed_field("Titel", &old.title, &self.title);
+        changed_field("Ds.-Nr.", &old.reference, &self.reference);
+        changed_field("Art", &old.paper_type, &self.paper_type);
+
+        if old.main_file != self.main_file {
+            if self.main_file.is_some() {
+                changes.push("Neue Fassung des Hauptdokuments".to_string());
+            } else {
+                changes.push("Hauptdokument entfernt".to_string());
+            }
+        }
+
+        for (url, name) in added(&old.auxiliary_files, &self.auxiliary_files) {
+            changes.push(format!("Neue Anlage: {}", file_label(url, name)));
+        }
+
+        for (url, name) in added(&self.auxiliary_files, &old.auxiliary_files) {
+            changes.push(format!("Anlage entfernt: {}", file_label(url, name)));
+        }
+
+        let added_orgs: Vec<_> = added(&old.organizations, &self.organizations)
+            .map(String::as_str)
+            .collect();
+        if !added_orgs.is_empty() {
+            changes.push(format!("Beratungsfolge ergänzt: {}", added_orgs.join(", ")));
+        }
+
+        let removed_orgs: Vec<_> = added(&self.organizations, &old.organizations)
+            .map(String::as_str)
+            .collect();
+        if !removed_orgs.is_empty() {
+            changes.push(format!(
+                "Aus der Beratungsfolge entfernt: {}",
+                removed_orgs.join(", ")
+            ));
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_since() {
+        let url = |s: &str| Url::parse(&format!("https://example.org/{s}")).unwrap();
+
+        let old = Fingerprint {
+            title: Some("Radweg".into()),
+            reference: Some("252807".into()),
+            paper_type: Some("Antrag".into()),
+            main_file: Some(url("v1.pdf")),
+            auxiliary_files: vec![(url("a.pdf"), Some("Anlage 1".into()))],
+            organizations: vec!["Rat".into()],
+        };
+
+        assert!(old.changes_since(&old).is_empty());
+
+        let new = Fingerprint {
+            title: Some("Radweg Beuel".into()),
+            main_file: Some(url("v2.pdf")),
+            auxiliary_files: vec![
+                (url("a.pdf"), Some("Anlage 1".into())),
+                (url("b.pdf"), None),
+            ],
+            organizations: vec!["Rat".into(), "Bezirksvertretung Beuel".into()],
+            ..old.clone()
+        };
+
+        assert_eq!(
+            new.changes_since(&old),
+            [
+                "Titel geändert: „Radweg“ → „Radweg Beuel“",
+                "Neue Fassung des Hauptdokuments",
+                "Neue Anlage: b.pdf",
+                "Beratungsfolge ergänzt: Bezirksvertretung Beuel",
+            ]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Extracts the text of a paper's main file, so that rules can match its content
+
+use std::time::Duration;
+
+use reqwest::{Client, Response};
+use tokio::task::spawn_blocking;
+use tokio::time::timeout;
+use url::Url;
+
+use super::http_request;
+
+/// Larger files are skipped
+const MAX_FILE_SIZE: usize = 15 * 1024 * 1024;
+
+/// The text is truncated after this number of characters, as it is kept in memory while
+/// the message is broadcast
+const MAX_TEXT_CHARS: usize = 100_000;
+
+const EXTRACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+async fn download(mut response: Response) -> reqwest::Result<Option<Vec<u8>>> {
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_FILE_SIZE as u64)
+    {
+        return Ok(None);
+    }
+
+    let mut data = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        if data.len() + chunk.len() > MAX_FILE_SIZE {
+            return Ok(None);
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(Some(data))
+}
+
+/// Collapses all whitespace and truncates the text to [`MAX_TEXT_CHARS`]
+fn normalize(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = 0;
+
+    for word in text.split_whitespace() {
+        chars += word.chars().count() + 1;
+        if chars > MAX_TEXT_CHARS {
+            break;
+        }
+
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.push_str(word);
+    }
+
+    result
+}
+
+/// Downloads a PDF file and extracts its text. Returns `None` if the file is too large,
+/// or if the extraction fails or takes too long.
+pub async fn extract_text(client: &Client, url: &Url) -> Option<String> {
+    let data = match http_request(client, url, download).await {
+        Ok(Some(data)) => data,
+        Ok(None) => {
+            log::info!("Skipping full text of {url}: file is too large");
+            return None;
+        }
+        Err(e) => {
+            log::warn!("Unable to download {url}: {e}");
+            return None;
+        }
+    };
+
+    // the extraction is CPU-bound, and might panic on malformed files. In case of a
+    // timeout, the blocking thread can't be stopped, but at least the scraper continues.
+    let extraction = spawn_blocking(move || pdf_extract::extract_text_from_mem(&data));
+
+    match timeout(EXTRACTION_TIMEOUT, extraction).await {
+        Ok(Ok(Ok(text))) => Some(normalize(&text)),
+        Ok(Ok(Err(e))) => {
+            log::warn!("Unable to extract text from {url}: {e}");
+            None
+        }
+        Ok(Err(e)) => {
+            log::warn!("Text extraction from {url} failed: {e}");
+            None
+        }
+        Err(_) => {
+            log::warn!("Text extraction from {url} timed out");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("  Beschluss-\nvorlage\n\n\tRadweg   Beuel "),
+            "Beschluss- vorlage Radweg Beuel"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Support for arbitrary OParl 1.1 servers, e.g. SessionNet, relying on the api alone
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
+use reqwest::Client;
+use tokio::sync::OnceCell;
+use url::{Position, Url};
+
+use super::oparl::{self, Body, Meeting, Paper, System, get_organization};
+use super::source::{PaperDetails, Source, basic_tags};
+use super::{Error, get_gremien};
+use crate::types::Tag;
+
+/// An OParl server, whose endpoints are discovered from its `System` object
+#[derive(Debug, Clone)]
+pub struct GenericOParl {
+    system: Url,
+    bodies: Arc<OnceCell<Vec<Body>>>,
+}
+
+impl GenericOParl {
+    pub fn new(system: Url) -> Self {
+        Self {
+            system,
+            bodies: Arc::default(),
+        }
+    }
+
+    async fn bodies(&self, client: &Client) -> Result<&[Body], Error> {
+        let bodies = self
+            .bodies
+            .get_or_try_init(async || {
+                let system: System = oparl::get_object(client, &self.system).await?;
+                oparl::get_list(client.clone(), system.body)
+                    .try_collect()
+                    .await
+            })
+            .await?;
+
+        Ok(bodies)
+    }
+
+    /// Concatenates the lists of all bodies, as selected by `list`
+    fn for_each_body<T, S, F>(
+        &self,
+        client: &Client,
+        list: F,
+    ) -> impl Stream<Item = Result<T, Error>> + Send + 'static + use<T, S, F>
+    where
+        T: Send + 'static,
+        S: Stream<Item = Result<T, Error>> + Send + 'static,
+        F: Fn(&Client, &Body) -> S + Send + 'static,
+    {
+        let this = self.clone();
+        let client = client.clone();
+
+        stream::once(async move {
+            let lists: Vec<S> = this
+                .bodies(&client)
+                .await?
+                .iter()
+                .map(|body| list(&client, body))
+                .collect();
+            Ok::<_, Error>(stream::iter(lists).flatten())
+        })
+        .try_flatten()
+    }
+}
+
+async fn organization_names(client: &Client, organizations: &[Url]) -> Vec<String> {
+    let mut names = vec![];
+
+    for org in organizations {
+        match get_organization(client, org).await {
+            Ok(org) => names.extend(org.name),
+            Err(e) => log::warn!("Unable to get organization info: {e}"),
+        }
+    }
+
+    names
+}
+
+impl Source for GenericOParl {
+    fn object_id(&self, url: &Url) -> Option<String> {
+        // the scheme is omitted, as servers might be reachable both via http and https
+        Some(url[Position::BeforeHost..].to_string())
+    }
+
+    async fn paper_lists(&self, client: &Client) -> Result<Vec<Url>, Error> {
+        let bodies = self.bodies(client).await?;
+        Ok(bodies.iter().map(|body| body.paper.clone()).collect())
+    }
+
+    fn meetings(
+        &self,
+        client: &Client,
+        since: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<Meeting, Error>> + Send + 'static {
+        self.for_each_body(client, move |client, body| {
+            oparl::get_meetings(client, &body.meeting, since)
+        })
+    }
+
+    async fn paper_details(&self, client: &Client, paper: &Paper) -> PaperDetails {
+        let mut tags = basic_tags(paper);
+
+        let originators = organization_names(client, &paper.originator_organization).await;
+        let directing = organization_names(client, &paper.under_direction_of).await;
+
+        for originator in &originators {
+            tags.push((Tag::Verfasser, originator.clone()));
+        }
+
+        for amt in &directing {
+            tags.push((Tag::Federführend, amt.clone()));
+            tags.push((Tag::Beteiligt, amt.clone()));
+        }
+
+        match get_gremien(client, &paper.consultation).await {
+            Ok(gremien) => {
+                tags.extend(gremien.into_iter().map(|(name, _, _)| (Tag::Gremium, name)));
+            }
+            Err(e) => log::warn!("Unable to get consultation info: {e}"),
+        }
+
+        let author = originators.into_iter().chain(directing).next();
+
+        PaperDetails {
+            tags,
+            author,
+            ..Default::default()
+        }
+    }
+
+    fn website(&self) -> Option<&Url> {
+        None
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use frankenstein::methods::SendMessageParams;
+use frankenstein::types::{InlineKeyboardMarkup, LinkPreviewOptions, ReplyMarkup};
+use reqwest::Client;
+use telegram_message_builder::{CHAR_LIMIT, WriteToMessage, bold, from_fn, text_link};
+use url::Url;
+
+use super::oparl::{Location, MaybeEmbedded, Meeting, get_organization};
+use super::source::Source;
+use super::{Error, Instance, link_button, record_tag_values};
+use crate::database::{DatabaseConnection, KnownItems};
+use crate::types::{Audience, Message, MessageKind, Tag, WEEKDAYS};
+
+const TRUNCATION_MARKER: &str = "…";
+
+/// A meeting is usually announced before its agenda is published. Both events are
+/// tracked separately, the agenda with the suffix `:tagesordnung`.
+fn known_id(id: &str, meeting: &Meeting) -> String {
+    if meeting.agenda_item.is_empty() {
+        id.to_string()
+    } else {
+        format!("{id}:tagesordnung")
+    }
+}
+
+/// Prepends the weekday to the formatted date, e.g. `Mo, 03.02.2025`
+fn format_with_weekday(start: &DateTime<FixedOffset>, format: &str) -> String {
+    let weekday = WEEKDAYS[start.weekday().num_days_from_monday() as usize];
+    format!("{weekday}, {}", start.format(format))
+}
+
+pub fn format_date(start: &DateTime<FixedOffset>) -> String {
+    format_with_weekday(start, "%d.%m.%Y")
+}
+
+fn format_start(start: &DateTime<FixedOffset>) -> String {
+    format_with_weekday(start, "%d.%m.%Y, %H:%M Uhr")
+}
+
+fn format_location(location: &Location) -> Option<String> {
+    let parts: Vec<&str> = [
+        location.description.as_deref(),
+        location.room.as_deref(),
+        location.street_address.as_deref(),
+        location.locality.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .collect();
+
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+pub async fn get_gremien(client: &Client, meeting: &Meeting) -> Vec<(String, Option<Url>)> {
+    let mut gremien = vec![];
+
+    for org in &meeting.organization {
+        match get_organization(client, org).await {
+            Ok(org) => gremien.extend(org.name.map(|name| (name, org.web))),
+            Err(e) => log::warn!("Unable to get organization info: {e}"),
+        }
+    }
+
+    gremien
+}
+
+fn generate_tags(meeting: &Meeting, gremien: &[(String, Option<Url>)]) -> Vec<(Tag, String)> {
+    let mut tags = vec![];
+
+    if let Some(name) = &meeting.name {
+        tags.push((Tag::Title, name.clone()));
+    }
+
+    for item in &meeting.agenda_item {
+        if let Some(name) = &item.name {
+            tags.push((Tag::Title, name.clone()));
+        }
+    }
+
+    for (gremium, _) in gremien {
+        tags.push((Tag::Gremium, gremium.clone()));
+    }
+
+    tags
+}
+
+/// generates a notification message for the given `Meeting`. If it already has agenda items,
+/// the message will contain the agenda.
+async fn generate_notification(
+    client: &Client,
+    instance: &Instance,
+    meeting: &Meeting,
+) -> Option<Message> {
+    let name = meeting.name.as_deref()?;
+    let start = meeting.start.as_ref()?;
+    let gremien = get_gremien(client, meeting).await;
+    let tags = generate_tags(meeting, &gremien);
+
+    let location = match &meeting.location {
+        Some(MaybeEmbedded::Embedded(location)) => format_location(location),
+        _ => None,
+    };
+
+    let headline = if meeting.agenda_item.is_empty() {
+        "📅 Neue Sitzung: "
+    } else {
+        "📋 Tagesordnung veröffentlicht: "
+    };
+
+    let message = from_fn(|msg| {
+        msg.write(headline)?;
+        msg.writeln(bold(name))?;
+
+        if !gremien.is_empty() {
+            write!(msg, "\n🏛️ ")?;
+            for (i, (name, link)) in gremien.iter().enumerate() {
+                if i > 0 {
+                    msg.write(" | ")?;
+                }
+
+                if let Some(link) = link {
+                    msg.write(text_link(link, name))?;
+                } else {
+                    msg.write(name)?;
+                }
+            }
+        }
+
+        write!(msg, "\n🕒 {}", format_start(start))?;
+
+        if let Some(location) = &location {
+            write!(msg, "\n📍 {location}")?;
+        }
+
+        if !meeting.agenda_item.is_empty() {
+            msg.write("\n\n")?;
+            msg.write(bold("Tagesordnung"))?;
+
+            // the agenda can be very long, so it will be truncated if necessary
+            msg.set_char_limit(CHAR_LIMIT - TRUNCATION_MARKER.len() - 1);
+            let mut truncated = false;
+
+            for item in &meeting.agenda_item {
+                let Some(item_name) = &item.name else {
+                    continue;
+                };
+
+                let line = match &item.number {
+                    Some(number) => format!("\n{number} {item_name}"),
+                    None => format!("\n• {item_name}"),
+                };
+
+                if msg.write(line).is_err() {
+                    truncated = true;
+                    break;
+                }
+            }
+
+            msg.set_char_limit(CHAR_LIMIT);
+            if truncated {
+                write!(msg, "\n{TRUNCATION_MARKER}")?;
+            }
+        }
+
+        Ok(())
+    })
+    .to_message();
+
+    let (text, entities) = match message {
+        Ok(m) => m,
+        Err(telegram_message_builder::Error::MessageTooLong) => {
+            log::warn!("Notification message for meeting \"{name}\" would be too long, skipping!");
+            return None;
+        }
+    };
+
+    let mut buttons = vec![];
+    buttons.extend(
+        meeting
+            .web
+            .as_ref()
+            .map(|url| link_button(instance.source.web_label(), url)),
+    );
+    buttons.extend(
+        meeting
+            .invitation
+            .as_ref()
+            .map(|file| link_button("📄 Einladung", &file.access_url)),
+    );
+    let keyboard = InlineKeyboardMarkup::builder()
+        .inline_keyboard(vec![buttons])
+        .build();
+    let request = SendMessageParams::builder()
+        .chat_id(0)
+        .text(text)
+        .entities(entities)
+        .link_preview_options(LinkPreviewOptions::builder().is_disabled(true).build())
+        .reply_markup(ReplyMarkup::InlineKeyboardMarkup(keyboard))
+        .build();
+
+    Some(Message {
+        request,
+        tags,
+        kind: MessageKind::Meeting,
+        audience: Audience::Subscribers,
+        instance: Some(instance.name.clone()),
+        fulltext: None,
+    })
+}
+
+pub async fn send_notifications(
+    db: &mut DatabaseConnection,
+    instance: &Instance,
+    http_client: &Client,
+    meetings: &[Meeting],
+) -> Result<(), Error> {
+    let now = Utc::now();
+
+    // collect items to BTreeMap to ensure ascending order
+    let mut meetings_map: BTreeMap<(DateTime<FixedOffset>, String), &Meeting> = BTreeMap::new();
+    for meeting in meetings {
+        let (Some(id), Some(start)) = (instance.source.object_id(&meeting.id), meeting.start)
+        else {
+            log::warn!("Link deviates from usual pattern, skipping: {}", meeting.id);
+            continue;
+        };
+
+        if start < now {
+            // changes to meetings that have already taken place (e.g. uploaded protocols)
+            // are of no interest here
+            continue;
+        }
+
+        if !db
+            .is_known_item(
+                &instance.name,
+                KnownItems::Meetings,
+                &known_id(&id, meeting),
+            )
+            .await?
+        {
+            meetings_map.insert((start, id), meeting);
+        }
+    }
+
+    for ((_, id), meeting) in meetings_map {
+        let has_agenda = !meeting.agenda_item.is_empty();
+        let known_id = known_id(&id, meeting);
+
+        let message = if meeting.cancelled == Some(true) {
+            None
+        } else {
+            generate_notification(http_client, instance, meeting).await
+        };
+
+        if let Some(message) = message {
+            let scheduled = db
+                .schedule_broadcast(
+                    &instance.name,
+                    KnownItems::Meetings,
+                    &known_id,
+                    &message,
+                    None,
+                )
+                .await?;
+            if scheduled.is_some() {
+                record_tag_values(db, &message).await;
+            }
+        } else {
+            db.add_known_item(&instance.name, KnownItems::Meetings, &known_id)
+                .await?;
+        }
+
+        if has_agenda {
+            // the agenda notification also serves as announcement
+            db.add_known_item(&instance.name, KnownItems::Meetings, &id)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
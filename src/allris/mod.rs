@@ -1,16 +1,25 @@
+mod allris4;
+mod decision;
+mod fingerprint;
+mod fulltext;
+mod generic;
 mod html;
+mod meeting;
+mod object_cache;
 mod oparl;
+mod source;
 
 use std::collections::BTreeMap;
 use std::pin::pin;
 use std::time::Duration;
 
-use chrono::Utc;
+use bot_utils::Recipient;
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
 use frankenstein::methods::SendMessageParams;
 use frankenstein::types::{
     InlineKeyboardButton, InlineKeyboardMarkup, LinkPreviewOptions, ReplyMarkup,
 };
-use futures_util::{Stream, TryStreamExt};
+use futures_util::TryStreamExt;
 use oparl::{Consultation, Paper, get_organization};
 use reqwest::{Client, Response};
 use telegram_message_builder::{WriteToMessage, bold, from_fn, italic, text_link};
@@ -20,9 +29,14 @@ use tokio_retry::RetryIf;
 use tokio_retry::strategy::ExponentialBackoff;
 use url::Url;
 
-use self::html::{WebsiteData, scrape_website};
-use crate::database::{self, DatabaseConnection};
-use crate::types::{Message, Tag};
+use self::allris4::{Allris, AllrisUrl};
+use self::fingerprint::Fingerprint;
+use self::generic::GenericOParl;
+pub use self::object_cache::init as init_object_cache;
+use self::source::PaperDetails;
+pub use self::source::{InstanceSource, Source};
+use crate::database::{self, DatabaseConnection, KnownItems, UpdateStatus};
+use crate::types::{Audience, Message, MessageKind, Tag, TrackedPaper};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -52,51 +66,6 @@ async fn http_request<T>(
     RetryIf::spawn(retry_strategy, action, retry_condition).await
 }
 
-fn generate_tags(dsnr: Option<&str>, paper: &Paper, data: &WebsiteData) -> Vec<(Tag, String)> {
-    use Tag::*;
-
-    let mut tags = vec![];
-
-    if let Some(title) = paper.name.as_ref() {
-        tags.push((Title, title.clone()))
-    }
-
-    if let Some(dsnr) = dsnr {
-        tags.push((Dsnr, dsnr.to_string()));
-    }
-
-    let WebsiteData {
-        unterstuetzer,
-        amt,
-        gremien,
-        beteiligt,
-        ..
-    } = data;
-
-    if let Some(paper_type) = &paper.paper_type {
-        tags.push((Art, paper_type.clone()));
-    }
-
-    for verfasser in unterstuetzer {
-        tags.push((Verfasser, verfasser.clone()));
-    }
-
-    if let Some(amt) = amt {
-        tags.push((Federführend, amt.clone()));
-        tags.push((Beteiligt, amt.clone()));
-    }
-
-    for amt in beteiligt {
-        tags.push((Beteiligt, amt.clone()))
-    }
-
-    for gremium in gremien {
-        tags.push((Gremium, gremium.0.clone()));
-    }
-
-    tags
-}
-
 async fn get_gremien(
     client: &Client,
     consultation: &[Consultation],
@@ -114,32 +83,48 @@ async fn get_gremien(
     Ok(gremien)
 }
 
-/// generates a notification message for the given `Paper`, complemented with information
-/// from the document's web page. Might return `None` if the document appears to be old.
-async fn generate_notification(client: &Client, paper: &Paper) -> Option<Message> {
-    let title = paper.name.as_deref()?;
-    let dsnr = paper.reference.as_deref();
-    let url = paper.web.as_ref()?;
-
-    let data = scrape_website(client, url).await;
+fn link_button(text: &str, url: &Url) -> InlineKeyboardButton {
+    InlineKeyboardButton::builder()
+        .text(text)
+        .url(url.to_string())
+        .build()
+}
 
-    let data = match data {
-        Ok(data) => data,
+/// Returns `None` if the organizations can't be retrieved. An incomplete fingerprint would
+/// report changes that didn't happen, so the fingerprint update is skipped in that case.
+async fn fingerprint(client: &Client, paper: &Paper) -> Option<Fingerprint> {
+    let organizations = match get_gremien(client, &paper.consultation).await {
+        Ok(gremien) => gremien.into_iter().map(|(name, _, _)| name).collect(),
         Err(e) => {
-            log::warn!("Couldn't scrape website: {e}");
-            WebsiteData::default()
+            log::warn!("Unable to get consultation info for {}: {e}", paper.id);
+            return None;
         }
     };
 
-    let tags = generate_tags(dsnr, paper, &data);
+    Some(Fingerprint::new(paper, organizations))
+}
 
-    let WebsiteData {
-        verfasser,
-        amt,
+/// generates a notification message for the given `Paper`, complemented with information
+/// from the instance's source. Might return `None` if the document appears to be old.
+///
+/// If `changes` are given, the message announces an update of an already known paper.
+/// If `historical` is set, old documents are announced as well.
+async fn generate_notification(
+    client: &Client,
+    instance: &Instance,
+    paper: &Paper,
+    changes: Option<&[String]>,
+    historical: bool,
+) -> Option<Message> {
+    let title = paper.name.as_deref()?;
+    let dsnr = paper.reference.as_deref();
+
+    let PaperDetails {
+        mut tags,
+        author,
         gremien,
         already_discussed,
-        ..
-    } = data;
+    } = instance.source.paper_details(client, paper).await;
 
     let gremien = match get_gremien(client, &paper.consultation).await {
         Ok(gr) if !gr.is_empty() => gr,
@@ -150,32 +135,31 @@ async fn generate_notification(client: &Client, paper: &Paper) -> Option<Message
         }
     };
 
-    if already_discussed {
+    if already_discussed && changes.is_none() && !historical {
         // was already discussed, probably old document, skipping
         log::info!("Skipping {dsnr:?} ({title}): was already discussed");
         return None;
     }
 
-    let verfasser = match (paper.paper_type.as_deref(), &verfasser, &amt) {
-        (Some("Anregungen und Beschwerden" | "Einwohnerfrage" | "Informationsbrief"), _, _) => {
-            // author is meaningless here, it's always the same Amt.
-            None
-        }
-        (Some("Stellungnahme der Verwaltung"), _, Some(amt)) => Some(amt),
-        (_, Some(verfasser), _) => Some(verfasser),
-        (_, None, Some(amt)) => Some(amt),
-        _ => None,
-    };
+    if let Some(file) = &paper.main_file
+        && let Some(text) = fulltext::extract_text(client, &file.access_url).await
+    {
+        tags.push((Tag::Volltext, text));
+    }
 
     let message = from_fn(|msg| {
+        if changes.is_some() {
+            msg.write("🔄 Aktualisiert: ")?;
+        }
+
         msg.writeln(bold(title))?;
 
         if let Some(paper_type) = paper.paper_type.as_deref() {
             write!(msg, "\n📌 {paper_type}")?;
         }
 
-        if let Some(verfasser) = verfasser {
-            write!(msg, "\n👤 {verfasser}")?;
+        if let Some(author) = &author {
+            write!(msg, "\n👤 {author}")?;
         }
 
         if !gremien.is_empty() {
@@ -205,6 +189,13 @@ async fn generate_notification(client: &Client, paper: &Paper) -> Option<Message
             write!(msg, "\n📎 Ds.-Nr. {dsnr}")?;
         }
 
+        if let Some(changes) = changes {
+            msg.write("\n")?;
+            for change in changes {
+                write!(msg, "\n• {change}")?;
+            }
+        }
+
         Ok(())
     })
     .to_message();
@@ -217,19 +208,18 @@ async fn generate_notification(client: &Client, paper: &Paper) -> Option<Message
         }
     };
 
-    let create_button = |text: &str, url: &Url| {
-        InlineKeyboardButton::builder()
-            .text(text)
-            .url(url.to_string())
-            .build()
-    };
-
-    let mut buttons = vec![create_button("🌐 Allris", url)];
+    let mut buttons = vec![];
+    buttons.extend(
+        paper
+            .web
+            .as_ref()
+            .map(|url| link_button(instance.source.web_label(), url)),
+    );
     buttons.extend(
         paper
             .main_file
             .as_ref()
-            .map(|file| create_button("📄 PDF", &file.access_url)),
+            .map(|file| link_button("📄 PDF", &file.access_url)),
     );
     let keyboard = InlineKeyboardMarkup::builder()
         .inline_keyboard(vec![buttons])
@@ -242,99 +232,491 @@ async fn generate_notification(client: &Client, paper: &Paper) -> Option<Message
         .reply_markup(ReplyMarkup::InlineKeyboardMarkup(keyboard))
         .build();
 
-    Some(Message { request, tags })
+    let kind = if changes.is_some() {
+        MessageKind::PaperUpdate
+    } else {
+        MessageKind::Paper
+    };
+
+    Some(Message {
+        request,
+        tags,
+        kind,
+        audience: Audience::Subscribers,
+        instance: Some(instance.name.clone()),
+        fulltext: None,
+    })
 }
 
-async fn send_notifications(
+/// Compares a known paper to its stored fingerprint and announces relevant changes
+async fn check_for_changes(
     db: &mut DatabaseConnection,
-    http_client: Client,
-    papers: impl Stream<Item = Result<Paper, Error>>,
+    instance: &Instance,
+    http_client: &Client,
+    volfdnr: &str,
+    paper: &Paper,
 ) -> Result<(), Error> {
-    // if operations fail, it is ok to abort the whole function (`?` operator).
-    // If redis or network connection is down, we'll just have to try again on a later invocation.
+    let Some(current) = fingerprint(http_client, paper).await else {
+        // checked again on the paper's next modification
+        return Ok(());
+    };
+    let serialized = serde_json::to_string(&current).map_err(database::Error::from)?;
+    let stored = db.get_fingerprint(&instance.name, volfdnr).await?;
+
+    let previous: Option<Fingerprint> = stored
+        .as_deref()
+        .and_then(|stored| serde_json::from_str(stored).ok());
+
+    let message = match &previous {
+        Some(previous) => {
+            let changes = current.changes_since(previous);
+            if changes.is_empty() {
+                return Ok(());
+            }
 
-    // collect items to BTreeMap to ensure ascending order
-    let mut papers_map: BTreeMap<String, Paper> = BTreeMap::new();
-    let mut papers = pin!(papers);
-    while let Some(paper) = papers.try_next().await? {
-        match paper.id.query_pairs().find(|(q, _)| q == "id") {
-            Some((_, volfdnr)) => {
-                if !db.is_known_volfdnr(&volfdnr).await? {
-                    papers_map.insert(volfdnr.to_string(), paper);
-                }
+            log::info!("Paper {volfdnr} has changed: {changes:?}");
+            generate_notification(http_client, instance, paper, Some(&changes), false).await
+        }
+        // papers announced before fingerprints were introduced are silently updated
+        None => None,
+    };
+
+    db.update_fingerprint(
+        &instance.name,
+        volfdnr,
+        stored.as_deref(),
+        &serialized,
+        message.as_ref(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Records the values of the message's tags, so that they are suggested when creating
+/// rules. Missing a value does no harm, so errors don't abort the update.
+async fn record_tag_values(db: &mut DatabaseConnection, message: &Message) {
+    if let Err(e) = db.record_tag_values(&message.tags, Utc::now()).await {
+        log::warn!("Unable to record tag values: {e}");
+    }
+}
+
+/// Schedules the notification for a new paper, if any, and marks the paper as known
+async fn announce_paper(
+    db: &mut DatabaseConnection,
+    instance: &Instance,
+    http_client: &Client,
+    volfdnr: &str,
+    paper: &Paper,
+    message: Option<&Message>,
+) -> Result<(), Error> {
+    if let Some(message) = message {
+        // the consultations are watched, so that the recipients can be informed
+        // about the results later on
+        let consultations: Vec<String> = paper
+            .consultation
+            .iter()
+            .filter_map(|c| c.id.as_ref().map(Url::to_string))
+            .collect();
+        let tracked = TrackedPaper {
+            volfdnr: volfdnr.to_string(),
+            title: paper.name.clone().unwrap_or_default(),
+            reference: paper.reference.clone(),
+        };
+
+        // this will schedule the notification message and at the same time (atomically)
+        // add the volfdnr to the list of already handled volfdnrs.
+        let scheduled = db
+            .schedule_broadcast(
+                &instance.name,
+                KnownItems::Papers,
+                volfdnr,
+                message,
+                Some((&consultations, &tracked)),
+            )
+            .await?;
+
+        // the values are offered as input when creating rules. They are only counted once,
+        // even if the update is retried.
+        if scheduled.is_some() {
+            record_tag_values(db, message).await;
+        }
+    } else {
+        db.add_known_item(&instance.name, KnownItems::Papers, volfdnr)
+            .await?;
+    }
+
+    // the fingerprint serves as reference to detect future changes. Without one, the next
+    // change only stores the fingerprint, just like for papers announced before fingerprints
+    if let Some(current) = fingerprint(http_client, paper).await {
+        let serialized = serde_json::to_string(&current).map_err(database::Error::from)?;
+        db.update_fingerprint(&instance.name, volfdnr, None, &serialized, None)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Orders the papers of a page by their id. The ascending order only holds within a page:
+/// the pages are processed one after another, so that an interrupted update can be resumed
+/// from the last processed page instead of downloading the whole window again.
+fn by_object_id(instance: &Instance, papers: Vec<Paper>) -> BTreeMap<String, Paper> {
+    let mut ordered = BTreeMap::new();
+    for paper in papers {
+        match instance.source.object_id(&paper.id) {
+            Some(volfdnr) => {
+                ordered.insert(volfdnr, paper);
             }
             None => {
                 log::warn!("Link deviates from usual pattern, skipping: {}", paper.id);
             }
         }
     }
+    ordered
+}
 
-    for (volfdnr, paper) in papers_map {
-        if let Some(message) = generate_notification(&http_client, &paper).await {
-            // this will schedule the notification message and at the same time (atomically)
-            // add the volfdnr to the list of already handled volfdnrs.
-            db.schedule_broadcast(&volfdnr, &message).await?;
+/// Announces the new papers of a page and checks the known ones for changes
+async fn send_notifications(
+    db: &mut DatabaseConnection,
+    instance: &Instance,
+    http_client: &Client,
+    papers: Vec<Paper>,
+) -> Result<(), Error> {
+    // if operations fail, it is ok to abort the whole function (`?` operator).
+    // If redis or network connection is down, we'll just have to try again on a later invocation.
+
+    let mut papers_map: BTreeMap<String, Paper> = BTreeMap::new();
+    let mut known_papers: BTreeMap<String, Paper> = BTreeMap::new();
+    for (volfdnr, paper) in by_object_id(instance, papers) {
+        if !db
+            .is_known_item(&instance.name, KnownItems::Papers, &volfdnr)
+            .await?
+        {
+            papers_map.insert(volfdnr, paper);
         } else {
-            db.add_known_volfdnr(&volfdnr).await?;
+            known_papers.insert(volfdnr, paper);
         }
     }
 
+    for (volfdnr, paper) in known_papers {
+        check_for_changes(db, instance, http_client, &volfdnr, &paper).await?;
+    }
+
+    for (volfdnr, paper) in papers_map {
+        let message = generate_notification(http_client, instance, &paper, None, false).await;
+        announce_paper(
+            db,
+            instance,
+            http_client,
+            &volfdnr,
+            &paper,
+            message.as_ref(),
+        )
+        .await?;
+    }
+
     Ok(())
 }
 
-pub async fn do_update(
-    allris_url: &AllrisUrl,
-    db_conn: &mut DatabaseConnection,
-) -> Result<(), Error> {
-    let Some(last_updated) = db_conn.get_last_update().await? else {
+/// Field of the update progress holding the start time of the update
+const PROGRESS_STARTED: &str = "started";
+
+pub async fn do_update(instance: &Instance, db_conn: &mut DatabaseConnection) -> Result<(), Error> {
+    let Some(last_updated) = db_conn.get_last_update(&instance.name).await? else {
         // the very first invocation :) save the timestamp but do nothing yet
-        db_conn.set_last_update(Utc::now()).await?;
+        db_conn.set_last_update(&instance.name, Utc::now()).await?;
         return Ok(());
     };
 
-    let update_started = Utc::now();
+    // an interrupted update is resumed, so that large windows (e.g. after downtime)
+    // don't have to be downloaded over and over again
+    let progress = db_conn.get_update_progress(&instance.name).await?;
+    let started = progress
+        .get(PROGRESS_STARTED)
+        .and_then(|t| t.parse().ok())
+        .and_then(DateTime::from_timestamp_millis);
+    let update_started = match started {
+        Some(started) => {
+            log::info!("Resuming update of {} started at {started}", instance.name);
+            started
+        }
+        None => {
+            let now = Utc::now();
+            let millis = now.timestamp_millis().to_string();
+            db_conn
+                .set_update_progress(&instance.name, PROGRESS_STARTED, &millis)
+                .await?;
+            now
+        }
+    };
+
     let http_client = reqwest::Client::new();
-    let papers = oparl::get_update(&http_client, allris_url, last_updated);
-    send_notifications(db_conn, http_client, papers).await?;
-    db_conn.set_last_update(update_started).await?;
+    for list in instance.source.paper_lists(&http_client).await? {
+        let url = match progress.get(list.as_str()) {
+            Some(next) if next.is_empty() => continue, // already completed
+            Some(next) if started.is_some() => Url::parse(next)?,
+            _ => oparl::papers_url(&list, last_updated, None),
+        };
+
+        let mut pages = pin!(oparl::get_paper_pages(&http_client, url, last_updated));
+        while let Some(page) = pages.try_next().await? {
+            send_notifications(db_conn, instance, &http_client, page.items).await?;
+
+            let next = page.next.as_ref().map_or("", Url::as_str);
+            db_conn
+                .set_update_progress(&instance.name, list.as_str(), next)
+                .await?;
+        }
+    }
+
+    let meetings: Vec<_> = instance
+        .source
+        .meetings(&http_client, last_updated)
+        .try_collect()
+        .await?;
+    meeting::send_notifications(db_conn, instance, &http_client, &meetings).await?;
+    decision::send_notifications(db_conn, instance, &http_client, &meetings).await?;
+    db_conn
+        .finish_update(&instance.name, update_started)
+        .await?;
 
     Ok(())
 }
 
-/// Represents the url to an Allris instance
+/// Parameters for announcing the papers of a past period, e.g. after an outage or for a new chat
 #[derive(Debug, Clone)]
-pub struct AllrisUrl {
-    // invariant: the url has a trailing backslash
-    url: Url,
+pub struct Backfill {
+    /// first day of the period (UTC)
+    pub from: NaiveDate,
+    /// last day of the period (UTC), inclusive
+    pub until: NaiveDate,
+    /// whether papers that have already been announced are sent again
+    pub include_known: bool,
+    /// if given, the papers are only sent to this chat instead of all matching chats
+    pub chat: Option<Recipient>,
 }
 
-impl AllrisUrl {
-    pub fn parse(input: &str) -> Result<Self, url::ParseError> {
-        let mut url = Url::parse(input)?;
+/// Schedules notifications for the papers modified in the given period and returns their
+/// number. The regular updates are not affected, apart from new papers becoming known.
+pub async fn backfill(
+    instance: &Instance,
+    db_conn: &mut DatabaseConnection,
+    backfill: &Backfill,
+) -> Result<usize, Error> {
+    let since = backfill.from.and_time(NaiveTime::MIN).and_utc();
+    let until = (backfill.until + Days::new(1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
 
-        let path = url.path();
-        if !path.ends_with("/") {
-            url.set_path(&format!("{path}/"));
+    let http_client = reqwest::Client::new();
+    let mut papers = vec![];
+    for list in instance.source.paper_lists(&http_client).await? {
+        let url = oparl::papers_url(&list, since, Some(until));
+        let pages = oparl::get_paper_pages(&http_client, url, since);
+        papers.extend(pages.map_ok(|page| page.items).try_concat().await?);
+    }
+
+    let mut papers_map: BTreeMap<String, (Paper, bool)> = BTreeMap::new();
+    for paper in papers {
+        let Some(volfdnr) = instance.source.object_id(&paper.id) else {
+            log::warn!("Link deviates from usual pattern, skipping: {}", paper.id);
+            continue;
+        };
+
+        let known = db_conn
+            .is_known_item(&instance.name, KnownItems::Papers, &volfdnr)
+            .await?;
+        if !known || backfill.include_known {
+            papers_map.insert(volfdnr, (paper, known));
         }
+    }
+
+    let mut scheduled = 0;
+    for (volfdnr, (paper, known)) in papers_map {
+        let message = generate_notification(&http_client, instance, &paper, None, true).await;
+
+        match (message, backfill.chat) {
+            (Some(mut message), Some(chat)) => {
+                message.audience = Audience::Chat(chat);
+                db_conn.schedule_message(&message).await?;
+            }
+            (Some(message), None) if known => {
+                db_conn.schedule_message(&message).await?;
+            }
+            // a belated announcement, just like during the regular updates
+            (Some(message), None) => {
+                announce_paper(
+                    db_conn,
+                    instance,
+                    &http_client,
+                    &volfdnr,
+                    &paper,
+                    Some(&message),
+                )
+                .await?;
+            }
+            (None, _) => continue,
+        }
+
+        scheduled += 1;
+    }
+
+    Ok(scheduled)
+}
+
+/// A council information system watched by the scraper. The name is shown to users and
+/// identifies the instance in the database and in filters, so it should not be changed later on.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub name: String,
+    pub source: InstanceSource,
+}
+
+/// Splits `NAME=URL` into its parts. If the name is omitted, the host name serves as name.
+fn parse_named_url(input: &str) -> Result<(String, Url), String> {
+    let (name, url) = match input.split_once('=') {
+        Some((name, url)) if !name.contains("://") => (Some(name.trim()), url),
+        _ => (None, input),
+    };
+
+    let url = Url::parse(url).map_err(|e| e.to_string())?;
+    let name = match name {
+        Some("") => return Err("Name must not be empty".into()),
+        Some(name) => name.to_string(),
+        None => url.host_str().ok_or("URL has no host")?.to_string(),
+    };
 
-        Ok(Self { url })
+    Ok((name, url))
+}
+
+impl Instance {
+    /// Parses the url of an Allris 4 instance, optionally preceded by `NAME=`
+    pub fn parse_allris(input: &str) -> Result<Self, String> {
+        let (name, url) = parse_named_url(input)?;
+        let url = AllrisUrl::parse(url.as_str()).map_err(|e| e.to_string())?;
+        let source = InstanceSource::Allris(Allris::new(url));
+
+        Ok(Self { name, source })
+    }
+
+    /// Parses the url of an OParl `System` object, optionally preceded by `NAME=`
+    pub fn parse_oparl(input: &str) -> Result<Self, String> {
+        let (name, url) = parse_named_url(input)?;
+        let source = InstanceSource::OParl(GenericOParl::new(url));
+
+        Ok(Self { name, source })
     }
 }
 
 /// Regularly checks for new documents, generates notification messages and stores them in the database
-pub async fn scraper(allris_url: AllrisUrl, update_interval: Duration, db: redis::Client) {
+pub async fn scraper(instances: Vec<Instance>, update_interval: Duration, db: redis::Client) {
     let mut interval = interval(update_interval);
     interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut migrated = false;
 
     loop {
         interval.tick().await;
 
-        log::info!("Updating ...");
         let db_timeout = Some(Duration::from_secs(10));
         let mut db_conn = DatabaseConnection::new(db.clone(), db_timeout);
-        match do_update(&allris_url, &mut db_conn).await {
-            Ok(()) => log::info!("Update finished!"),
-            Err(e) => log::error!("Update failed: {e}"),
+
+        if !migrated {
+            // the state from before multiple instances were supported belongs to the first one
+            let first = &instances[0].name;
+            match db_conn.migrate_legacy_keys(first).await {
+                Ok(()) => migrated = true,
+                Err(e) => {
+                    log::error!("Migrating database keys failed: {e}");
+                    continue;
+                }
+            }
         }
+
+        for instance in &instances {
+            log::info!("Updating {} ...", instance.name);
+            let result = do_update(instance, &mut db_conn).await;
+            match &result {
+                Ok(()) => log::info!("Update of {} finished!", instance.name),
+                Err(e) => log::error!("Update of {} failed: {e}", instance.name),
+            }
+
+            // shown to the owner by `/statistik`
+            let status = UpdateStatus {
+                finished: Utc::now(),
+                error: result.err().map(|e| e.to_string()),
+            };
+            if let Err(e) = db_conn.set_update_status(&instance.name, &status).await {
+                log::warn!("Unable to record the status of the update: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::oparl::Paper;
+    use super::source::Source;
+    use super::{Instance, InstanceSource, by_object_id};
+
+    #[test]
+    fn test_parse_instance() {
+        let instance =
+            Instance::parse_allris("Stadt Bonn=https://www.bonn.sitzung-online.de").unwrap();
+        assert_eq!(instance.name, "Stadt Bonn");
+        assert_eq!(
+            instance.source.website().map(Url::as_str),
+            Some("https://www.bonn.sitzung-online.de/")
+        );
+
+        let instance = Instance::parse_allris("https://www.bonn.sitzung-online.de/?a=b").unwrap();
+        assert_eq!(instance.name, "www.bonn.sitzung-online.de");
+
+        let instance = Instance::parse_oparl("https://sessionnet.example.org/oparl").unwrap();
+        assert!(matches!(instance.source, InstanceSource::OParl(_)));
+
+        assert!(Instance::parse_allris("=https://www.bonn.sitzung-online.de/").is_err());
+        assert!(Instance::parse_allris("Bonn=kein Link").is_err());
+    }
+
+    #[test]
+    fn test_object_id() {
+        let allris = Instance::parse_allris("https://www.bonn.sitzung-online.de/").unwrap();
+        let oparl = Instance::parse_oparl("https://sessionnet.example.org/oparl").unwrap();
+
+        let url = Url::parse("https://www.bonn.sitzung-online.de/oparl/papers?id=2025-01").unwrap();
+        assert_eq!(allris.source.object_id(&url).as_deref(), Some("2025-01"));
+
+        let url = Url::parse("https://sessionnet.example.org/oparl/paper/4711").unwrap();
+        assert_eq!(
+            oparl.source.object_id(&url).as_deref(),
+            Some("sessionnet.example.org/oparl/paper/4711")
+        );
+    }
+
+    #[test]
+    fn test_page_order() {
+        let instance = Instance::parse_allris("https://www.bonn.sitzung-online.de/").unwrap();
+        let page = |ids: &[&str]| -> Vec<Paper> {
+            ids.iter()
+                .map(|id| {
+                    let url = format!("https://www.bonn.sitzung-online.de/oparl/papers?id={id}");
+                    serde_json::from_value(serde_json::json!({ "id": url, "deleted": false }))
+                        .unwrap()
+                })
+                .collect()
+        };
+
+        // the papers of a page are announced in ascending order, but a later page may
+        // contain older papers, as each page is completed before the next one is loaded
+        let first = by_object_id(&instance, page(&["2025-03", "2025-01"]));
+        let second = by_object_id(&instance, page(&["2024-09", "2025-02"]));
+        let announced: Vec<&str> = first
+            .keys()
+            .chain(second.keys())
+            .map(String::as_str)
+            .collect();
+        assert_eq!(announced, ["2025-01", "2025-03", "2024-09", "2025-02"]);
     }
 }
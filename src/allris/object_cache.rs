@@ -0,0 +1,142 @@
+//! Caches OParl objects that rarely change, e.g. organizations, in the database, with an
+//! in-process LRU cache in front. Outdated entries are still used, but refreshed in the
+//! background, so that generating notifications doesn't have to wait for the api.
+
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{Error, http_request};
+use crate::database::{DatabaseConnection, SharedDatabaseConnection};
+use crate::lru_cache::{Lru, LruCache};
+
+/// Entries older than this are refreshed in the background
+const REFRESH_AFTER: Duration = Duration::days(3);
+
+/// Entries older than this are discarded
+const MAX_AGE: Duration = Duration::days(30);
+
+static DATABASE: OnceLock<SharedDatabaseConnection> = OnceLock::new();
+
+/// Enables the database layer of all object caches. Without it, objects are cached in memory only.
+pub fn init(db: redis::Client) {
+    let db = DatabaseConnection::new(db, Some(std::time::Duration::from_secs(5)));
+    let _ = DATABASE.set(db.into_shared());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry<T> {
+    fetched: DateTime<Utc>,
+    object: T,
+}
+
+impl<T> Entry<T> {
+    fn age(&self) -> Duration {
+        Utc::now() - self.fetched
+    }
+}
+
+pub struct ObjectCache<T> {
+    /// distinguishes the objects in the database
+    namespace: &'static str,
+    memory: LruCache<Url, Entry<T>>,
+    refreshing: Mutex<HashSet<Url>>,
+}
+
+impl<T> ObjectCache<T>
+where
+    T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    pub fn new(namespace: &'static str, capacity: usize) -> Self {
+        Self {
+            namespace,
+            memory: LruCache::new(Lru::new(capacity)),
+            refreshing: Mutex::default(),
+        }
+    }
+
+    async fn load_stored(&self, id: &Url) -> Option<Entry<T>> {
+        let db = DATABASE.get()?;
+
+        let stored = match db.get_cached_object(self.namespace, id.as_str()).await {
+            Ok(stored) => stored?,
+            Err(e) => {
+                log::warn!("Unable to read cached object {id}: {e}");
+                return None;
+            }
+        };
+
+        match serde_json::from_str::<Entry<T>>(&stored) {
+            Ok(entry) if entry.age() < MAX_AGE => Some(entry),
+            result => {
+                if let Err(e) = result {
+                    log::warn!("Discarding invalid cached object {id}: {e}");
+                }
+                let _ = db.remove_cached_object(self.namespace, id.as_str()).await;
+                None
+            }
+        }
+    }
+
+    async fn fetch(&self, client: &Client, id: &Url) -> Result<Entry<T>, Error> {
+        let object = http_request(client, id, Response::json).await?;
+        let entry = Entry {
+            fetched: Utc::now(),
+            object,
+        };
+
+        if let Some(db) = DATABASE.get() {
+            let stored = serde_json::to_string(&entry).expect("serialization doesn't fail");
+            if let Err(e) = db.cache_object(self.namespace, id.as_str(), &stored).await {
+                log::warn!("Unable to cache object {id}: {e}");
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// Fetches the object again, unless this is already happening
+    fn refresh(&'static self, client: &Client, id: &Url) {
+        if !self.refreshing.lock().unwrap().insert(id.clone()) {
+            return;
+        }
+
+        let client = client.clone();
+        let id = id.clone();
+
+        tokio::spawn(async move {
+            match self.fetch(&client, &id).await {
+                Ok(entry) => self.memory.insert(id.clone(), entry).await,
+                Err(e) => log::warn!("Unable to refresh {id}: {e}"),
+            }
+
+            self.refreshing.lock().unwrap().remove(&id);
+        });
+    }
+
+    /// Returns the object, preferably from one of the caches
+    pub async fn get(&'static self, client: &Client, id: &Url) -> Result<T, Error> {
+        let entry = self
+            .memory
+            .get_if_valid(
+                id.clone(),
+                |entry| entry.age() < MAX_AGE,
+                async || match self.load_stored(id).await {
+                    Some(entry) => Ok(entry),
+                    None => self.fetch(client, id).await,
+                },
+            )
+            .await?;
+
+        if entry.age() > REFRESH_AFTER {
+            self.refresh(client, id);
+        }
+
+        Ok(entry.object.clone())
+    }
+}
@@ -1,22 +1,23 @@
 use std::future::ready;
 use std::sync::LazyLock;
 
-use chrono::{DateTime, Days, Duration, NaiveDate, SecondsFormat, TimeZone, Utc};
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, SecondsFormat, TimeZone, Utc};
 use futures_util::{Stream, TryStreamExt};
 use reqwest::Response;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use url::Url;
 
-use super::{AllrisUrl, Error};
+use super::Error;
+use super::object_cache::ObjectCache;
 use crate::allris::http_request;
-use crate::lru_cache::{Lru, LruCache};
 
 /// Caches calls to the api's `organization` endpoints, as these information will
 /// rarely change.
-static ORGANIZATIONS: LazyLock<LruCache<Url, (DateTime<Utc>, Organization)>> =
-    LazyLock::new(|| LruCache::new(Lru::new(50)));
+static ORGANIZATIONS: LazyLock<ObjectCache<Organization>> =
+    LazyLock::new(|| ObjectCache::new("organizations", 500));
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +30,7 @@ pub struct Organization {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Consultation {
+    pub id: Option<Url>,
     pub role: Option<String>,
     pub authoritative: Option<bool>,
     #[serde(default)]
@@ -40,6 +42,7 @@ pub struct Consultation {
 #[serde(rename_all = "camelCase")]
 pub struct File {
     pub access_url: Url,
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -49,17 +52,91 @@ pub struct Paper {
     pub name: Option<String>,
     pub reference: Option<String>,
     pub main_file: Option<File>,
+    #[serde(default)]
+    pub auxiliary_file: Vec<File>,
     pub date: Option<NaiveDate>,
     pub paper_type: Option<String>,
     pub web: Option<Url>,
     #[serde(default)]
     pub consultation: Vec<Consultation>,
+    #[serde(default)]
+    pub originator_organization: Vec<Url>,
+    #[serde(default)]
+    pub under_direction_of: Vec<Url>,
+    pub deleted: bool,
+}
+
+/// The entry point of an OParl server
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct System {
+    pub id: Url,
+    pub body: Url,
+    pub website: Option<Url>,
+}
+
+/// A municipality or other body, whose lists are offered by the server
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Body {
+    pub id: Url,
+    pub name: Option<String>,
+    pub paper: Url,
+    pub meeting: Url,
+    pub website: Option<Url>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub description: Option<String>,
+    pub street_address: Option<String>,
+    pub room: Option<String>,
+    pub locality: Option<String>,
+}
+
+/// The `location` field of a meeting is either embedded or only referenced by its url
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MaybeEmbedded<T> {
+    Embedded(T),
+    Reference(Url),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgendaItem {
+    pub id: Url,
+    pub number: Option<String>,
+    pub name: Option<String>,
+    pub public: Option<bool>,
+    pub consultation: Option<Url>,
+    pub result: Option<String>,
+    pub resolution_file: Option<File>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meeting {
+    pub id: Url,
+    pub name: Option<String>,
+    pub cancelled: Option<bool>,
+    pub start: Option<DateTime<FixedOffset>>,
+    pub end: Option<DateTime<FixedOffset>>,
+    pub location: Option<MaybeEmbedded<Location>>,
+    #[serde(default)]
+    pub organization: Vec<Url>,
+    pub invitation: Option<File>,
+    #[serde(default)]
+    pub agenda_item: Vec<AgendaItem>,
+    pub web: Option<Url>,
+    #[serde(default)]
     pub deleted: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-struct Papers {
-    data: Vec<Paper>,
+struct Page<T> {
+    data: Vec<T>,
     #[serde(default)]
     links: Links,
 }
@@ -74,12 +151,13 @@ fn to_rfc3339(t: DateTime<impl TimeZone>) -> String {
     t.to_rfc3339_opts(SecondsFormat::Secs, false)
 }
 
-fn endpoint_url<T: TimeZone>(
-    url: &AllrisUrl,
+/// Restricts a list to the objects modified in the given period
+fn filtered_list_url<T: TimeZone>(
+    list: &Url,
     since: DateTime<T>,
     until: Option<DateTime<T>>,
 ) -> Url {
-    let mut url = url.url.join("oparl/papers").unwrap();
+    let mut url = list.clone();
 
     {
         let mut query_pairs = url.query_pairs_mut();
@@ -94,35 +172,38 @@ fn endpoint_url<T: TimeZone>(
 }
 
 pub async fn get_organization(client: &reqwest::Client, id: &Url) -> Result<Organization, Error> {
-    ORGANIZATIONS
-        .get_if_valid(
-            id.clone(),
-            |(t, _)| Utc::now() - t < Duration::days(3),
-            async || {
-                let r: Organization = http_request(client, id, Response::json).await?;
-                Ok((Utc::now(), r))
-            },
-        )
-        .await
-        .map(|x| x.1.clone())
-}
-
-fn get_papers(
+    ORGANIZATIONS.get(client, id).await
+}
+
+/// A page of a paginated list
+#[derive(Debug)]
+pub struct ListPage<T> {
+    pub items: Vec<T>,
+    /// the url of the next page, if there is one
+    pub next: Option<Url>,
+}
+
+/// Retrieves the pages of a paginated list, following the `next` links
+pub fn get_pages<T: DeserializeOwned + Send + Sync + 'static>(
     client: reqwest::Client,
     url: Url,
-) -> impl Stream<Item = Result<Paper, Error>> + Send + Sync + Unpin + 'static {
-    let (tx, rx) = mpsc::channel::<Result<Vec<Paper>, Error>>(3);
+) -> impl Stream<Item = Result<ListPage<T>, Error>> + Send + Sync + Unpin + 'static {
+    let (tx, rx) = mpsc::channel::<Result<ListPage<T>, Error>>(3);
 
     tokio::spawn(async move {
         let mut next_url = Some(url);
 
         while let Some(url) = next_url {
-            match http_request::<Papers>(&client, &url, Response::json).await {
+            match http_request::<Page<T>>(&client, &url, Response::json).await {
                 Ok(content) => {
-                    if tx.send(Ok(content.data)).await.is_err() {
+                    next_url = content.links.next;
+                    let page = ListPage {
+                        items: content.data,
+                        next: next_url.clone(),
+                    };
+                    if tx.send(Ok(page)).await.is_err() {
                         return;
                     }
-                    next_url = content.links.next;
                 }
                 Err(e) => {
                     let _ = tx.send(Err(e.into())).await;
@@ -133,21 +214,57 @@ fn get_papers(
     });
 
     ReceiverStream::new(rx)
-        .map_ok(|vec| futures_util::stream::iter(vec.into_iter().map(Ok)))
+}
+
+/// Retrieves all objects of a paginated list
+pub fn get_list<T: DeserializeOwned + Send + Sync + 'static>(
+    client: reqwest::Client,
+    url: Url,
+) -> impl Stream<Item = Result<T, Error>> + Send + Sync + Unpin + 'static {
+    get_pages(client, url)
+        .map_ok(|page| futures_util::stream::iter(page.items.into_iter().map(Ok)))
         .try_flatten()
 }
 
-pub fn get_update(
+/// Retrieves a single object, e.g. the `System` or a `Body`
+pub async fn get_object<T: DeserializeOwned>(
     client: &reqwest::Client,
-    url: &AllrisUrl,
+    url: &Url,
+) -> Result<T, Error> {
+    Ok(http_request(client, url, Response::json).await?)
+}
+
+/// The url of the first page of papers modified since `since`, and before `until`, if given
+pub fn papers_url(list: &Url, since: DateTime<Utc>, until: Option<DateTime<Utc>>) -> Url {
+    // include older changes to address possible inaccuracies
+    let since = since - chrono::Duration::hours(2);
+    filtered_list_url(list, since, until)
+}
+
+/// Retrieves the pages of a paper list, starting at `url`, which was obtained from
+/// [`papers_url`] with the same `since`. Deleted papers are omitted.
+pub fn get_paper_pages(
+    client: &reqwest::Client,
+    url: Url,
     since: DateTime<Utc>,
-) -> impl Stream<Item = Result<Paper, Error>> + Send + Sync + Unpin + 'static {
+) -> impl Stream<Item = Result<ListPage<Paper>, Error>> + Send + Sync + Unpin + 'static + use<> {
     // there are sometimes very old papers included. we don't want them
     let oldest_date = (since - Days::new(2)).date_naive();
 
-    // include older changes to address possible inaccuracies
+    get_pages(client.clone(), url).map_ok(move |mut page: ListPage<Paper>| {
+        page.items
+            .retain(|paper| !paper.deleted && paper.date >= Some(oldest_date));
+        page
+    })
+}
+
+/// Retrieves the meetings of the given list that have been modified since `since`
+pub fn get_meetings(
+    client: &reqwest::Client,
+    list: &Url,
+    since: DateTime<Utc>,
+) -> impl Stream<Item = Result<Meeting, Error>> + Send + Sync + Unpin + 'static + use<> {
     let since = since - chrono::Duration::hours(2);
-    let url = endpoint_url(url, since, None);
-    get_papers(client.clone(), url)
-        .try_filter(move |paper| ready(!paper.deleted && paper.date >= Some(oldest_date)))
+    let url = filtered_list_url(list, since, None);
+    get_list(client.clone(), url).try_filter(|meeting: &Meeting| ready(!meeting.deleted))
 }
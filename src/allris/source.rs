@@ -0,0 +1,120 @@
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use futures_util::future::Either;
+use reqwest::Client;
+use url::Url;
+
+use super::Error;
+use super::allris4::Allris;
+use super::generic::GenericOParl;
+use super::oparl::{Meeting, Paper};
+use crate::types::Tag;
+
+/// Information about a paper beyond its OParl object
+#[derive(Debug, Default)]
+pub struct PaperDetails {
+    pub tags: Vec<(Tag, String)>,
+    /// the author to be shown in the notification
+    pub author: Option<String>,
+    /// used if the gremien can't be obtained from the paper's consultations
+    pub gremien: Vec<(String, Option<Url>, bool)>,
+    /// whether the paper has already been discussed, which indicates an old document
+    pub already_discussed: bool,
+}
+
+/// A council information system the scraper retrieves its updates from
+pub trait Source {
+    /// Derives a stable id from the url of an OParl object, used to recognize known items
+    fn object_id(&self, url: &Url) -> Option<String>;
+
+    /// The urls of the OParl lists containing the papers, e.g. one per body
+    fn paper_lists(&self, client: &Client) -> impl Future<Output = Result<Vec<Url>, Error>> + Send;
+
+    /// The meetings modified since the given point in time
+    fn meetings(
+        &self,
+        client: &Client,
+        since: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<Meeting, Error>> + Send + 'static;
+
+    /// Collects the tags and further information about a paper
+    fn paper_details(
+        &self,
+        client: &Client,
+        paper: &Paper,
+    ) -> impl Future<Output = PaperDetails> + Send;
+
+    /// Website of the council information system, if known
+    fn website(&self) -> Option<&Url>;
+
+    /// Label of the buttons linking to the web pages of papers and meetings
+    fn web_label(&self) -> &'static str {
+        "🌐 Website"
+    }
+}
+
+/// Tags that can be derived from the OParl object alone
+pub fn basic_tags(paper: &Paper) -> Vec<(Tag, String)> {
+    let mut tags = vec![];
+    tags.extend(paper.name.clone().map(|title| (Tag::Title, title)));
+    tags.extend(paper.reference.clone().map(|dsnr| (Tag::Dsnr, dsnr)));
+    tags.extend(paper.paper_type.clone().map(|art| (Tag::Art, art)));
+    tags
+}
+
+/// The source of an [`Instance`](super::Instance)
+#[derive(Debug, Clone)]
+pub enum InstanceSource {
+    Allris(Allris),
+    OParl(GenericOParl),
+}
+
+impl Source for InstanceSource {
+    fn object_id(&self, url: &Url) -> Option<String> {
+        match self {
+            Self::Allris(source) => source.object_id(url),
+            Self::OParl(source) => source.object_id(url),
+        }
+    }
+
+    async fn paper_lists(&self, client: &Client) -> Result<Vec<Url>, Error> {
+        match self {
+            Self::Allris(source) => source.paper_lists(client).await,
+            Self::OParl(source) => source.paper_lists(client).await,
+        }
+    }
+
+    fn meetings(
+        &self,
+        client: &Client,
+        since: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<Meeting, Error>> + Send + 'static {
+        match self {
+            Self::Allris(source) => Either::Left(source.meetings(client, since)),
+            Self::OParl(source) => Either::Right(source.meetings(client, since)),
+        }
+    }
+
+    async fn paper_details(&self, client: &Client, paper: &Paper) -> PaperDetails {
+        match self {
+            Self::Allris(source) => source.paper_details(client, paper).await,
+            Self::OParl(source) => source.paper_details(client, paper).await,
+        }
+    }
+
+    fn website(&self) -> Option<&Url> {
+        match self {
+            Self::Allris(source) => source.website(),
+            Self::OParl(source) => source.website(),
+        }
+    }
+
+    fn web_label(&self) -> &'static str {
+        match self {
+            Self::Allris(source) => source.web_label(),
+            Self::OParl(source) => source.web_label(),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use frankenstein::methods::SendMessageParams;
+use serde::{Deserialize, Serialize};
+
+use super::keyboard::{Button, Choice, Choices};
+use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
+use crate::bot::keyboard::remove_keyboard;
+use crate::locale::Locale;
+use crate::types::{AnnouncementTarget, Audience, Message, MessageKind};
+
+pub const COMMAND: Command = Command {
+    name: "ankuendigung",
+
+    group_admin: false,
+    group_member: false,
+    private_chat: false,
+    admin: true,
+    owner: true,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConfirmAnnouncement {
+    target: AnnouncementTarget,
+    text: String,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct ConfirmChoice(bool);
+
+impl<'a> Choice<'a> for ConfirmChoice {
+    type Action = bool;
+
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
+        let text = if self.0 {
+            tr!(locale, "announcement.confirm")
+        } else {
+            tr!(locale, "dialogue.cancel")
+        };
+
+        Button::Text {
+            text: text.into(),
+            action: |x| x.0,
+        }
+    }
+}
+
+fn buttons() -> &'static [ConfirmChoice; 2] {
+    &[ConfirmChoice(true), ConfirmChoice(false)]
+}
+
+/// Splits off the optional target, which is given by the first word
+fn parse_params(param: &str) -> (AnnouncementTarget, &str) {
+    let param = param.trim();
+    let (first, rest) = param.split_once(char::is_whitespace).unwrap_or((param, ""));
+
+    match first.to_lowercase().as_str() {
+        "privat" => (AnnouncementTarget::Private, rest.trim_start()),
+        "gruppen" => (AnnouncementTarget::Groups, rest.trim_start()),
+        _ => (AnnouncementTarget::All, param),
+    }
+}
+
+fn target_label(target: AnnouncementTarget, locale: Locale) -> &'static str {
+    match target {
+        AnnouncementTarget::All => tr!(locale, "announcement.all"),
+        AnnouncementTarget::Private => tr!(locale, "announcement.private"),
+        AnnouncementTarget::Groups => tr!(locale, "announcement.groups"),
+    }
+}
+
+impl ConfirmAnnouncement {
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        cx.reset_dialogue(channel).await?;
+
+        if buttons().match_action(cx.input(), cx.locale) != Some(true) {
+            return respond!(
+                cx,
+                text = tr!(cx.locale, "dialogue.cancelled"),
+                reply_markup = remove_keyboard()
+            )
+            .await;
+        }
+
+        // the chat id is replaced for each recipient
+        let message = Message {
+            request: SendMessageParams::builder()
+                .chat_id(0)
+                .text(self.text)
+                .build(),
+            tags: vec![],
+            kind: MessageKind::Announcement,
+            audience: Audience::Announcement(self.target),
+            instance: None,
+            fulltext: None,
+        };
+        cx.inner.database.schedule_message(&message).await?;
+
+        respond!(
+            cx,
+            text = tr!(cx.locale, "announcement.scheduled"),
+            reply_markup = remove_keyboard()
+        )
+        .await
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    let (target, text) = parse_params(param.unwrap_or_default());
+    if text.is_empty() {
+        let text = tr!(cx.locale, "announcement.usage", command = COMMAND.name);
+        return respond!(cx, text).await;
+    }
+
+    let count = cx
+        .inner
+        .database
+        .get_active_chats()
+        .await?
+        .into_iter()
+        .filter(|&recipient| target.includes(recipient))
+        .count();
+
+    let question = tr!(
+        cx.locale,
+        "announcement.question",
+        count = count,
+        target = target_label(target, cx.locale)
+    );
+    let state = ConfirmAnnouncement {
+        target,
+        text: text.to_string(),
+    };
+
+    let dialogue = cx.get_dialogue().await?;
+    cx.update_dialogue(state, dialogue.channel).await?;
+    respond!(
+        cx,
+        text = format!("{question}\n\n{text}"),
+        reply_markup = buttons().keyboard_markup(cx.locale)
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_params;
+    use crate::types::AnnouncementTarget;
+
+    #[test]
+    fn test_parse_params() {
+        assert_eq!(
+            parse_params("Privat Neue Funktion:\n/werte"),
+            (AnnouncementTarget::Private, "Neue Funktion:\n/werte")
+        );
+        assert_eq!(
+            parse_params("gruppen\nWartung heute Abend"),
+            (AnnouncementTarget::Groups, "Wartung heute Abend")
+        );
+        assert_eq!(
+            parse_params(" Privatchats bleiben privat"),
+            (AnnouncementTarget::All, "Privatchats bleiben privat")
+        );
+        assert_eq!(parse_params("privat"), (AnnouncementTarget::Private, ""));
+    }
+}
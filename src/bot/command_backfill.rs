@@ -0,0 +1,131 @@
+use bot_utils::Recipient;
+use chrono::{NaiveDate, Utc};
+use frankenstein::AsyncTelegramApi;
+use frankenstein::methods::SendMessageParams;
+
+use super::{Command, HandleMessage, HandlerResult};
+use crate::allris::{self, Backfill};
+
+pub const COMMAND: Command = Command {
+    name: "nachladen",
+
+    group_admin: false,
+    group_member: false,
+    private_chat: false,
+    admin: true,
+    owner: true,
+};
+
+fn parse_date(input: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(input, "%d.%m.%Y")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%Y-%m-%d"))
+        .ok()
+}
+
+/// Parses the command's parameters. The papers are sent to `chat`, unless `alle` is given.
+fn parse_params(param: &str, chat: Recipient, today: NaiveDate) -> Option<Backfill> {
+    let mut dates = vec![];
+    let mut all_chats = false;
+    let mut include_known = false;
+
+    for word in param.split_whitespace() {
+        match word.to_lowercase().as_str() {
+            "alle" => all_chats = true,
+            "erneut" => include_known = true,
+            _ => dates.push(parse_date(word)?),
+        }
+    }
+
+    let (from, until) = match dates[..] {
+        [from] => (from, today),
+        [from, until] => (from, until),
+        _ => return None,
+    };
+
+    if from > until {
+        return None;
+    }
+
+    Some(Backfill {
+        from,
+        until,
+        include_known,
+        chat: (!all_chats).then_some(chat),
+    })
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    let dialogue = cx.get_dialogue().await?;
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let today = Utc::now().date_naive();
+
+    let Some(backfill) = param.and_then(|param| parse_params(param, recipient, today)) else {
+        let text = tr!(cx.locale, "backfill.usage", command = COMMAND.name);
+        return respond!(cx, text).await;
+    };
+
+    respond!(cx, text = tr!(cx.locale, "backfill.loading")).await?;
+
+    // this might take a while, so other messages are handled in the meantime
+    let bot = cx.inner.bot.clone();
+    let mut db = cx.inner.database.get_dedicated();
+    let instances = cx.inner.instances.clone();
+    let reply_to = cx.recipient();
+    let locale = cx.locale;
+
+    tokio::spawn(async move {
+        let mut lines = vec![];
+        for instance in &instances {
+            let line = match allris::backfill(instance, &mut db, &backfill).await {
+                Ok(count) => tr!(
+                    locale,
+                    "backfill.scheduled",
+                    instance = instance.name,
+                    count = count
+                ),
+                Err(e) => {
+                    log::warn!("Backfill of {} failed: {e}", instance.name);
+                    tr!(locale, "backfill.failed", instance = instance.name)
+                }
+            };
+            lines.push(line);
+        }
+
+        let params = SendMessageParams::builder()
+            .chat_id(reply_to.chat_id)
+            .maybe_message_thread_id(reply_to.thread_id)
+            .text(lines.join("\n"))
+            .build();
+        if let Err(e) = bot.send_message(&params).await {
+            log::warn!("Unable to report backfill result: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::parse_params;
+
+    #[test]
+    fn test_parse_params() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+
+        let backfill = parse_params("01.03.2025 2025-03-05 erneut", 42.into(), date(20)).unwrap();
+        assert_eq!((backfill.from, backfill.until), (date(1), date(5)));
+        assert!(backfill.include_known);
+        assert_eq!(backfill.chat, Some(42.into()));
+
+        let backfill = parse_params("3.3.2025 alle", 42.into(), date(20)).unwrap();
+        assert_eq!((backfill.from, backfill.until), (date(3), date(20)));
+        assert!(!backfill.include_known);
+        assert_eq!(backfill.chat, None);
+
+        assert!(parse_params("05.03.2025 01.03.2025", 42.into(), date(20)).is_none());
+        assert!(parse_params("gestern", 42.into(), date(20)).is_none());
+        assert!(parse_params("", 42.into(), date(20)).is_none());
+    }
+}
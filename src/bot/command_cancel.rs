@@ -3,12 +3,12 @@ use super::{Command, DialogueState, HandleMessage, HandlerResult};
 
 pub const COMMAND: Command = Command {
     name: "abbrechen",
-    description: "Brich den aktuellen Vorgang ab",
 
     group_admin: true,
     group_member: true,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
@@ -16,9 +16,9 @@ pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerRe
 
     let text = if dialogue.state != DialogueState::default() {
         cx.reset_dialogue(dialogue.channel).await?;
-        "Befehl wurde abgebrochen!"
+        tr!(cx.locale, "cancel.done")
     } else {
-        "Es war kein Befehl aktiv"
+        tr!(cx.locale, "cancel.inactive")
     };
 
     respond!(cx, text, reply_markup = remove_keyboard()).await
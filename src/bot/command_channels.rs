@@ -0,0 +1,206 @@
+use bot_utils::Recipient;
+use frankenstein::AsyncTelegramApi;
+use frankenstein::methods::GetChatParams;
+use serde::{Deserialize, Serialize};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold};
+
+use super::keyboard::{Button, Choice, Choices, remove_keyboard};
+use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
+use crate::locale::Locale;
+
+pub const COMMAND: Command = Command {
+    name: "kanaele",
+
+    group_admin: false,
+    group_member: false,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChannelListSelection {
+    channels: Vec<SelectedChannel>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ChannelChoice<'a> {
+    PrivateChat,
+    /// the channel with its position in the list
+    Channel(usize, &'a SelectedChannel),
+}
+
+impl<'a> Choice<'a> for ChannelChoice<'a> {
+    type Action = Option<&'a SelectedChannel>;
+
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
+        let text = match self {
+            Self::PrivateChat => tr!(locale, "target.button.this_chat").to_string(),
+            Self::Channel(i, channel) => format!("{}. {}", i + 1, channel_name(channel, locale)),
+        };
+
+        Button::Text {
+            text: text.into(),
+            action: |choice| match choice {
+                Self::PrivateChat => None,
+                Self::Channel(_, channel) => Some(channel),
+            },
+        }
+    }
+}
+
+fn choices(channels: &[SelectedChannel]) -> Vec<ChannelChoice<'_>> {
+    let channels = channels
+        .iter()
+        .enumerate()
+        .map(|(i, channel)| ChannelChoice::Channel(i, channel));
+    [ChannelChoice::PrivateChat]
+        .into_iter()
+        .chain(channels)
+        .collect()
+}
+
+fn channel_name(channel: &SelectedChannel, locale: Locale) -> String {
+    match (&channel.title, &channel.username) {
+        (Some(title), _) => title.clone(),
+        (None, Some(username)) => format!("@{username}"),
+        (None, None) => tr!(locale, "channel.unknown").to_string(),
+    }
+}
+
+/// Fetches the current title and username of the channel. Returns `None` if the bot can't
+/// access the channel anymore.
+async fn refresh(
+    cx: HandleMessage<'_>,
+    channel: SelectedChannel,
+) -> HandlerResult<Option<SelectedChannel>> {
+    let params = GetChatParams::builder().chat_id(channel.chat_id).build();
+
+    match cx.inner.bot.get_chat(&params).await {
+        Ok(response) => Ok(Some(SelectedChannel {
+            chat_id: channel.chat_id,
+            title: response.result.title,
+            username: response.result.username,
+        })),
+        Err(frankenstein::Error::Api(e)) => {
+            log::info!(
+                "Channel {} is not accessible: {}",
+                channel.chat_id,
+                e.description
+            );
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl ChannelListSelection {
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        _: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        let channel = match choices(&self.channels).match_action(cx.input(), cx.locale) {
+            Some(channel) => channel.cloned(),
+            None => {
+                let text = tr!(
+                    cx.locale,
+                    "target.use_buttons",
+                    cancel = super::command_cancel::COMMAND.name
+                );
+                let reply_markup = choices(&self.channels).keyboard_markup(cx.locale);
+                return respond!(cx, text, reply_markup).await;
+            }
+        };
+
+        let Some(channel) = channel else {
+            cx.reset_dialogue(None).await?;
+            let text = tr!(cx.locale, "target.reset");
+            return respond!(cx, text, reply_markup = remove_keyboard()).await;
+        };
+
+        // fails unless the user is still an administrator of the channel
+        cx.selected_chat(&Some(channel.clone())).await?;
+
+        let (text, entities) = tr_msg!(
+            cx.locale,
+            "target.selected",
+            channel = channel.hyperlink(cx.locale),
+            command = COMMAND.name
+        )
+        .to_message()?;
+
+        cx.reset_dialogue(Some(channel)).await?;
+        respond!(cx, text, entities, reply_markup = remove_keyboard()).await
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
+    if cx.chat_id() < 0 {
+        return respond!(cx, text = tr!(cx.locale, "target.private_only")).await;
+    }
+
+    let database = &cx.inner.database;
+    let user_id = cx.chat_id();
+    let mut dialogue = cx.get_dialogue().await?;
+
+    let stored: Vec<SelectedChannel> = database.get_channels(user_id).await?;
+    let mut channels = vec![];
+    for channel in stored {
+        let channel_id = channel.chat_id;
+        match refresh(cx, channel).await? {
+            Some(channel) => {
+                database.add_channel(user_id, channel_id, &channel).await?;
+                channels.push(channel);
+            }
+            None => database.remove_channel(user_id, channel_id).await?,
+        }
+    }
+
+    if channels.is_empty() {
+        let text = tr!(
+            cx.locale,
+            "channels.none",
+            command = super::command_target::COMMAND.name
+        );
+        return respond!(cx, text).await;
+    }
+
+    channels.sort_by_cached_key(|channel| channel_name(channel, cx.locale).to_lowercase());
+
+    let mut msg = MessageBuilder::new();
+    msg.writeln(bold(tr!(cx.locale, "channels.title")))?;
+    msg.write("\n")?;
+
+    for (i, channel) in channels.iter().enumerate() {
+        let rules = database
+            .get_filters(Recipient::from(channel.chat_id))
+            .await?
+            .len();
+
+        write!(msg, "{}. ", i + 1)?;
+        msg.write(channel.hyperlink(cx.locale))?;
+        if rules == 0 {
+            msg.write(tr!(cx.locale, "channels.inactive"))?;
+        } else {
+            msg.write(tr!(cx.locale, "channels.active", count = rules))?;
+        }
+
+        if let Some(current) = &mut dialogue.channel
+            && current.chat_id == channel.chat_id
+        {
+            *current = channel.clone();
+            msg.write(tr!(cx.locale, "channels.current"))?;
+        }
+        msg.write("\n")?;
+    }
+
+    msg.write("\n")?;
+    msg.write(tr!(cx.locale, "channels.question"))?;
+
+    let (text, entities) = msg.build();
+    let reply_markup = choices(&channels).keyboard_markup(cx.locale);
+    let state = ChannelListSelection { channels };
+    cx.update_dialogue(state, dialogue.channel).await?;
+    respond!(cx, text, entities, reply_markup).await
+}
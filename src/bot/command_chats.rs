@@ -0,0 +1,124 @@
+use bot_utils::Recipient;
+use frankenstein::AsyncTelegramApi;
+use frankenstein::methods::GetChatParams;
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, code, concat};
+
+use super::{Command, HandleMessage, HandlerResult};
+use crate::database::{BACKLOG_LIMIT, ChatOverview};
+use crate::types::ChatKind;
+
+pub const COMMAND: Command = Command {
+    name: "chats",
+
+    group_admin: false,
+    group_member: false,
+    private_chat: false,
+    admin: true,
+    owner: true,
+};
+
+/// Up to how many chats are listed
+const MAX_LISTED: usize = 50;
+
+/// The number of unprocessed messages, which are only counted up to a limit
+pub(super) fn backlog(chat: &ChatOverview) -> String {
+    if chat.backlog >= BACKLOG_LIMIT {
+        format!("{BACKLOG_LIMIT}+")
+    } else {
+        chat.backlog.to_string()
+    }
+}
+
+/// The chat's kind and id, e.g. "👥 -4711"
+pub(super) fn chat_line(recipient: Recipient) -> impl WriteToMessage {
+    let emoji = ChatKind::of(recipient).emoji();
+    concat!(emoji, " ", code(recipient))
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    let mut chats = cx.inner.database.get_chat_overviews().await?;
+
+    if let Some(param) = param.map(str::trim).filter(|param| !param.is_empty()) {
+        let Ok(recipient) = param.parse::<Recipient>() else {
+            let text = tr!(cx.locale, "chats.usage", command = COMMAND.name);
+            return respond!(cx, text).await;
+        };
+
+        return match chats.iter().find(|chat| chat.recipient == recipient) {
+            Some(chat) => inspect_chat(cx, chat).await,
+            None => respond!(cx, text = tr!(cx.locale, "chats.not_registered")).await,
+        };
+    }
+
+    chats.sort_by_key(|chat| (std::cmp::Reverse(chat.rules), chat.recipient.chat_id));
+
+    let mut msg = MessageBuilder::new();
+    msg.writeln(bold(tr!(cx.locale, "chats.title", count = chats.len())))?;
+    msg.write("\n")?;
+
+    for chat in chats.iter().take(MAX_LISTED) {
+        msg.write(chat_line(chat.recipient))?;
+        let details = tr!(
+            cx.locale,
+            "chats.line",
+            rules = chat.rules,
+            backlog = backlog(chat)
+        );
+        writeln!(msg, ": {details}")?;
+    }
+
+    if chats.len() > MAX_LISTED {
+        let more = chats.len() - MAX_LISTED;
+        msg.writeln(tr!(cx.locale, "test.more", count = more))?;
+    }
+
+    msg.write("\n")?;
+    msg.write(tr!(cx.locale, "chats.inspect", command = COMMAND.name))?;
+
+    let (text, entities) = msg.build();
+    respond!(cx, text, entities).await
+}
+
+async fn inspect_chat(cx: HandleMessage<'_>, chat: &ChatOverview) -> HandlerResult {
+    let database = &cx.inner.database;
+    let recipient = chat.recipient;
+    let filters = database.get_filters(recipient).await?;
+    let mode = database.get_delivery_mode(recipient).await?;
+
+    // the chat might not be accessible anymore
+    let params = GetChatParams::builder().chat_id(recipient.chat_id).build();
+    let title = match cx.inner.bot.get_chat(&params).await {
+        Ok(response) => {
+            let chat = response.result;
+            chat.title
+                .or(chat.username.map(|username| format!("@{username}")))
+                .or(chat.first_name)
+        }
+        Err(e) => {
+            log::info!("Unable to get chat {recipient}: {e}");
+            None
+        }
+    };
+
+    let mut msg = MessageBuilder::new();
+    msg.write(chat_line(recipient))?;
+    if let Some(title) = title {
+        write!(msg, " – {title}")?;
+    }
+    msg.write("\n")?;
+    msg.writeln(tr!(
+        cx.locale,
+        "chats.delivery",
+        mode = mode.describe(cx.locale)
+    ))?;
+    msg.writeln(tr!(cx.locale, "chats.backlog", backlog = backlog(chat)))?;
+    msg.write("\n")?;
+
+    for (i, f) in filters.iter().enumerate() {
+        msg.writeln(bold(tr!(cx.locale, "rule.number", number = i + 1)))?;
+        msg.writeln(f.describe(cx.locale))?;
+    }
+
+    let (text, entities) = msg.build();
+    respond!(cx, text, entities).await
+}
@@ -0,0 +1,215 @@
+use chrono::{Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use telegram_message_builder::WriteToMessage;
+
+use super::keyboard::{Button, Choice, Choices, remove_keyboard};
+use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
+use crate::locale::Locale;
+use crate::types::{DeliveryMode, weekday_name};
+
+pub const COMMAND: Command = Command {
+    name: "zustellung",
+
+    group_admin: true,
+    group_member: false,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+/// The hours offered for daily and weekly digests
+const HOURS: [u32; 8] = [6, 7, 8, 9, 12, 15, 18, 20];
+
+const WEEKDAY_VALUES: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeliverySelection {
+    Mode,
+    Weekday,
+    Hour { weekday: Option<Weekday> },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DeliveryChoice {
+    Instant,
+    Hourly,
+    Daily,
+    Weekly,
+    Weekday(Weekday),
+    Hour(u32),
+    Cancel,
+}
+
+impl<'a> Choice<'a> for DeliveryChoice {
+    type Action = Self;
+
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
+        let text = match self {
+            Self::Instant => tr!(locale, "delivery.button.instant").into(),
+            Self::Hourly => tr!(locale, "delivery.button.hourly").into(),
+            Self::Daily => tr!(locale, "delivery.button.daily").into(),
+            Self::Weekly => tr!(locale, "delivery.button.weekly").into(),
+            Self::Weekday(weekday) => weekday_name(*weekday, locale).into(),
+            Self::Hour(hour) => tr!(locale, "delivery.button.hour", hour = hour).into(),
+            Self::Cancel => tr!(locale, "dialogue.cancel").into(),
+        };
+
+        Button::Text {
+            text,
+            action: |x| x,
+        }
+    }
+}
+
+impl DeliverySelection {
+    fn choices(&self) -> Vec<DeliveryChoice> {
+        use DeliveryChoice::*;
+
+        let mut choices = match self {
+            Self::Mode => vec![Instant, Hourly, Daily, Weekly],
+            Self::Weekday => WEEKDAY_VALUES.into_iter().map(Weekday).collect(),
+            Self::Hour { .. } => HOURS.into_iter().map(Hour).collect(),
+        };
+
+        choices.push(Cancel);
+        choices
+    }
+
+    fn question(&self, locale: Locale) -> &'static str {
+        match self {
+            Self::Mode => tr!(locale, "delivery.question.mode"),
+            Self::Weekday => tr!(locale, "delivery.question.weekday"),
+            Self::Hour { .. } => tr!(locale, "delivery.question.hour"),
+        }
+    }
+
+    async fn ask(self, cx: HandleMessage<'_>, channel: Option<SelectedChannel>) -> HandlerResult {
+        let text = self.question(cx.locale);
+        let reply_markup = self.choices().keyboard_markup(cx.locale);
+        cx.update_dialogue(self, channel).await?;
+        respond!(cx, text, reply_markup).await
+    }
+
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        let mode = match (&self, self.choices().match_action(cx.input(), cx.locale)) {
+            (_, Some(DeliveryChoice::Instant)) => DeliveryMode::Instant,
+            (_, Some(DeliveryChoice::Hourly)) => DeliveryMode::Hourly,
+            (_, Some(DeliveryChoice::Daily)) => {
+                return Self::Hour { weekday: None }.ask(cx, channel).await;
+            }
+            (_, Some(DeliveryChoice::Weekly)) => return Self::Weekday.ask(cx, channel).await,
+            (_, Some(DeliveryChoice::Weekday(weekday))) => {
+                let weekday = Some(weekday);
+                return Self::Hour { weekday }.ask(cx, channel).await;
+            }
+            (Self::Hour { weekday: None }, Some(DeliveryChoice::Hour(hour))) => {
+                DeliveryMode::Daily { hour }
+            }
+            (
+                Self::Hour {
+                    weekday: Some(weekday),
+                },
+                Some(DeliveryChoice::Hour(hour)),
+            ) => DeliveryMode::Weekly {
+                weekday: *weekday,
+                hour,
+            },
+            (_, None) if cx.text() != Some(tr!(cx.locale, "dialogue.cancel")) => {
+                let text = tr!(
+                    cx.locale,
+                    "dialogue.use_buttons",
+                    cancel = super::command_cancel::COMMAND.name
+                );
+                let reply_markup = self.choices().keyboard_markup(cx.locale);
+                return respond!(cx, text, reply_markup).await;
+            }
+            _ => {
+                cx.reset_dialogue(channel).await?;
+                return respond!(
+                    cx,
+                    text = tr!(cx.locale, "dialogue.cancelled"),
+                    reply_markup = remove_keyboard()
+                )
+                .await;
+            }
+        };
+
+        let recipient = cx.selected_chat(&channel).await?;
+        let due = mode.next_due(Utc::now());
+        let saved = cx
+            .inner
+            .database
+            .set_delivery_mode(recipient, mode, due)
+            .await?;
+
+        let target = SelectedChannel::chat_selection_accusative(&channel, cx.locale);
+        let (text, entities) = if !saved {
+            tr_msg!(cx.locale, "rules.none", target = target).to_message()?
+        } else if mode == DeliveryMode::Instant {
+            tr_msg!(cx.locale, "delivery.saved.instant", target = target).to_message()?
+        } else {
+            let mode = mode.describe(cx.locale);
+            tr_msg!(
+                cx.locale,
+                "delivery.saved.digest",
+                target = target,
+                mode = mode
+            )
+            .to_message()?
+        };
+
+        if channel.is_none() {
+            cx.remove_dialogue().await?;
+        } else {
+            cx.reset_dialogue(channel).await?;
+        }
+
+        respond!(cx, text, entities, reply_markup = remove_keyboard()).await
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
+    let dialogue = cx.get_dialogue().await?;
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let filters = cx.inner.database.get_filters(recipient).await?;
+    let target = SelectedChannel::chat_selection_accusative(&dialogue.channel, cx.locale);
+
+    if filters.is_empty() {
+        let (text, entities) = tr_msg!(
+            cx.locale,
+            "rules.none_create",
+            target = target,
+            new_rule = super::command_new_rule::COMMAND.name
+        )
+        .to_message()?;
+        return respond!(cx, text, entities, reply_markup = remove_keyboard()).await;
+    }
+
+    let mode = cx.inner.database.get_delivery_mode(recipient).await?;
+    let state = DeliverySelection::Mode;
+
+    let (text, entities) = tr_msg!(
+        cx.locale,
+        "delivery.current",
+        target = target,
+        mode = mode.describe(cx.locale),
+        question = state.question(cx.locale)
+    )
+    .to_message()?;
+
+    let reply_markup = state.choices().keyboard_markup(cx.locale);
+    cx.update_dialogue(state, dialogue.channel).await?;
+    respond!(cx, text, entities, reply_markup).await
+}
@@ -0,0 +1,427 @@
+use std::convert::identity;
+
+use serde::{Deserialize, Serialize};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, code};
+
+use super::command_new_rule::{input_placeholder, pattern_error};
+use super::keyboard::{Button, Choice, Choices, force_reply, remove_keyboard};
+use super::{Command, Error, HandleMessage, HandlerResult, SelectedChannel, command_values};
+use crate::locale::Locale;
+use crate::types::{Condition, Filter, MatchMode, Tag};
+
+pub const COMMAND: Command = Command {
+    name: "regel_bearbeiten",
+
+    group_admin: true,
+    group_member: false,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EditFilterSelection {
+    filters: Vec<Filter>,
+}
+
+impl EditFilterSelection {
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        match self
+            .filters
+            .iter()
+            .enumerate()
+            .match_action(cx.input(), cx.locale)
+        {
+            Some((index, filter)) => {
+                let state = RuleEditor {
+                    index,
+                    original: filter.clone(),
+                    draft: filter.clone(),
+                    step: EditStep::Overview,
+                };
+                state.show("", cx, channel).await
+            }
+            None => {
+                let text = tr!(
+                    cx.locale,
+                    "dialogue.select_rule",
+                    cancel = super::command_cancel::COMMAND.name
+                );
+                let reply_markup = self.filters.iter().enumerate().keyboard_markup(cx.locale);
+                respond!(cx, text, reply_markup).await
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+enum EditStep {
+    Overview,
+    /// one of the conditions is selected
+    Condition(usize),
+    /// the tag of a new condition is chosen
+    NewCondition,
+    /// the value for an existing condition, or a new condition on the tag, is entered
+    Value {
+        condition: Option<usize>,
+        tag: Tag,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+enum EditButton {
+    Condition(usize),
+    Add,
+    Save,
+    Negate,
+    ChangeValue,
+    Mode(MatchMode),
+    Remove,
+    Back,
+    Tag(Tag),
+}
+
+impl<'a> Choice<'a> for EditButton {
+    type Action = Self;
+
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
+        let text = match self {
+            EditButton::Condition(i) => {
+                tr!(locale, "edit_rule.button.condition", number = i + 1).into()
+            }
+            EditButton::Add => tr!(locale, "edit_rule.button.add").into(),
+            EditButton::Save => tr!(locale, "new_rule.button.save").into(),
+            EditButton::Negate => tr!(locale, "edit_rule.button.negate").into(),
+            EditButton::ChangeValue => tr!(locale, "edit_rule.button.change_value").into(),
+            EditButton::Mode(mode) => {
+                tr!(locale, "new_rule.button.mode", mode = mode.label(locale)).into()
+            }
+            EditButton::Remove => tr!(locale, "edit_rule.button.remove").into(),
+            EditButton::Back => tr!(locale, "edit_rule.button.back").into(),
+            EditButton::Tag(tag) => tag.label(locale).into(),
+        };
+
+        Button::Text {
+            text,
+            action: identity,
+        }
+    }
+}
+
+/// Edits a copy of a rule, which replaces the rule when it is saved
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RuleEditor {
+    /// the position of the rule in the chat's list
+    index: usize,
+    /// the rule as it was when editing started
+    original: Filter,
+    draft: Filter,
+    step: EditStep,
+}
+
+impl RuleEditor {
+    fn buttons(&self) -> Vec<EditButton> {
+        match &self.step {
+            EditStep::Overview => (0..self.draft.expression.conditions().len())
+                .map(EditButton::Condition)
+                .chain([EditButton::Add, EditButton::Save])
+                .collect(),
+            EditStep::Condition(i) => {
+                let mode = self
+                    .condition(*i)
+                    .map_or_else(MatchMode::default, |c| c.mode);
+                vec![
+                    EditButton::Negate,
+                    EditButton::ChangeValue,
+                    EditButton::Mode(mode),
+                    EditButton::Remove,
+                    EditButton::Back,
+                ]
+            }
+            EditStep::NewCondition => Tag::TAGS
+                .iter()
+                .copied()
+                .map(EditButton::Tag)
+                .chain([EditButton::Back])
+                .collect(),
+            EditStep::Value { .. } => vec![],
+        }
+    }
+
+    fn condition(&self, index: usize) -> Option<&Condition> {
+        self.draft.expression.conditions().get(index).copied()
+    }
+
+    fn condition_mut(&mut self, index: usize) -> Option<&mut Condition> {
+        self.draft.expression.condition_mut(index)
+    }
+
+    /// Sends a description of the current step, preceded by `note`
+    async fn show(
+        self,
+        note: &str,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        let mut msg = MessageBuilder::new();
+        if !note.is_empty() {
+            msg.write(note)?;
+            msg.write("\n\n")?;
+        }
+
+        match &self.step {
+            EditStep::Overview => {
+                let title = tr!(cx.locale, "edit_rule.title", number = self.index + 1);
+                msg.writeln(bold(title))?;
+                msg.writeln(self.draft.describe(cx.locale))?;
+
+                let conditions = self.draft.expression.conditions();
+                if !conditions.is_empty() {
+                    msg.writeln(tr!(cx.locale, "edit_rule.conditions"))?;
+                    for (i, condition) in conditions.iter().enumerate() {
+                        writeln!(msg, "{}. {}", i + 1, condition.describe(cx.locale))?;
+                    }
+                    msg.write("\n")?;
+                }
+
+                msg.write(tr!(cx.locale, "edit_rule.overview"))?;
+            }
+            EditStep::Condition(i) => {
+                let Some(condition) = self.condition(*i) else {
+                    return Err(Error::UnexpectedMessage);
+                };
+                msg.write(tr!(
+                    cx.locale,
+                    "edit_rule.condition",
+                    number = i + 1,
+                    condition = condition.describe(cx.locale)
+                ))?;
+                msg.write("\n\n")?;
+                msg.write(tr!(cx.locale, "edit_rule.question"))?;
+            }
+            EditStep::NewCondition => {
+                msg.write(tr!(cx.locale, "edit_rule.new_condition"))?;
+            }
+            EditStep::Value { condition, tag } => {
+                let mode = condition
+                    .and_then(|i| self.condition(i))
+                    .map_or_else(MatchMode::default, |c| c.mode);
+
+                msg.write(tr_msg!(
+                    cx.locale,
+                    "edit_rule.value",
+                    tag = bold(tag.label(cx.locale)),
+                    description = mode.description(cx.locale)
+                ))?;
+                if let Some(example) = command_values::examples(cx, *tag).await?.first() {
+                    msg.write(tr_msg!(
+                        cx.locale,
+                        "edit_rule.example",
+                        example = code(example)
+                    ))?;
+                }
+                if condition.is_none() {
+                    msg.write(tr!(cx.locale, "edit_rule.negate_hint"))?;
+                }
+
+                let (text, entities) = msg.build();
+                let placeholder = input_placeholder(mode, cx.locale);
+                cx.update_dialogue(self, channel).await?;
+                return respond!(cx, text, entities, reply_markup = force_reply(placeholder)).await;
+            }
+        }
+
+        let (text, entities) = msg.build();
+        let reply_markup = self.buttons().keyboard_markup(cx.locale);
+        cx.update_dialogue(self, channel).await?;
+        respond!(cx, text, entities, reply_markup).await
+    }
+
+    /// Replaces the rule with the draft, unless the rule has been changed in the meantime
+    async fn save(self, cx: HandleMessage<'_>, channel: Option<SelectedChannel>) -> HandlerResult {
+        let recipient = cx.selected_chat(&channel).await?;
+
+        let saved = cx
+            .inner
+            .database
+            .update_filter(recipient, &|filters| match filters.get_mut(self.index) {
+                Some(filter) if *filter == self.original => {
+                    *filter = self.draft.clone();
+                    true
+                }
+                _ => false,
+            })
+            .await?;
+
+        let text = if saved {
+            tr!(cx.locale, "edit_rule.saved")
+        } else {
+            tr!(cx.locale, "edit_rule.conflict")
+        };
+
+        cx.reset_dialogue(channel).await?;
+        respond!(cx, text, reply_markup = remove_keyboard()).await
+    }
+
+    async fn handle_value(
+        mut self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+        condition: Option<usize>,
+        tag: Tag,
+    ) -> HandlerResult {
+        let Some(text) = cx.text() else {
+            return Err(Error::UnexpectedMessage);
+        };
+
+        let new_condition = match condition.and_then(|i| self.condition(i)) {
+            Some(existing) => Condition {
+                pattern: text.to_string(),
+                ..existing.clone()
+            },
+            None => {
+                let (negate, pattern) = match text.strip_prefix('!') {
+                    Some(pattern) => (true, pattern),
+                    None => (false, text),
+                };
+                Condition {
+                    tag,
+                    pattern: pattern.to_string(),
+                    negate,
+                    mode: MatchMode::default(),
+                }
+            }
+        };
+
+        if let Some((text, entities)) = pattern_error(&new_condition.regex(), cx.locale)? {
+            let placeholder = input_placeholder(new_condition.mode, cx.locale);
+            return respond!(cx, text, entities, reply_markup = force_reply(placeholder)).await;
+        }
+
+        let note = match condition.and_then(|i| self.condition_mut(i)) {
+            Some(existing) => {
+                *existing = new_condition;
+                tr!(cx.locale, "edit_rule.value_changed")
+            }
+            None => {
+                self.draft.expression.push_condition(new_condition);
+                tr!(cx.locale, "edit_rule.condition_added")
+            }
+        };
+
+        self.step = EditStep::Overview;
+        self.show(note, cx, channel).await
+    }
+
+    pub(super) async fn handle_message(
+        mut self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        if let EditStep::Value { condition, tag } = self.step {
+            return self.handle_value(cx, channel, condition, tag).await;
+        }
+
+        let Some(button) = self.buttons().match_action(cx.input(), cx.locale) else {
+            let text = tr!(
+                cx.locale,
+                "dialogue.use_buttons",
+                cancel = super::command_cancel::COMMAND.name
+            );
+            let reply_markup = self.buttons().keyboard_markup(cx.locale);
+            return respond!(cx, text, reply_markup).await;
+        };
+
+        let selected = match self.step {
+            EditStep::Condition(i) => i,
+            _ => 0,
+        };
+
+        let note = match button {
+            EditButton::Save => return self.save(cx, channel).await,
+            EditButton::Condition(i) => {
+                self.step = EditStep::Condition(i);
+                ""
+            }
+            EditButton::Add => {
+                self.step = EditStep::NewCondition;
+                ""
+            }
+            EditButton::Tag(tag) => {
+                self.step = EditStep::Value {
+                    condition: None,
+                    tag,
+                };
+                ""
+            }
+            EditButton::ChangeValue => {
+                let Some(condition) = self.condition(selected) else {
+                    return Err(Error::UnexpectedMessage);
+                };
+                self.step = EditStep::Value {
+                    condition: Some(selected),
+                    tag: condition.tag,
+                };
+                ""
+            }
+            EditButton::Negate => {
+                if let Some(condition) = self.condition_mut(selected) {
+                    condition.negate = !condition.negate;
+                }
+                tr!(cx.locale, "edit_rule.negated")
+            }
+            EditButton::Mode(mode) => {
+                if let Some(condition) = self.condition_mut(selected) {
+                    condition.mode = mode.next();
+                }
+                tr!(cx.locale, "edit_rule.mode_changed")
+            }
+            EditButton::Remove => {
+                self.draft.expression.remove_condition(selected);
+                self.step = EditStep::Overview;
+                tr!(cx.locale, "edit_rule.removed")
+            }
+            EditButton::Back => {
+                self.step = EditStep::Overview;
+                ""
+            }
+        };
+
+        self.show(note, cx, channel).await
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
+    let dialogue = cx.get_dialogue().await?;
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let filters = cx.inner.database.get_filters(recipient).await?;
+
+    if filters.is_empty() {
+        let target = SelectedChannel::chat_selection_accusative(&dialogue.channel, cx.locale);
+        let (text, entities) = tr_msg!(cx.locale, "rules.none", target = target).to_message()?;
+        return respond!(cx, text, entities, reply_markup = remove_keyboard()).await;
+    }
+
+    let mut msg = MessageBuilder::new();
+
+    let chat = SelectedChannel::chat_selection(&dialogue.channel, cx.locale);
+    msg.writeln(tr_msg!(cx.locale, "selection.current", chat = chat))?;
+    msg.write(tr!(cx.locale, "edit_rule.select"))?;
+    msg.write("\n\n")?;
+
+    for (i, f) in filters.iter().enumerate() {
+        msg.writeln(bold(tr!(cx.locale, "rule.number", number = i + 1)))?;
+        msg.writeln(f.describe(cx.locale))?;
+    }
+
+    let (text, entities) = msg.build();
+    let reply_markup = filters.iter().enumerate().keyboard_markup(cx.locale);
+    let state = EditFilterSelection { filters };
+
+    cx.update_dialogue(state, dialogue.channel).await?;
+    respond!(cx, text, entities, reply_markup).await
+}
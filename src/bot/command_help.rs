@@ -1,155 +1,208 @@
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 use frankenstein::types::MessageEntity;
 use telegram_message_builder::{WriteToMessage, bold, concat, from_fn, italic, text_link};
 
 use super::{Command, HandleMessage, HandlerResult, command_privacy};
+use crate::allris::{Instance, Source};
 use crate::bot::{
-    command_cancel, command_help, command_new_rule, command_remove_all_rules, command_remove_rule,
-    command_rules, command_start, command_target,
+    command_cancel, command_channels, command_delivery, command_edit_rule, command_help,
+    command_language, command_new_rule, command_permissions, command_remove_all_rules,
+    command_remove_rule, command_rules, command_search, command_start, command_target,
+    command_test_rule, command_values,
 };
+use crate::branding::Branding;
+use crate::locale::Locale;
 
 pub const COMMAND: Command = Command {
     name: "hilfe",
-    description: "Zeige die Hilfenachricht an",
 
     group_admin: true,
     group_member: true,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
-static MESSAGE_PRIVATE: OnceLock<(String, Vec<MessageEntity>)> = OnceLock::new();
-static MESSAGE_GROUP: OnceLock<(String, Vec<MessageEntity>)> = OnceLock::new();
-
-const fn intro_paragraph() -> impl WriteToMessage {
-    concat!(
-        bold("🤖 Allris-Bot"),
-        "\nDieser Bot benachrichtigt dich, wenn im ",
-        text_link(
-            "https://www.bonn.sitzung-online.de",
-            "Ratsinformationssystem der Stadt Bonn"
-        ),
-        " neue Vorlagen veröffentlicht werden – lege dazu ",
-        bold("Regeln"),
-        " fest, welche Vorlagen du erhalten willst.\n"
-    )
+type Message = (String, Vec<MessageEntity>);
+
+/// The help messages by locale, for groups and for private chats
+static MESSAGES: OnceLock<HashMap<(Locale, bool), Message>> = OnceLock::new();
+
+fn intro_paragraph<'a>(
+    branding: &'a Branding,
+    instances: &'a [Instance],
+    locale: Locale,
+) -> impl WriteToMessage + 'a {
+    from_fn(move |msg| {
+        msg.writeln(bold(concat!("🤖 ", &branding.bot_name)))?;
+
+        if instances.len() == 1 {
+            msg.write(tr!(locale, "help.intro.one"))?;
+        } else {
+            msg.write(tr!(locale, "help.intro.many"))?;
+        }
+
+        for (i, instance) in instances.iter().enumerate() {
+            match i {
+                0 => (),
+                i if i == instances.len() - 1 => msg.write(tr!(locale, "list.and"))?,
+                _ => msg.write(", ")?,
+            }
+            match instance.source.website() {
+                Some(url) => msg.write(text_link(url, &instance.name))?,
+                None => msg.write(&instance.name)?,
+            }
+        }
+
+        msg.write(tr!(locale, "help.intro.published"))?;
+        msg.write(bold(tr!(locale, "help.intro.rules")))?;
+        msg.writeln(tr!(locale, "help.intro.end"))
+    })
+}
+
+/// A paragraph with a title, a description and a list of commands
+fn commands_paragraph(
+    locale: Locale,
+    key: &'static str,
+    commands: &'static [&'static Command],
+) -> impl WriteToMessage {
+    from_fn(move |msg| {
+        msg.writeln(bold(tr!(locale, &format!("help.{key}.title"))))?;
+        msg.writeln(italic(tr!(locale, &format!("help.{key}.description"))))?;
+        for command in commands {
+            msg.write(command.line(locale))?;
+        }
+        Ok(())
+    })
 }
 
-const fn rules_paragraph() -> impl WriteToMessage {
-    let desc =
-        "Du erhältst Benachrichtungen für alle Vorlagen, auf die mindestens eine Regel zutrifft.";
-    concat!(
-        bold("🔧 Regeln verwalten"),
-        "\n",
-        italic(desc),
-        "\n",
-        command_new_rule::COMMAND,
-        command_rules::COMMAND,
-        command_remove_rule::COMMAND,
-        command_remove_all_rules::COMMAND,
+fn rules_paragraph(locale: Locale) -> impl WriteToMessage {
+    commands_paragraph(
+        locale,
+        "rules",
+        &[
+            &command_new_rule::COMMAND,
+            &command_rules::COMMAND,
+            &command_edit_rule::COMMAND,
+            &command_remove_rule::COMMAND,
+            &command_remove_all_rules::COMMAND,
+            &command_test_rule::COMMAND,
+            &command_values::COMMAND,
+        ],
     )
 }
 
-const fn target_paragraph() -> impl WriteToMessage {
-    let desc = "Der Bot kann Benachrichtigungen hier im Chat oder in einem deiner Kanäle senden.";
-    concat!(
-        bold("📬 Ziel einstellen"),
-        "\n",
-        italic(desc),
-        "\n",
-        command_target::COMMAND,
+fn target_paragraph(locale: Locale) -> impl WriteToMessage {
+    commands_paragraph(
+        locale,
+        "target",
+        &[
+            &command_target::COMMAND,
+            &command_channels::COMMAND,
+            &command_delivery::COMMAND,
+        ],
     )
 }
 
-fn miscellaneous_paragraph() -> impl WriteToMessage {
-    from_fn(|msg| {
-        msg.writeln(bold("🆘 Sonstiges"))?;
-
-        write!(
-            msg,
-            "{cancel}\
-             /{hilfe} oder /{start} – Zeige diese Hilfe an\n\
-             {privacy}",
-            cancel = command_cancel::COMMAND,
-            hilfe = command_help::COMMAND.name,
-            start = command_start::COMMAND.name,
-            privacy = command_privacy::COMMAND,
-        )
+fn permissions_paragraph(locale: Locale) -> impl WriteToMessage {
+    commands_paragraph(locale, "permissions", &[&command_permissions::COMMAND])
+}
+
+fn miscellaneous_paragraph(locale: Locale) -> impl WriteToMessage {
+    from_fn(move |msg| {
+        msg.writeln(bold(tr!(locale, "help.misc.title")))?;
+        msg.write(command_search::COMMAND.line(locale))?;
+        msg.write(command_cancel::COMMAND.line(locale))?;
+        msg.writeln(tr!(
+            locale,
+            "help.misc.help",
+            help = command_help::COMMAND.name,
+            start = command_start::COMMAND.name
+        ))?;
+        msg.write(command_language::COMMAND.line(locale))?;
+        msg.write(command_privacy::COMMAND.line(locale))
     })
 }
 
-fn regex_paragraph() -> impl WriteToMessage {
-    concat!(
-        bold("📚 Reguläre Ausdrücke (Regex)"),
-        "\nBeim Erstellen einer Regel kannst du festlegen, dass ein bestimmtes Merkmal ein sogenanntes Regex-Pattern erfüllen muss. \
-         Gib dort einfach den Text ein, nach dem du filtern möchtest – das funktioniert in den meisten Fällen zuverlässig. \
-         Falls du komplexere Muster brauchst, helfen dir ",
-        text_link("https://regex101.com", "regex101.com"),
-        " oder ChatGPT beim Ausprobieren und Erlernen von regulären Ausdrücken.\n"
-    )
+fn regex_paragraph(locale: Locale) -> impl WriteToMessage {
+    from_fn(move |msg| {
+        msg.writeln(bold(tr!(locale, "help.regex.title")))?;
+        msg.write(tr!(locale, "help.regex.text"))?;
+        msg.write(text_link("https://regex101.com", "regex101.com"))?;
+        msg.writeln(tr!(locale, "help.regex.end"))
+    })
 }
 
-fn disclaimer_paragraph() -> impl WriteToMessage {
-    concat!(
-        bold("⚖️ Hinweis"),
-        "\nDieser Bot ist ein rein privates, nicht-kommerzielles Projekt zur \
-        automatischen Benachrichtigung über neue Vorlagen aus dem ALLRIS®-System der Stadt Bonn. \
-        Er steht weder in Verbindung zur Firma CC e-gov GmbH noch zur Stadt Bonn. \n",
-        bold(
-            "Für Vollständigkeit, Richtigkeit oder Aktualität der bereitgestellten \
-            Informationen wird keine Gewähr übernommen.",
-        ),
-        "\n",
-    )
+fn disclaimer_paragraph(locale: Locale) -> impl WriteToMessage {
+    from_fn(move |msg| {
+        msg.writeln(bold(tr!(locale, "help.disclaimer.title")))?;
+        msg.write(tr!(locale, "help.disclaimer.text"))?;
+        msg.write(" \n")?;
+        msg.writeln(bold(tr!(locale, "help.disclaimer.warranty")))
+    })
 }
 
-fn about_paragraph(owner: Option<&str>) -> impl WriteToMessage {
+fn about_paragraph(contact: Option<&str>, locale: Locale) -> impl WriteToMessage {
     from_fn(move |msg| {
-        msg.writeln(bold("👨‍💻 Mehr Infos & Kontakt"))?;
-
-        write!(
-            msg,
-            "Der Quellcode dieses Bots ist öffentlich zugänglich: {} (Version {})",
-            env!("CARGO_PKG_REPOSITORY"),
-            env!("CARGO_PKG_VERSION")
-        )?;
-
-        if let Some(owner) = owner {
-            write!(
-                msg,
-                "\n\nFragen, Feedback oder Ideen? Schreib mir gern: @{owner}"
-            )?;
+        msg.writeln(bold(tr!(locale, "help.about.title")))?;
+
+        msg.write(tr!(
+            locale,
+            "help.about.source",
+            url = env!("CARGO_PKG_REPOSITORY"),
+            version = env!("CARGO_PKG_VERSION")
+        ))?;
+
+        if let Some(contact) = contact {
+            msg.write("\n\n")?;
+            msg.write(tr!(locale, "help.about.contact", contact = contact))?;
         }
 
         Ok(())
     })
 }
 
-fn message(group: bool, owner: Option<&str>) -> (String, Vec<MessageEntity>) {
+fn message(
+    group: bool,
+    branding: &Branding,
+    instances: &[Instance],
+    locale: Locale,
+) -> (String, Vec<MessageEntity>) {
     from_fn(|msg| {
-        msg.writeln(intro_paragraph())?;
-        msg.writeln(rules_paragraph())?;
+        msg.writeln(intro_paragraph(branding, instances, locale))?;
+        msg.writeln(rules_paragraph(locale))?;
 
-        if !group {
-            msg.writeln(target_paragraph())?;
+        if group {
+            msg.writeln(permissions_paragraph(locale))?;
+        } else {
+            msg.writeln(target_paragraph(locale))?;
         }
 
-        msg.writeln(miscellaneous_paragraph())?;
-        msg.writeln(regex_paragraph())?;
-        msg.writeln(disclaimer_paragraph())?;
-        msg.write(about_paragraph(owner))
+        msg.writeln(miscellaneous_paragraph(locale))?;
+        msg.writeln(regex_paragraph(locale))?;
+        msg.writeln(disclaimer_paragraph(locale))?;
+        msg.write(about_paragraph(branding.contact.as_deref(), locale))
     })
     .to_message()
     .expect("help message too long!")
 }
 
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
-    let owner = cx.inner.owner.as_deref();
-    let (text, entities) = if cx.chat_id() < 0 {
-        MESSAGE_GROUP.get_or_init(|| message(true, owner))
-    } else {
-        MESSAGE_PRIVATE.get_or_init(|| message(false, owner))
-    };
+    let messages = MESSAGES.get_or_init(|| {
+        let branding = &cx.inner.branding;
+        let instances = &cx.inner.instances;
+        Locale::LOCALES
+            .iter()
+            .flat_map(|&locale| [(locale, false), (locale, true)])
+            .map(|(locale, group)| {
+                let message = message(group, branding, instances, locale);
+                ((locale, group), message)
+            })
+            .collect()
+    });
+
+    let (text, entities) = &messages[&(cx.locale, cx.chat_id() < 0)];
     respond!(cx, text, entities = entities.clone()).await
 }
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use super::keyboard::{Button, Choice, Choices, remove_keyboard};
+use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
+use crate::locale::Locale;
+
+pub const COMMAND: Command = Command {
+    name: "sprache",
+
+    group_admin: true,
+    group_member: false,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LanguageSelection(());
+
+#[derive(Debug, Clone, Copy)]
+enum LanguageChoice {
+    Locale(Locale),
+    /// follow the language of the users' apps, labeled in the given locale
+    Automatic(Locale),
+}
+
+impl<'a> Choice<'a> for LanguageChoice {
+    type Action = Option<Locale>;
+
+    fn button(&self, _: Locale) -> Button<'a, Self> {
+        let text = match self {
+            Self::Locale(locale) => tr!(*locale, "language.name"),
+            Self::Automatic(locale) => tr!(*locale, "language.automatic"),
+        };
+
+        Button::Text {
+            text: text.into(),
+            action: |choice| match choice {
+                Self::Locale(locale) => Some(locale),
+                Self::Automatic(_) => None,
+            },
+        }
+    }
+}
+
+fn choices(locale: Locale) -> Vec<LanguageChoice> {
+    let mut choices: Vec<_> = Locale::LOCALES
+        .iter()
+        .map(|&locale| LanguageChoice::Locale(locale))
+        .collect();
+    choices.push(LanguageChoice::Automatic(locale));
+    choices
+}
+
+/// Stores the locale for the chat, and confirms it in the new language
+async fn set_locale(cx: HandleMessage<'_>, locale: Option<Locale>) -> HandlerResult {
+    cx.inner.database.set_locale(cx.chat_id(), locale).await?;
+
+    let text = match locale {
+        Some(locale) => tr!(locale, "language.set"),
+        None => {
+            let locale = cx
+                .user()
+                .and_then(|user| user.language_code.as_deref())
+                .and_then(Locale::from_code)
+                .unwrap_or_default();
+            tr!(locale, "language.automatic_set")
+        }
+    };
+
+    respond!(cx, text, reply_markup = remove_keyboard()).await
+}
+
+impl LanguageSelection {
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        match choices(cx.locale).match_action(cx.input(), cx.locale) {
+            Some(locale) => {
+                cx.reset_dialogue(channel).await?;
+                set_locale(cx, locale).await
+            }
+            None => {
+                let text = tr!(
+                    cx.locale,
+                    "dialogue.use_buttons",
+                    cancel = super::command_cancel::COMMAND.name
+                );
+                let reply_markup = choices(cx.locale).keyboard_markup(cx.locale);
+                respond!(cx, text, reply_markup).await
+            }
+        }
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    // the language can be given directly, e.g. `/sprache en`
+    if let Some(code) = param.map(str::trim).filter(|param| !param.is_empty()) {
+        let locale = match Locale::from_code(code) {
+            Some(locale) => Some(locale),
+            None if code.eq_ignore_ascii_case("auto") => None,
+            None => {
+                let mut codes: Vec<_> = Locale::LOCALES.iter().map(|l| l.code()).collect();
+                codes.push("auto");
+                let text = tr!(
+                    cx.locale,
+                    "language.unknown",
+                    code = code,
+                    codes = codes.join(", ")
+                );
+                return respond!(cx, text).await;
+            }
+        };
+
+        return set_locale(cx, locale).await;
+    }
+
+    let current = match cx.inner.database.get_locale(cx.chat_id()).await? {
+        Some(locale) => tr!(locale, "language.name"),
+        None => tr!(cx.locale, "language.automatic"),
+    };
+    let text = format!(
+        "{}\n\n{}",
+        tr!(cx.locale, "language.current", language = current),
+        tr!(cx.locale, "language.question")
+    );
+
+    let dialogue = cx.get_dialogue().await?;
+    let reply_markup = choices(cx.locale).keyboard_markup(cx.locale);
+    cx.update_dialogue(LanguageSelection::default(), dialogue.channel)
+        .await?;
+    respond!(cx, text, reply_markup).await
+}
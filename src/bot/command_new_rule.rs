@@ -1,131 +1,372 @@
 use std::convert::identity;
-use std::iter;
 
+use frankenstein::types::{MessageEntity, ReplyMarkup};
 use regex::RegexBuilder;
 use serde::{Deserialize, Serialize};
-use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, code, concat, pre};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, code, concat, from_fn, pre};
 
+use super::command_values::{MAX_SUGGESTIONS, known_values};
 use super::keyboard::{force_reply, remove_keyboard};
-use super::{Command, Error, SelectedChannel};
+use super::{Command, Error, SelectedChannel, command_test_rule};
+use crate::allris::Instance;
 use crate::bot::keyboard::{Button, Choice, Choices};
 use crate::bot::{HandleMessage, HandlerResult};
-use crate::types::{Condition, Filter, Tag};
+use crate::filter_syntax;
+use crate::locale::Locale;
+use crate::types::{Condition, Expr, Filter, MatchMode, Scope, Tag};
 
 pub const COMMAND: Command = Command {
     name: "neue_regel",
-    description: "Erstelle eine neue Benachrichtigungsregel",
 
     group_admin: true,
-    group_member: true,
+    group_member: false,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TagButton {
     Save,
+    Test,
+    Scope(Scope),
+    Updates(bool),
+    Snippets(bool),
+    Mode(MatchMode),
+    Instance { name: String, selected: bool },
     Select(Tag),
 }
 
 impl<'a> Choice<'a> for TagButton {
     type Action = TagButton;
 
-    fn button(&self) -> Button<'a, Self> {
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
         match self {
             TagButton::Save => Button::Text {
-                text: "✅ Speichern".into(),
+                text: tr!(locale, "new_rule.button.save").into(),
+                action: identity,
+            },
+            TagButton::Test => Button::Text {
+                text: tr!(locale, "new_rule.button.test").into(),
+                action: identity,
+            },
+            TagButton::Scope(scope) => Button::Text {
+                text: tr!(locale, "new_rule.button.scope", scope = scope.label(locale)).into(),
+                action: identity,
+            },
+            TagButton::Updates(updates) => Button::Text {
+                text: if *updates {
+                    tr!(locale, "new_rule.button.updates_on").into()
+                } else {
+                    tr!(locale, "new_rule.button.updates_off").into()
+                },
+                action: identity,
+            },
+            TagButton::Snippets(snippets) => Button::Text {
+                text: if *snippets {
+                    tr!(locale, "new_rule.button.snippets_on").into()
+                } else {
+                    tr!(locale, "new_rule.button.snippets_off").into()
+                },
+                action: identity,
+            },
+            TagButton::Mode(mode) => Button::Text {
+                text: tr!(locale, "new_rule.button.mode", mode = mode.label(locale)).into(),
+                action: identity,
+            },
+            TagButton::Instance { name, selected } => Button::Text {
+                text: if *selected {
+                    format!("☑️ {name}").into()
+                } else {
+                    format!("⬜ {name}").into()
+                },
                 action: identity,
             },
             TagButton::Select(tag) => Button::Text {
-                text: tag.label().into(),
+                text: tag.label(locale).into(),
                 action: identity,
             },
         }
     }
 }
 
-fn buttons() -> Vec<TagButton> {
-    Tag::TAGS
-        .iter()
-        .copied()
-        .map(TagButton::Select)
-        .chain(iter::once(TagButton::Save))
-        .collect()
-}
+/// Shown as an example for the textual syntax
+const EXAMPLE_EXPRESSION: &str = "gremium:Rat & (art:Antrag | art:Anfrage)";
 
-pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
     let dialogue = cx.get_dialogue().await?;
 
-    let reply_markup = buttons().keyboard_markup();
+    if let Some(input) = param.filter(|param| !param.trim().is_empty()) {
+        return start_with_expression(cx, dialogue.channel, input).await;
+    }
+
+    let state = TagSelection::default();
+    let reply_markup = state
+        .buttons(&cx.inner.instances)
+        .keyboard_markup(cx.locale);
+    let instances_hint = if cx.inner.instances.len() > 1 {
+        tr!(cx.locale, "new_rule.instances_hint")
+    } else {
+        ""
+    };
     let (text, entities) = concat!(
         "🎛️ ",
-        bold("Regel erstellen"),
-        "\n\n\
-        Wähle ein Merkmal für die erste Bedingung oder tippe auf „Speichern“, \
-        um die Regel sofort ohne Bedingungen (alle Vorlagen werden erfasst) anzulegen.\n\n\
-        Ausgewählter Chat: ",
-        SelectedChannel::chat_selection(&dialogue.channel)
+        bold(tr!(cx.locale, "new_rule.title")),
+        "\n\n",
+        tr!(cx.locale, "new_rule.intro"),
+        instances_hint,
+        "\n\n",
+        tr_msg!(
+            cx.locale,
+            "new_rule.expression",
+            example = code(format!("/{} {EXAMPLE_EXPRESSION}", COMMAND.name))
+        ),
+        "\n\n",
+        tr_msg!(
+            cx.locale,
+            "new_rule.selected_chat",
+            chat = SelectedChannel::chat_selection(&dialogue.channel, cx.locale)
+        )
     )
     .to_message()?;
 
-    cx.update_dialogue(TagSelection::default(), dialogue.channel)
-        .await?;
+    cx.update_dialogue(state, dialogue.channel).await?;
     respond!(cx, text, entities, reply_markup).await
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TagSelection {
+    /// a rule entered in the textual syntax, which the selected conditions are added to
+    #[serde(default)]
+    expression: Option<Expr>,
     previous_conditions: Vec<Condition>,
+    #[serde(default)]
+    scope: Scope,
+    #[serde(default)]
+    updates: bool,
+    /// empty if all instances are selected
+    #[serde(default)]
+    instances: Vec<String>,
+    #[serde(default)]
+    snippets: bool,
+    /// the match mode for the next condition
+    #[serde(default)]
+    mode: MatchMode,
 }
 
 impl TagSelection {
+    /// The rule with all conditions selected so far
+    fn filter(&self) -> Filter {
+        let conditions = self
+            .previous_conditions
+            .iter()
+            .cloned()
+            .map(Expr::Condition);
+        let expression = match &self.expression {
+            Some(expression) if self.previous_conditions.is_empty() => expression.clone(),
+            Some(expression) => {
+                Expr::And([expression.clone()].into_iter().chain(conditions).collect())
+            }
+            None => Expr::And(conditions.collect()),
+        };
+
+        Filter {
+            expression,
+            scope: self.scope,
+            updates: self.updates,
+            instances: self.instances.clone(),
+            snippets: self.snippets,
+        }
+    }
+
+    fn buttons(&self, instances: &[Instance]) -> Vec<TagButton> {
+        // there's nothing to choose from with a single instance
+        let instance_buttons =
+            instances
+                .iter()
+                .filter(|_| instances.len() > 1)
+                .map(|i| TagButton::Instance {
+                    name: i.name.clone(),
+                    selected: self.instances.is_empty() || self.instances.contains(&i.name),
+                });
+
+        // snippets are only available for conditions on the full text
+        let has_fulltext = self
+            .previous_conditions
+            .iter()
+            .chain(self.expression.iter().flat_map(Expr::conditions))
+            .any(|c| c.tag == Tag::Volltext);
+        let snippets_button = has_fulltext.then_some(TagButton::Snippets(self.snippets));
+
+        Tag::TAGS
+            .iter()
+            .copied()
+            .map(TagButton::Select)
+            .chain([
+                TagButton::Mode(self.mode),
+                TagButton::Scope(self.scope),
+                TagButton::Updates(self.updates),
+            ])
+            .chain(snippets_button)
+            .chain(instance_buttons)
+            .chain([TagButton::Test, TagButton::Save])
+            .collect()
+    }
+
     pub(super) async fn handle_message(
         self,
         cx: HandleMessage<'_>,
         channel: Option<SelectedChannel>,
     ) -> HandlerResult {
-        match buttons().match_action(cx.message) {
+        let instances = &cx.inner.instances;
+
+        match self.buttons(instances).match_action(cx.input(), cx.locale) {
             Some(TagButton::Save) => {
-                let chat_id = cx.selected_chat(&channel).await?;
+                let recipient = cx.selected_chat(&channel).await?;
 
                 cx.inner
                     .database
-                    .update_filter(chat_id, &|filters| {
-                        filters.push(Filter {
-                            conditions: self.previous_conditions.clone(),
-                        });
+                    .update_filter(recipient, &|filters| {
+                        filters.push(self.filter());
                     })
                     .await?;
 
-                let (text, entities) = concat!(
-                    "✅ Die Regel für ",
-                    SelectedChannel::chat_selection_accusative(&channel),
-                    " wurde gespeichert und ist nun aktiv!"
-                )
-                .to_message()?;
+                let target = SelectedChannel::chat_selection_accusative(&channel, cx.locale);
+                let (text, entities) =
+                    tr_msg!(cx.locale, "new_rule.saved", target = target).to_message()?;
 
                 cx.reset_dialogue(channel).await?;
 
                 respond!(cx, text, entities, reply_markup = remove_keyboard()).await
             }
+            Some(TagButton::Scope(scope)) => {
+                let scope = scope.next();
+                let text = tr!(cx.locale, "new_rule.scope", scope = scope.label(cx.locale));
+                let state = TagSelection { scope, ..self };
+                let reply_markup = state.buttons(instances).keyboard_markup(cx.locale);
+
+                cx.update_dialogue(state, channel).await?;
+                respond!(cx, text, reply_markup).await
+            }
+            Some(TagButton::Test) => {
+                let (text, entities) = command_test_rule::dry_run(cx, &self.filter()).await?;
+                let reply_markup = self.buttons(instances).keyboard_markup(cx.locale);
+                respond!(cx, text, entities, reply_markup).await
+            }
+            Some(TagButton::Mode(mode)) => {
+                let mode = mode.next();
+                let text = tr!(
+                    cx.locale,
+                    "new_rule.mode",
+                    description = mode.description(cx.locale)
+                );
+                let state = TagSelection { mode, ..self };
+                let reply_markup = state.buttons(instances).keyboard_markup(cx.locale);
+
+                cx.update_dialogue(state, channel).await?;
+                respond!(cx, text, reply_markup).await
+            }
+            Some(TagButton::Updates(updates)) => {
+                let updates = !updates;
+                let text = if updates {
+                    tr!(cx.locale, "new_rule.updates_on")
+                } else {
+                    tr!(cx.locale, "new_rule.updates_off")
+                };
+                let state = TagSelection { updates, ..self };
+                let reply_markup = state.buttons(instances).keyboard_markup(cx.locale);
+
+                cx.update_dialogue(state, channel).await?;
+                respond!(cx, text, reply_markup).await
+            }
+            Some(TagButton::Snippets(snippets)) => {
+                let snippets = !snippets;
+                let text = if snippets {
+                    tr!(cx.locale, "new_rule.snippets_on")
+                } else {
+                    tr!(cx.locale, "new_rule.snippets_off")
+                };
+                let state = TagSelection { snippets, ..self };
+                let reply_markup = state.buttons(instances).keyboard_markup(cx.locale);
+
+                cx.update_dialogue(state, channel).await?;
+                respond!(cx, text, reply_markup).await
+            }
+            Some(TagButton::Instance { name, selected }) => {
+                let selection: Vec<String> = instances
+                    .iter()
+                    .map(|i| &i.name)
+                    .filter(|i| {
+                        if **i == name {
+                            !selected
+                        } else {
+                            self.instances.is_empty() || self.instances.contains(i)
+                        }
+                    })
+                    .cloned()
+                    .collect();
+
+                if selection.is_empty() {
+                    let text = tr!(cx.locale, "new_rule.instances_empty");
+                    let reply_markup = self.buttons(instances).keyboard_markup(cx.locale);
+                    return respond!(cx, text, reply_markup).await;
+                }
+
+                let text = tr!(cx.locale, "new_rule.scope", scope = selection.join(", "));
+
+                // a rule for all instances also covers instances that are added later on
+                let selection = if selection.len() == instances.len() {
+                    vec![]
+                } else {
+                    selection
+                };
+                let state = TagSelection {
+                    instances: selection,
+                    ..self
+                };
+                let reply_markup = state.buttons(instances).keyboard_markup(cx.locale);
+
+                cx.update_dialogue(state, channel).await?;
+                respond!(cx, text, reply_markup).await
+            }
             Some(TagButton::Select(tag)) => {
+                let suggestions = known_values(cx, tag, "").await?;
+                let suggestions: Vec<String> = suggestions
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|(value, _)| value)
+                    .collect();
+
                 let state = PatternInput {
+                    expression: self.expression,
                     previous_conditions: self.previous_conditions,
+                    scope: self.scope,
+                    updates: self.updates,
+                    instances: self.instances,
+                    snippets: self.snippets,
+                    mode: self.mode,
                     tag,
+                    suggestions,
                 };
 
                 let mut msg = MessageBuilder::new();
 
-                msg.write("Du hast das Merkmal ")?;
-                msg.write(bold(tag.label()))?;
-                if let Some(desc) = tag.description() {
-                    write!(msg, " ({desc})")?;
-                }
-                msg.write(" gewählt.")?;
-
-                if !tag.examples().is_empty() {
-                    msg.write("\n\nMögliche Werte sind beispielsweise: ")?;
-                    for (i, example) in tag.examples().iter().enumerate() {
+                let label = from_fn(|msg| {
+                    msg.write(bold(tag.label(cx.locale)))?;
+                    if let Some(desc) = tag.description(cx.locale) {
+                        write!(msg, " ({desc})")?;
+                    }
+                    Ok(())
+                });
+                msg.write(tr_msg!(cx.locale, "new_rule.tag_selected", tag = label))?;
+
+                if !state.suggestions.is_empty() {
+                    msg.write("\n\n")?;
+                    msg.write(tr!(cx.locale, "new_rule.suggestions"))?;
+                } else if !cx.inner.branding.examples(tag).is_empty() {
+                    msg.write("\n\n")?;
+                    msg.write(tr!(cx.locale, "new_rule.examples"))?;
+                    for (i, example) in cx.inner.branding.examples(tag).iter().enumerate() {
                         if i != 0 {
                             msg.write(", ")?;
                         }
@@ -133,34 +374,28 @@ impl TagSelection {
                     }
                 }
 
-                msg.write(concat!(
-                    "\n\nGib nun ein Regex-Pattern ein, wie z. B. ",
-                    code("Wert"),
-                    " oder ",
-                    code("Option 1|Option 2"),
-                    ". Um die Bedingung \
-                     umzudrehen, beginne mit einem Ausrufezeichen – dann werden \
-                     alle Vorlagen, auf die das Pattern zutrifft, ausgeschlossen.",
+                msg.write("\n\n")?;
+                msg.write(tr!(
+                    cx.locale,
+                    "new_rule.value",
+                    description = state.mode.description(cx.locale)
                 ))?;
 
                 let (text, entities) = msg.build();
+                let reply_markup = state.reply_markup(cx.locale);
 
                 cx.update_dialogue(state, channel).await?;
-                respond!(
-                    cx,
-                    text,
-                    entities,
-                    reply_markup = force_reply("Regex-Pattern")
-                )
-                .await
+                respond!(cx, text, entities, reply_markup).await
             }
             None => {
-                let text = format!(
-                    "️Bitte wähle ein gültiges Merkmal aus, oder sende /{} zum Abbrechen",
-                    super::command_cancel::COMMAND.name
+                let text = tr!(
+                    cx.locale,
+                    "new_rule.invalid_tag",
+                    cancel = super::command_cancel::COMMAND.name
                 );
 
-                respond!(cx, text, reply_markup = buttons().keyboard_markup()).await
+                let reply_markup = self.buttons(instances).keyboard_markup(cx.locale);
+                respond!(cx, text, reply_markup).await
             }
         }
     }
@@ -168,76 +403,288 @@ impl TagSelection {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PatternInput {
+    #[serde(default)]
+    expression: Option<Expr>,
     previous_conditions: Vec<Condition>,
+    #[serde(default)]
+    scope: Scope,
+    #[serde(default)]
+    updates: bool,
+    #[serde(default)]
+    instances: Vec<String>,
+    #[serde(default)]
+    snippets: bool,
+    #[serde(default = "MatchMode::legacy")]
+    mode: MatchMode,
     tag: Tag,
+    /// known values offered as buttons
+    #[serde(default)]
+    suggestions: Vec<String>,
 }
 
 impl PatternInput {
-    pub(super) async fn handle_message(
+    fn reply_markup(&self, locale: Locale) -> ReplyMarkup {
+        if self.suggestions.is_empty() {
+            force_reply(input_placeholder(self.mode, locale))
+        } else {
+            self.suggestions
+                .iter()
+                .map(String::as_str)
+                .keyboard_markup(locale)
+        }
+    }
+
+    /// Offers the known values that contain the query as buttons
+    async fn search(
         self,
         cx: HandleMessage<'_>,
         channel: Option<SelectedChannel>,
+        query: &str,
     ) -> HandlerResult {
-        let Some(text) = &cx.message.text else {
-            return Err(Error::UnexpectedMessage);
+        let values = known_values(cx, self.tag, query).await?;
+        let text = if values.is_empty() {
+            tr!(cx.locale, "new_rule.search_empty")
+        } else {
+            tr!(cx.locale, "new_rule.search")
         };
 
-        let (negation, raw_pattern) = match text.strip_prefix('!') {
-            Some(pat) => (true, pat),
-            None => (false, text.as_str()),
+        let state = PatternInput {
+            suggestions: values
+                .into_iter()
+                .take(MAX_SUGGESTIONS)
+                .map(|(value, _)| value)
+                .collect(),
+            ..self
         };
+        let reply_markup = state.reply_markup(cx.locale);
+
+        cx.update_dialogue(state, channel).await?;
+        respond!(cx, text, reply_markup).await
+    }
+
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        let suggestion = self
+            .suggestions
+            .iter()
+            .map(String::as_str)
+            .match_action(cx.input(), cx.locale);
+        let (negation, pattern) = match suggestion {
+            // known values are taken literally
+            Some(value) if self.mode == MatchMode::Regex => (false, regex::escape(value)),
+            Some(value) => (false, value.to_string()),
+            None => {
+                let Some(text) = cx.text() else {
+                    return Err(Error::UnexpectedMessage);
+                };
 
-        let regex_check = if raw_pattern.contains('\n') {
-            let text = "❌ Ungültiges Regex-Pattern: Zeilenumbrüche sind nicht erlaubt. Bitte versuche es erneut.".to_message()?;
-            Err(text)
-        } else if let Err(e) = RegexBuilder::new(raw_pattern).size_limit(10000).build() {
-            let text = match e {
-                regex::Error::CompiledTooBig(_) => {
-                    "❌ Ungültiges Regex-Pattern: Das Pattern ist zu groß. Bitte versuche es erneut.".to_message()?
+                if let Some(query) = text.strip_prefix('?') {
+                    return self.search(cx, channel, query).await;
                 }
-                e => {
-                    concat!(
-                        "❌ Ungültiges Regex-Pattern. Bitte versuche es erneut. Tipp: Frage ChatGPT um Hilfe.\n\n",
-                        pre(e)
-                    ).to_message()?
+
+                match text.strip_prefix('!') {
+                    Some(pat) => (true, pat.to_string()),
+                    None => (false, text.to_string()),
                 }
-            };
-            Err(text)
-        } else {
-            Ok(())
+            }
         };
 
-        if let Err((text, entities)) = regex_check {
-            respond!(
-                cx,
-                text,
-                entities,
-                reply_markup = force_reply("Regex-Pattern")
-            )
-            .await?;
+        let condition = Condition {
+            tag: self.tag,
+            pattern,
+            negate: negation,
+            mode: self.mode,
+        };
+
+        if let Some((text, entities)) = pattern_error(&condition.regex(), cx.locale)? {
+            let reply_markup = self.reply_markup(cx.locale);
+            respond!(cx, text, entities, reply_markup).await?;
             return Ok(());
         }
 
         let mut conditions = self.previous_conditions;
-        conditions.push(Condition {
-            tag: self.tag,
-            pattern: raw_pattern.to_string(),
-            negate: negation,
-        });
-
-        let summary = Filter { conditions };
-        let text = format_args!(
-            "Bedingung hinzugefügt – aktuelle Regel:\n\n{summary}\n\
-            Wähle ein weiteres Merkmal oder tippe auf „Speichern“.",
-        )
-        .to_message()?
-        .0;
+        conditions.push(condition);
 
         let state = TagSelection {
-            previous_conditions: summary.conditions,
+            expression: self.expression,
+            previous_conditions: conditions,
+            scope: self.scope,
+            updates: self.updates,
+            instances: self.instances,
+            snippets: self.snippets,
+            mode: self.mode,
+        };
+        let filter = state.filter();
+        let snippets_hint = if self.tag == Tag::Volltext {
+            tr!(cx.locale, "new_rule.snippets_hint")
+        } else {
+            ""
         };
+        let text = tr!(
+            cx.locale,
+            "new_rule.condition_added",
+            summary = filter.describe(cx.locale),
+            hint = snippets_hint
+        );
+
+        let reply_markup = state
+            .buttons(&cx.inner.instances)
+            .keyboard_markup(cx.locale);
 
         cx.update_dialogue(state, channel).await?;
-        respond!(cx, text, reply_markup = buttons().keyboard_markup()).await
+        respond!(cx, text, reply_markup).await
+    }
+}
+
+pub(super) fn input_placeholder(mode: MatchMode, locale: Locale) -> &'static str {
+    match mode {
+        MatchMode::Regex => tr!(locale, "input.regex"),
+        _ => tr!(locale, "input.text"),
+    }
+}
+
+/// Returns an error message if the regex of a condition is not valid
+pub(super) fn pattern_error(
+    pattern: &str,
+    locale: Locale,
+) -> Result<Option<(String, Vec<MessageEntity>)>, Error> {
+    let message = if pattern.contains('\n') {
+        tr!(locale, "pattern.newline").to_message()?
+    } else if let Err(e) = RegexBuilder::new(pattern).size_limit(10000).build() {
+        match e {
+            regex::Error::CompiledTooBig(_) => tr!(locale, "pattern.too_big").to_message()?,
+            e => concat!(tr!(locale, "pattern.invalid"), "\n\n", pre(e)).to_message()?,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(message))
+}
+
+/// Returns an error message for the first condition of the expression with an invalid regex
+fn expression_error(
+    expression: &Expr,
+    locale: Locale,
+) -> Result<Option<(String, Vec<MessageEntity>)>, Error> {
+    for condition in expression.conditions() {
+        if let Some(message) = pattern_error(&condition.regex(), locale)? {
+            return Ok(Some(message));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Starts a rule from an expression in the textual syntax, see [`filter_syntax`]. Like
+/// for the selected conditions, the options are set before the rule is saved.
+async fn start_with_expression(
+    cx: HandleMessage<'_>,
+    channel: Option<SelectedChannel>,
+    input: &str,
+) -> HandlerResult {
+    let expression = match filter_syntax::parse(input) {
+        Ok(expression) => expression,
+        Err(e) => {
+            let (text, entities) = concat!(
+                tr!(
+                    cx.locale,
+                    "error.invalid_expression",
+                    error = e.describe(cx.locale)
+                ),
+                "\n\n",
+                tr_msg!(
+                    cx.locale,
+                    "new_rule.example",
+                    example = code(EXAMPLE_EXPRESSION)
+                )
+            )
+            .to_message()?;
+            return respond!(cx, text, entities).await;
+        }
+    };
+
+    if let Some((text, entities)) = expression_error(&expression, cx.locale)? {
+        return respond!(cx, text, entities).await;
+    }
+
+    let snippets_hint = if expression
+        .conditions()
+        .iter()
+        .any(|c| c.tag == Tag::Volltext)
+    {
+        tr!(cx.locale, "new_rule.snippets_hint")
+    } else {
+        ""
+    };
+    let state = TagSelection {
+        expression: Some(expression),
+        ..Default::default()
+    };
+    let text = tr!(
+        cx.locale,
+        "new_rule.expression_parsed",
+        summary = state.filter().describe(cx.locale),
+        hint = snippets_hint
+    );
+    let reply_markup = state
+        .buttons(&cx.inner.instances)
+        .keyboard_markup(cx.locale);
+
+    cx.update_dialogue(state, channel).await?;
+    respond!(cx, text, reply_markup).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TagSelection, expression_error};
+    use crate::filter_syntax::parse;
+    use crate::locale::Locale;
+    use crate::types::{Condition, Expr, MatchMode, Scope, Tag};
+
+    #[test]
+    fn test_expression_error() {
+        let error = |input: &str| expression_error(&parse(input).unwrap(), Locale::De).unwrap();
+
+        // text values are escaped, whatever characters they contain
+        assert!(error(r#"titel:"C++""#).is_none());
+        assert!(error(r#"titel:"(Radweg" & !gremium:"[Rat""#).is_none());
+        assert!(error(r#"titel:/(Radweg/"#).is_some());
+    }
+
+    #[test]
+    fn test_filter_with_expression() {
+        let expression = parse("gremium:Rat | art:Antrag").unwrap();
+        let condition = Condition {
+            tag: Tag::Title,
+            pattern: "Radweg".to_string(),
+            negate: false,
+            mode: MatchMode::Contains,
+        };
+
+        // the options apply to rules entered in the textual syntax as well
+        let state = TagSelection {
+            expression: Some(expression.clone()),
+            scope: Scope::Meetings,
+            updates: true,
+            ..Default::default()
+        };
+        let filter = state.filter();
+        assert_eq!(filter.expression, expression);
+        assert_eq!(filter.scope, Scope::Meetings);
+        assert!(filter.updates);
+
+        // further conditions are added to the expression
+        let state = TagSelection {
+            previous_conditions: vec![condition.clone()],
+            ..state
+        };
+        assert_eq!(
+            state.filter().expression,
+            Expr::And(vec![expression, Expr::Condition(condition)])
+        );
     }
 }
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use super::keyboard::{Button, Choice, Choices, remove_keyboard};
+use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
+use crate::locale::Locale;
+
+pub const COMMAND: Command = Command {
+    name: "rechte",
+
+    group_admin: true,
+    group_member: false,
+    private_chat: false,
+    admin: false,
+    owner: false,
+};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PermissionSelection(());
+
+/// Whether all members may manage the rules, not only the administrators
+#[derive(Debug, Clone, Copy)]
+struct PermissionChoice(bool);
+
+impl PermissionChoice {
+    fn label(self, locale: Locale) -> &'static str {
+        if self.0 {
+            tr!(locale, "permissions.members")
+        } else {
+            tr!(locale, "permissions.admins")
+        }
+    }
+}
+
+impl<'a> Choice<'a> for PermissionChoice {
+    type Action = bool;
+
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
+        Button::Text {
+            text: self.label(locale).into(),
+            action: |x| x.0,
+        }
+    }
+}
+
+fn choices() -> &'static [PermissionChoice; 2] {
+    &[PermissionChoice(false), PermissionChoice(true)]
+}
+
+impl PermissionSelection {
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        let Some(member_managed) = choices().match_action(cx.input(), cx.locale) else {
+            let text = tr!(
+                cx.locale,
+                "dialogue.use_buttons",
+                cancel = super::command_cancel::COMMAND.name
+            );
+            let reply_markup = choices().keyboard_markup(cx.locale);
+            return respond!(cx, text, reply_markup).await;
+        };
+
+        cx.reset_dialogue(channel).await?;
+        cx.inner
+            .database
+            .set_member_managed(cx.chat_id(), member_managed)
+            .await?;
+
+        let who = PermissionChoice(member_managed).label(cx.locale);
+        let text = tr!(cx.locale, "permissions.set", who = who);
+        respond!(cx, text, reply_markup = remove_keyboard()).await
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
+    if !cx.is_group() {
+        return respond!(cx, text = tr!(cx.locale, "permissions.only_groups")).await;
+    }
+
+    let member_managed = cx.inner.database.get_member_managed(cx.chat_id()).await?;
+    let text = format!(
+        "{}\n\n{}",
+        tr!(
+            cx.locale,
+            "permissions.current",
+            who = PermissionChoice(member_managed).label(cx.locale)
+        ),
+        tr!(cx.locale, "permissions.question")
+    );
+
+    let dialogue = cx.get_dialogue().await?;
+    cx.update_dialogue(PermissionSelection::default(), dialogue.channel)
+        .await?;
+    respond!(
+        cx,
+        text,
+        reply_markup = choices().keyboard_markup(cx.locale)
+    )
+    .await
+}
@@ -4,21 +4,19 @@ use super::{Command, HandleMessage, HandlerResult};
 
 pub const COMMAND: Command = Command {
     name: "datenschutz",
-    description: "Zeige die Datenschutzerklärung an",
 
     private_chat: true,
     group_member: true,
     group_admin: true,
     admin: true,
+    owner: false,
 };
 
-const TEXT: &str = include_str!("privacy.html");
-
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
-    let mut text = String::from(TEXT);
-    if let Some(owner) = &cx.inner.owner {
-        text += "\nBei Fragen kontaktiere mich direkt über Telegram: @";
-        text += owner;
+    let mut text = cx.inner.branding.privacy_policy.clone();
+    if let Some(contact) = &cx.inner.branding.contact {
+        text += "\n";
+        text += &tr!(cx.locale, "privacy.contact", contact = contact);
     }
     respond!(cx, text, parse_mode = ParseMode::Html).await
 }
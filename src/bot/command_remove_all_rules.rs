@@ -1,18 +1,19 @@
 use serde::{Deserialize, Serialize};
-use telegram_message_builder::{WriteToMessage, concat};
+use telegram_message_builder::WriteToMessage;
 
 use super::keyboard::{Button, Choice, Choices};
 use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
 use crate::bot::keyboard::remove_keyboard;
+use crate::locale::Locale;
 
 pub const COMMAND: Command = Command {
     name: "alle_regeln_loeschen",
-    description: "Entferne alle Regeln",
 
     group_admin: true,
-    group_member: true,
+    group_member: false,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,11 +25,11 @@ struct ConfirmChoice(bool);
 impl<'a> Choice<'a> for ConfirmChoice {
     type Action = bool;
 
-    fn button(&self) -> Button<'a, Self> {
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
         let text = if self.0 {
-            "⚠️ Ja, alles löschen!"
+            tr!(locale, "remove_all.confirm")
         } else {
-            "Abbrechen"
+            tr!(locale, "dialogue.cancel")
         };
 
         Button::Text {
@@ -48,16 +49,16 @@ impl ConfirmRemoveAllFilters {
         cx: HandleMessage<'_>,
         channel: Option<SelectedChannel>,
     ) -> HandlerResult {
-        let chat_id = cx.selected_chat(&channel).await?;
+        let recipient = cx.selected_chat(&channel).await?;
 
-        match buttons().match_action(cx.message) {
+        match buttons().match_action(cx.input(), cx.locale) {
             Some(true) => {
-                let removed = cx.inner.database.remove_subscription(chat_id).await?;
+                let removed = cx.inner.database.remove_subscription(recipient).await?;
 
                 let text = if removed {
-                    "✅ Deine Regeln wurden gelöscht!"
+                    tr!(cx.locale, "remove_all.done")
                 } else {
-                    "❌ Die Regeln konnten leider nicht gelöscht werden. Bitte versuche es erneut."
+                    tr!(cx.locale, "remove_all.failed")
                 };
 
                 if channel.is_none() {
@@ -73,7 +74,7 @@ impl ConfirmRemoveAllFilters {
 
                 respond!(
                     cx,
-                    text = "Der Vorgang wurde abgebrochen!",
+                    text = tr!(cx.locale, "dialogue.cancelled"),
                     reply_markup = remove_keyboard()
                 )
                 .await
@@ -84,25 +85,19 @@ impl ConfirmRemoveAllFilters {
 
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
     let dialogue = cx.get_dialogue().await?;
-    let chat_id = cx.selected_chat(&dialogue.channel).await?;
-    let filters = cx.inner.database.get_filters(chat_id).await?;
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let filters = cx.inner.database.get_filters(recipient).await?;
 
     let (text, entities) = {
-        let target = SelectedChannel::chat_selection_accusative(&dialogue.channel);
+        let target = SelectedChannel::chat_selection_accusative(&dialogue.channel, cx.locale);
 
         if filters.is_empty() {
             let (text, entities) =
-                concat!("Zur Zeit sind keine Regeln für ", target, " aktiv!").to_message()?;
+                tr_msg!(cx.locale, "rules.none", target = target).to_message()?;
             return respond!(cx, text, entities, reply_markup = remove_keyboard()).await;
         }
 
-        concat!(
-            "🗑️ Du bist dabei, alle Regeln für ",
-            target,
-            " zu entfernen.\n\n",
-            "Bist du sicher? Danach bekommst du erst mal keine Benachrichtigungen mehr."
-        )
-        .to_message()?
+        tr_msg!(cx.locale, "remove_all.question", target = target).to_message()?
     };
 
     let state = ConfirmRemoveAllFilters(());
@@ -111,7 +106,7 @@ pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerRe
         cx,
         text,
         entities,
-        reply_markup = buttons().keyboard_markup()
+        reply_markup = buttons().keyboard_markup(cx.locale)
     )
     .await
 }
@@ -1,19 +1,20 @@
 use serde::{Deserialize, Serialize};
-use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, concat};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold};
 
 use super::keyboard::{Button, Choice, Choices};
 use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
 use crate::bot::keyboard::remove_keyboard;
+use crate::locale::Locale;
 use crate::types::Filter;
 
 pub const COMMAND: Command = Command {
     name: "regel_loeschen",
-    description: "Lösche eine bestehende Regel",
 
     group_admin: true,
-    group_member: true,
+    group_member: false,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,9 +25,9 @@ pub struct RemoveFilterSelection {
 impl<'a> Choice<'a> for (usize, &'a Filter) {
     type Action = Self;
 
-    fn button(&self) -> Button<'a, Self::Action> {
+    fn button(&self, locale: Locale) -> Button<'a, Self::Action> {
         Button::Text {
-            text: format!("Regel {}", self.0 + 1).into(),
+            text: tr!(locale, "rule.number", number = self.0 + 1).into(),
             action: |x| x,
         }
     }
@@ -38,14 +39,19 @@ impl RemoveFilterSelection {
         cx: HandleMessage<'_>,
         channel: Option<SelectedChannel>,
     ) -> HandlerResult {
-        let chat_id = cx.selected_chat(&channel).await?;
-
-        match self.filters.iter().enumerate().match_action(cx.message) {
+        let recipient = cx.selected_chat(&channel).await?;
+
+        match self
+            .filters
+            .iter()
+            .enumerate()
+            .match_action(cx.input(), cx.locale)
+        {
             Some((i, filter)) => {
                 let removed = cx
                     .inner
                     .database
-                    .update_filter(chat_id, &|filters| {
+                    .update_filter(recipient, &|filters| {
                         if filters[i] == *filter {
                             filters.remove(i);
                             true
@@ -56,20 +62,21 @@ impl RemoveFilterSelection {
                     .await?;
 
                 let text = if removed {
-                    "✅ Die Regel wurde gelöscht!"
+                    tr!(cx.locale, "remove_rule.done")
                 } else {
-                    "❌ Die Regel konnte leider nicht gelöscht werden. Bitte versuche es erneut."
+                    tr!(cx.locale, "remove_rule.failed")
                 };
 
                 cx.reset_dialogue(channel).await?;
                 respond!(cx, text, reply_markup = remove_keyboard()).await
             }
             None => {
-                let text = format!(
-                    "Bitte nutze die Schaltflächen, um einen Regel auszuwählen, oder sende /{} zum Abbrechen",
-                    super::command_cancel::COMMAND.name
+                let text = tr!(
+                    cx.locale,
+                    "dialogue.select_rule",
+                    cancel = super::command_cancel::COMMAND.name
                 );
-                let reply_markup = self.filters.iter().enumerate().keyboard_markup();
+                let reply_markup = self.filters.iter().enumerate().keyboard_markup(cx.locale);
                 respond!(cx, text, reply_markup).await
             }
         }
@@ -78,29 +85,29 @@ impl RemoveFilterSelection {
 
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
     let dialogue = cx.get_dialogue().await?;
-    let chat_id = cx.selected_chat(&dialogue.channel).await?;
-    let filters = cx.inner.database.get_filters(chat_id).await?;
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let filters = cx.inner.database.get_filters(recipient).await?;
 
     if filters.is_empty() {
-        let target = SelectedChannel::chat_selection_accusative(&dialogue.channel);
-        let (text, entities) =
-            concat!("Zur Zeit sind keine Regeln für ", target, " aktiv!").to_message()?;
+        let target = SelectedChannel::chat_selection_accusative(&dialogue.channel, cx.locale);
+        let (text, entities) = tr_msg!(cx.locale, "rules.none", target = target).to_message()?;
         return respond!(cx, text, entities, reply_markup = remove_keyboard()).await;
     }
 
     let mut msg = MessageBuilder::new();
 
-    msg.write("Aktuelle Auswahl: ")?;
-    msg.write(SelectedChannel::chat_selection(&dialogue.channel))?;
-    msg.write("\n\nWähle einen der folgenden Regeln zum Löschen aus:\n\n")?;
+    let chat = SelectedChannel::chat_selection(&dialogue.channel, cx.locale);
+    msg.writeln(tr_msg!(cx.locale, "selection.current", chat = chat))?;
+    msg.write(tr!(cx.locale, "remove_rule.question"))?;
+    msg.write("\n\n")?;
 
     for (i, f) in filters.iter().enumerate() {
-        msg.writeln(bold(concat!("Regel ", i + 1)))?;
-        msg.writeln(f)?;
+        msg.writeln(bold(tr!(cx.locale, "rule.number", number = i + 1)))?;
+        msg.writeln(f.describe(cx.locale))?;
     }
 
     let (text, entities) = msg.build();
-    let reply_markup = filters.iter().enumerate().keyboard_markup();
+    let reply_markup = filters.iter().enumerate().keyboard_markup(cx.locale);
     let state = RemoveFilterSelection { filters };
 
     cx.update_dialogue(state, dialogue.channel).await?;
@@ -1,37 +1,36 @@
-use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, concat};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold};
 
 use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
 use crate::bot::keyboard::remove_keyboard;
 
 pub const COMMAND: Command = Command {
     name: "regeln",
-    description: "Zeige alle bestehenden Regeln an",
 
     group_admin: true,
     group_member: true,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
     let dialogue = cx.get_dialogue().await?;
-    let chat_id = cx.selected_chat(&dialogue.channel).await?;
-    let filters = cx.inner.database.get_filters(chat_id).await?;
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let filters = cx.inner.database.get_filters(recipient).await?;
 
-    let target = SelectedChannel::chat_selection_accusative(&dialogue.channel);
+    let target = SelectedChannel::chat_selection_accusative(&dialogue.channel, cx.locale);
 
     let (text, entities) = if filters.is_empty() {
-        concat!("Es sind keine Regeln für ", target, " aktiv.").to_message()?
+        tr_msg!(cx.locale, "rules.empty", target = target).to_message()?
     } else {
         let mut msg = MessageBuilder::new();
 
-        msg.write("Zur Zeit sind die folgenden Regeln für ")?;
-        msg.write(target)?;
-        msg.write(" aktiv:\n\n")?;
+        msg.write(tr_msg!(cx.locale, "rules.list", target = target))?;
+        msg.write("\n\n")?;
 
         for (i, f) in filters.iter().enumerate() {
-            msg.writeln(bold(concat!("Regel ", i + 1)))?;
-            msg.writeln(f)?;
+            msg.writeln(bold(tr!(cx.locale, "rule.number", number = i + 1)))?;
+            msg.writeln(f.describe(cx.locale))?;
         }
 
         msg.build()
@@ -0,0 +1,421 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use frankenstein::AsyncTelegramApi;
+use frankenstein::types::MessageEntity;
+use serde::{Deserialize, Serialize};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold};
+use tokio::sync::Mutex;
+
+use super::keyboard::{Button, Choice, Choices};
+use super::{Command, Error, HandleMessage, HandlerResult, SelectedChannel};
+use crate::database::{SharedDatabaseConnection, StreamId};
+use crate::digest::DigestItem;
+use crate::locale::Locale;
+use crate::types::{Audience, Message, Tag};
+
+pub const COMMAND: Command = Command {
+    name: "suche",
+
+    group_admin: true,
+    group_member: true,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+/// Number of recent notifications that are searched
+const SAMPLE_SIZE: usize = 1000;
+
+/// Maximum number of results that are offered
+const MAX_RESULTS: usize = 50;
+
+/// Number of results listed on a page
+const PAGE_SIZE: usize = 5;
+
+/// How long the recent notifications are kept in memory before they are loaded again
+const INDEX_TTL: Duration = Duration::from_secs(60);
+
+/// A notification with the searched texts in lowercase
+#[derive(Debug)]
+pub(super) struct Entry {
+    pub id: StreamId,
+    pub message: Message,
+    headline: String,
+    /// the values of the tags, except for the full text, which is stored separately
+    tags: Vec<String>,
+}
+
+impl Entry {
+    fn new(id: StreamId, message: Message) -> Self {
+        let headline = message.request.text.lines().next().unwrap_or_default();
+        let tags = message
+            .tags
+            .iter()
+            .filter(|(tag, _)| *tag != Tag::Volltext)
+            .map(|(_, value)| value.to_lowercase())
+            .collect();
+
+        Self {
+            id,
+            headline: headline.to_lowercase(),
+            tags,
+            message,
+        }
+    }
+}
+
+type Entries = Arc<Vec<Entry>>;
+
+/// The recent notifications, kept in memory, as inline queries arrive with every keystroke
+#[derive(Debug, Default)]
+pub(super) struct SearchIndex(Mutex<Option<(Instant, Entries)>>);
+
+impl SearchIndex {
+    /// Returns the recent notifications, newest first
+    pub(super) async fn entries(
+        &self,
+        database: &SharedDatabaseConnection,
+    ) -> HandlerResult<Entries> {
+        let mut cached = self.0.lock().await;
+        if let Some((loaded, entries)) = &*cached
+            && loaded.elapsed() < INDEX_TTL
+        {
+            return Ok(entries.clone());
+        }
+
+        let messages = database.get_recent_messages(SAMPLE_SIZE).await?;
+        let entries: Entries = Arc::new(
+            messages
+                .into_iter()
+                .map(|(id, message)| Entry::new(id, message))
+                .collect(),
+        );
+        *cached = Some((Instant::now(), entries.clone()));
+        Ok(entries)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchResults {
+    query: String,
+    /// the matching notifications, best match first
+    results: Vec<StreamId>,
+    page: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SearchChoice {
+    /// a result by its position in the list
+    Result(usize),
+    Previous,
+    Next,
+}
+
+impl<'a> Choice<'a> for SearchChoice {
+    type Action = Self;
+
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
+        let text = match self {
+            Self::Result(i) => (i + 1).to_string().into(),
+            Self::Previous => tr!(locale, "search.previous").into(),
+            Self::Next => tr!(locale, "search.next").into(),
+        };
+
+        Button::Text {
+            text,
+            action: |x| x,
+        }
+    }
+}
+
+/// Splits the query into lowercase words
+fn terms(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// Ranks a notification: each term must occur in the headline or in one of the tags, and
+/// counts twice if it occurs in the headline. Returns `None` if a term doesn't occur.
+fn score(entry: &Entry, terms: &[String]) -> Option<usize> {
+    terms.iter().try_fold(0, |score, term| {
+        if entry.headline.contains(term.as_str()) {
+            Some(score + 2)
+        } else if entry.tags.iter().any(|value| value.contains(term.as_str())) {
+            Some(score + 1)
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the notifications matching the query, best match first. Among equally good
+/// matches, the order of `entries` is kept, so newer notifications should come first.
+pub(super) fn search<'a>(entries: &'a [Entry], query: &str) -> Vec<&'a Entry> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return vec![];
+    }
+
+    let mut items: Vec<DigestItem> = vec![];
+    let mut results = vec![];
+    for entry in entries {
+        let message = &entry.message;
+
+        // follow-ups, digests and announcements are no notifications of their own
+        if !matches!(message.audience, Audience::Subscribers | Audience::Chat(_)) {
+            continue;
+        }
+
+        let Some(score) = score(entry, &terms) else {
+            continue;
+        };
+
+        // papers replayed for single chats would be listed repeatedly
+        let item = DigestItem::new(entry.id, message);
+        if items
+            .iter()
+            .any(|x| x.headline == item.headline && x.link == item.link)
+        {
+            continue;
+        }
+        items.push(item);
+        results.push((score, entry));
+    }
+
+    results.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    results.into_iter().map(|(_, entry)| entry).collect()
+}
+
+impl SearchResults {
+    fn pages(&self) -> usize {
+        self.results.len().div_ceil(PAGE_SIZE)
+    }
+
+    fn choices(&self) -> Vec<SearchChoice> {
+        let start = self.page * PAGE_SIZE;
+        let end = (start + PAGE_SIZE).min(self.results.len());
+
+        let mut choices: Vec<_> = (start..end).map(SearchChoice::Result).collect();
+        if self.page > 0 {
+            choices.push(SearchChoice::Previous);
+        }
+        if self.page + 1 < self.pages() {
+            choices.push(SearchChoice::Next);
+        }
+        choices
+    }
+
+    /// Lists the results of the current page
+    async fn page_message(
+        &self,
+        cx: HandleMessage<'_>,
+    ) -> HandlerResult<(String, Vec<MessageEntity>)> {
+        let mut msg = MessageBuilder::new();
+        msg.write("🔎 ")?;
+        msg.writeln(bold(tr!(cx.locale, "search.title", query = &self.query)))?;
+        msg.writeln(tr!(
+            cx.locale,
+            "search.page",
+            count = self.results.len(),
+            page = self.page + 1,
+            pages = self.pages()
+        ))?;
+
+        let start = self.page * PAGE_SIZE;
+        for (i, &id) in self.results.iter().enumerate().skip(start).take(PAGE_SIZE) {
+            let Some(message) = cx.inner.database.get_message(id).await? else {
+                continue;
+            };
+
+            // a page must not exceed the character limit, however long the headlines are
+            let backup = msg.clone();
+            let written = write!(msg, "\n{}. ", i + 1)
+                .and_then(|()| DigestItem::new(id, &message).write_to(&mut msg));
+            if written.is_err() {
+                msg = backup;
+                break;
+            }
+        }
+
+        msg.write("\n\n")?;
+        msg.write(tr!(cx.locale, "search.select"))?;
+        Ok(msg.build())
+    }
+
+    async fn respond(&self, cx: HandleMessage<'_>) -> HandlerResult {
+        let (text, entities) = self.page_message(cx).await?;
+        let reply_markup = self.choices().keyboard_markup(cx.locale);
+        respond!(cx, text, entities, reply_markup).await
+    }
+
+    pub(super) async fn handle_message(
+        mut self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        // another search term may be sent right away
+        if let Some(query) = cx.text() {
+            return run_search(cx, query, channel).await;
+        }
+
+        match self.choices().match_action(cx.input(), cx.locale) {
+            Some(SearchChoice::Result(i)) => send_result(cx, self.results[i]).await,
+            Some(SearchChoice::Previous) => {
+                self.page -= 1;
+                self.respond(cx).await?;
+                cx.update_dialogue(self, channel).await
+            }
+            Some(SearchChoice::Next) => {
+                self.page += 1;
+                self.respond(cx).await?;
+                cx.update_dialogue(self, channel).await
+            }
+            None => Err(Error::UnexpectedMessage),
+        }
+    }
+}
+
+/// Sends the notification again, with the links to Allris and the PDF file. The list of
+/// results is kept, so that further results can be opened.
+async fn send_result(cx: HandleMessage<'_>, id: StreamId) -> HandlerResult {
+    let Some(message) = cx.inner.database.get_message(id).await? else {
+        return respond!(cx, text = tr!(cx.locale, "search.expired")).await;
+    };
+
+    let mut request = message.request;
+    request.chat_id = cx.chat_id().into();
+    request.message_thread_id = cx.recipient().thread_id;
+    cx.inner.bot.send_message(&request).await?;
+    Ok(())
+}
+
+async fn run_search(
+    cx: HandleMessage<'_>,
+    query: &str,
+    channel: Option<SelectedChannel>,
+) -> HandlerResult {
+    let entries = cx.inner.search_index.entries(&cx.inner.database).await?;
+    let oldest = entries.last().and_then(|entry| entry.id.time());
+
+    let results: Vec<StreamId> = search(&entries, query)
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|entry| entry.id)
+        .collect();
+
+    if results.is_empty() {
+        cx.reset_dialogue(channel).await?;
+        let text = match oldest {
+            Some(oldest) => tr!(
+                cx.locale,
+                "search.no_results",
+                query = query,
+                since = oldest.format(tr!(cx.locale, "date.format"))
+            ),
+            None => tr!(cx.locale, "test.no_messages").to_string(),
+        };
+        return respond!(cx, text).await;
+    }
+
+    let state = SearchResults {
+        query: query.to_string(),
+        results,
+        page: 0,
+    };
+    state.respond(cx).await?;
+    cx.update_dialogue(state, channel).await
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    let dialogue = cx.get_dialogue().await?;
+
+    match param.map(str::trim).filter(|query| !query.is_empty()) {
+        Some(query) => run_search(cx, query, dialogue.channel).await,
+        None => {
+            let text = tr!(cx.locale, "search.usage", command = COMMAND.name);
+            respond!(cx, text).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use frankenstein::methods::SendMessageParams;
+
+    use super::{Entry, search};
+    use crate::types::{Audience, Message, MessageKind, Tag};
+
+    fn message(text: &str, tags: &[(Tag, &str)], audience: Audience) -> Message {
+        Message {
+            request: SendMessageParams::builder().chat_id(0).text(text).build(),
+            tags: tags
+                .iter()
+                .map(|(tag, value)| (*tag, value.to_string()))
+                .collect(),
+            kind: MessageKind::Paper,
+            audience,
+            instance: None,
+            fulltext: None,
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let messages = vec![
+            message(
+                "Sanierung der Turnhalle\nNeue Beleuchtung",
+                &[
+                    (Tag::Gremium, "Ausschuss für Radverkehr"),
+                    (Tag::Volltext, "Das Flutlicht der Turnhalle wird erneuert."),
+                ],
+                Audience::Subscribers,
+            ),
+            message(
+                "Neuer Radweg an der Hauptstraße",
+                &[(Tag::Art, "Antrag")],
+                Audience::Subscribers,
+            ),
+            message(
+                "Neuer Radweg an der Hauptstraße",
+                &[(Tag::Art, "Antrag")],
+                Audience::Chat((-1).into()),
+            ),
+            message(
+                "Radweg-Beschluss",
+                &[],
+                Audience::RecipientsOf("1-0".parse().unwrap()),
+            ),
+        ];
+        let entries: Vec<Entry> = messages
+            .into_iter()
+            .enumerate()
+            .map(|(i, msg)| Entry::new(format!("{}-0", 10 - i).parse().unwrap(), msg))
+            .collect();
+
+        let headlines = |query: &str| -> Vec<String> {
+            search(&entries, query)
+                .into_iter()
+                .map(|entry| entry.message.request.text.clone())
+                .collect()
+        };
+
+        // matches in the headline rank higher, duplicates and follow-ups are omitted
+        assert_eq!(
+            headlines("rad"),
+            [
+                "Neuer Radweg an der Hauptstraße",
+                "Sanierung der Turnhalle\nNeue Beleuchtung"
+            ]
+        );
+        // all terms must occur
+        assert_eq!(
+            headlines("RADWEG antrag"),
+            ["Neuer Radweg an der Hauptstraße"]
+        );
+        // of the text, only the first line is searched
+        assert!(headlines("Beleuchtung").is_empty());
+        // the full text is left out, as it's only loaded when a notification is sent
+        assert!(headlines("Flutlicht").is_empty());
+        assert!(headlines("  ").is_empty());
+    }
+}
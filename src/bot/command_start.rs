@@ -2,12 +2,12 @@ use super::{Command, HandleMessage, HandlerResult, command_help, command_privacy
 
 pub const COMMAND: Command = Command {
     name: "start",
-    description: "Zeige die Hilfenachricht an",
 
     group_admin: true,
     group_member: true,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
@@ -0,0 +1,86 @@
+use std::cmp::Reverse;
+
+use chrono_tz::Europe::Berlin;
+use telegram_message_builder::{MessageBuilder, bold, italic};
+
+use super::command_chats::{backlog, chat_line};
+use super::{Command, HandleMessage, HandlerResult};
+use crate::types::ChatKind;
+
+pub const COMMAND: Command = Command {
+    name: "statistik",
+
+    group_admin: false,
+    group_member: false,
+    private_chat: false,
+    admin: true,
+    owner: true,
+};
+
+/// Up to how many chats with unprocessed messages are listed
+const MAX_BACKLOGS: usize = 5;
+
+pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
+    let database = &cx.inner.database;
+    let mut chats = database.get_chat_overviews().await?;
+    let stream_length = database.get_stream_length().await?;
+    let update_status = database.get_update_status().await?;
+    let locale = cx.locale;
+
+    let mut msg = MessageBuilder::new();
+    msg.writeln(bold(tr!(locale, "statistics.title")))?;
+    msg.write("\n")?;
+
+    msg.writeln(tr!(locale, "statistics.chats", count = chats.len()))?;
+    for &kind in ChatKind::KINDS {
+        let count = chats
+            .iter()
+            .filter(|chat| ChatKind::of(chat.recipient) == kind)
+            .count();
+        writeln!(msg, "{} {}: {count}", kind.emoji(), kind.label(locale))?;
+    }
+    msg.write("\n")?;
+
+    let rules: usize = chats.iter().map(|chat| chat.rules).sum();
+    let max = chats
+        .iter()
+        .map(|chat| chat.rules)
+        .max()
+        .unwrap_or_default();
+    msg.writeln(tr!(locale, "statistics.rules", count = rules, max = max))?;
+    msg.writeln(tr!(locale, "statistics.stream", count = stream_length))?;
+
+    chats.retain(|chat| chat.backlog > 0);
+    chats.sort_by_key(|chat| Reverse(chat.backlog));
+    msg.writeln(tr!(locale, "statistics.backlog", chats = chats.len()))?;
+    for chat in chats.iter().take(MAX_BACKLOGS) {
+        msg.write(chat_line(chat.recipient))?;
+        writeln!(msg, ": {}", backlog(chat))?;
+    }
+    msg.write("\n")?;
+
+    msg.writeln(bold(tr!(locale, "statistics.updates")))?;
+    for instance in &cx.inner.instances {
+        let Some(status) = update_status.get(&instance.name) else {
+            msg.writeln(tr!(
+                locale,
+                "statistics.no_update",
+                instance = instance.name
+            ))?;
+            continue;
+        };
+
+        let finished = status.finished.with_timezone(&Berlin);
+        let finished = finished.format(tr!(locale, "datetime.format"));
+        match &status.error {
+            None => writeln!(msg, "✅ {}: {finished}", instance.name)?,
+            Some(error) => {
+                writeln!(msg, "❌ {}: {finished}", instance.name)?;
+                msg.writeln(italic(error))?;
+            }
+        }
+    }
+
+    let (text, entities) = msg.build();
+    respond!(cx, text, entities).await
+}
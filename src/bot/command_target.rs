@@ -4,15 +4,16 @@ use telegram_message_builder::{WriteToMessage, concat};
 
 use super::keyboard::{Button, Choice, Choices, remove_keyboard};
 use super::{Command, Error, HandleMessage, HandlerResult, SelectedChannel};
+use crate::locale::Locale;
 
 pub const COMMAND: Command = Command {
     name: "ziel",
-    description: "Lege fest, für welchen Chat du Benachrichtigungen konfigurieren möchtest",
 
     group_admin: false,
     group_member: false,
     private_chat: true,
     admin: true,
+    owner: false,
 };
 
 #[derive(Debug)]
@@ -29,14 +30,14 @@ enum Alternatives {
 
 impl<'a> Choice<'a> for &'a Alternatives {
     type Action = Action;
-    fn button(&self) -> Button<'a, Self> {
+    fn button(&self, locale: Locale) -> Button<'a, Self> {
         match self {
             Alternatives::PrivateChat => Button::Text {
-                text: "Dieser Chat".into(),
+                text: tr!(locale, "target.button.this_chat").into(),
                 action: |_| Action::PrivateChat,
             },
             &&Alternatives::RequestChannel(request_id) => Button::RequestChat {
-                text: "Channel auswählen".into(),
+                text: tr!(locale, "target.button.channel").into(),
                 request_id,
                 request_chat: |request_id| {
                     let permissions = ChatAdministratorRights::builder()
@@ -89,21 +90,27 @@ impl ChannelSelection {
         cx: HandleMessage<'_>,
         _: Option<SelectedChannel>,
     ) -> HandlerResult {
-        match self.buttons.match_action(cx.message) {
+        match self.buttons.match_action(cx.input(), cx.locale) {
             Some(Action::ChannelShared(channel)) => self.handle_chat_shared(cx, channel).await,
             Some(Action::PrivateChat) => self.handle_reset(cx).await,
-            None if cx.message.text.is_some() => self.handle_unexpected_text(cx).await,
+            None if cx.text().is_some() => self.handle_unexpected_text(cx).await,
             _ => Err(Error::UnexpectedMessage),
         }
     }
 
     async fn handle_unexpected_text(&self, cx: HandleMessage<'_>) -> HandlerResult {
-        let text = format!(
-            "️Bitte verwende die Schaltflächen, um einen Chat auszuwählen, oder sende /{} zum Abbrechen.",
-            super::command_cancel::COMMAND.name
+        let text = tr!(
+            cx.locale,
+            "target.use_buttons",
+            cancel = super::command_cancel::COMMAND.name
         );
 
-        respond!(cx, text, reply_markup = self.buttons.keyboard_markup()).await
+        respond!(
+            cx,
+            text,
+            reply_markup = self.buttons.keyboard_markup(cx.locale)
+        )
+        .await
     }
 
     async fn handle_chat_shared(
@@ -111,21 +118,26 @@ impl ChannelSelection {
         cx: HandleMessage<'_>,
         channel: SelectedChannel,
     ) -> HandlerResult {
-        let (text, entities) = concat!(
-            "✅ Der Kanal ",
-            channel.hyperlink(),
-            " wurde ausgewählt!\n\nDu kannst nun die Einstellungen für diesen Channel ändern. Führe /",
-            COMMAND.name,
-            " erneut aus, um die Auswahl zu ändern oder zurückzusetzen."
+        let (text, entities) = tr_msg!(
+            cx.locale,
+            "target.selected",
+            channel = channel.hyperlink(cx.locale),
+            command = COMMAND.name
         )
         .to_message()?;
 
+        // offered by `/kanaele` from now on
+        cx.inner
+            .database
+            .add_channel(cx.chat_id(), channel.chat_id, &channel)
+            .await?;
+
         cx.reset_dialogue(Some(channel)).await?;
         respond!(cx, text, entities, reply_markup = remove_keyboard()).await
     }
 
     async fn handle_reset(&self, cx: HandleMessage<'_>) -> HandlerResult {
-        let text = "✅ Du kannst nun wieder Einstellungen für diesen privaten Chat vornehmen.";
+        let text = tr!(cx.locale, "target.reset");
         cx.reset_dialogue(None).await?;
         respond!(cx, text, reply_markup = remove_keyboard()).await
     }
@@ -133,11 +145,7 @@ impl ChannelSelection {
 
 pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerResult {
     if cx.message.chat.id < 0 {
-        respond!(
-            cx,
-            text = "Dieser Befehl wird nur in privaten Chats unterstützt!"
-        )
-        .await?;
+        respond!(cx, text = tr!(cx.locale, "target.private_only")).await?;
         return Ok(());
     }
 
@@ -146,17 +154,18 @@ pub async fn handle_command(cx: HandleMessage<'_>, _: Option<&str>) -> HandlerRe
     let dialogue = cx.get_dialogue().await?;
     let current_channel = &dialogue.channel;
     let state = ChannelSelection::new(request_id, current_channel.is_some());
-    let reply_markup = state.buttons.keyboard_markup();
+    let reply_markup = state.buttons.keyboard_markup(cx.locale);
 
     let sentence = if current_channel.is_some() {
-        "\n\nDu kannst zu diesem privaten Chat zurückwechseln oder einen anderen Kanal wählen:"
+        tr!(cx.locale, "target.question_switch")
     } else {
-        "\n\nDu kannst stattdessen auch einen Kanal auswählen:"
+        tr!(cx.locale, "target.question")
     };
 
+    let chat = SelectedChannel::chat_selection(current_channel, cx.locale);
     let (text, entities) = concat!(
-        "Aktuelle Auswahl: ",
-        SelectedChannel::chat_selection(current_channel),
+        tr_msg!(cx.locale, "selection.current", chat = chat),
+        "\n\n",
         sentence
     )
     .to_message()?;
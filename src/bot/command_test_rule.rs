@@ -0,0 +1,193 @@
+use frankenstein::types::MessageEntity;
+use serde::{Deserialize, Serialize};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold};
+
+use super::keyboard::{Choices, remove_keyboard};
+use super::{Command, HandleMessage, HandlerResult, SelectedChannel};
+use crate::digest::DigestItem;
+use crate::filter_cache::CompiledFilters;
+use crate::filter_syntax;
+use crate::types::{Audience, Filter, Scope, Tag};
+
+pub const COMMAND: Command = Command {
+    name: "regel_testen",
+
+    group_admin: true,
+    group_member: true,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+/// Number of recent notifications a rule is tested against
+const SAMPLE_SIZE: usize = 500;
+
+/// Maximum number of matching notifications that are listed
+const MAX_LISTED: usize = 10;
+
+/// Tests the rule against the recent notifications, and describes the result
+pub(super) async fn dry_run(
+    cx: HandleMessage<'_>,
+    filter: &Filter,
+) -> HandlerResult<(String, Vec<MessageEntity>)> {
+    let database = &cx.inner.database;
+    let messages = database.get_recent_messages(SAMPLE_SIZE).await?;
+
+    // follow-ups and digests aren't matched against rules
+    let mut messages: Vec<_> = messages
+        .into_iter()
+        .filter(|(_, msg)| matches!(msg.audience, Audience::Subscribers | Audience::Chat(_)))
+        .collect();
+
+    // the full texts are stored separately and only loaded if the rule needs them
+    let conditions = filter.expression.conditions();
+    if conditions.iter().any(|c| c.tag == Tag::Volltext) {
+        for (_, msg) in &mut messages {
+            if let Some(fulltext) = &msg.fulltext
+                && let Some(text) = database.get_fulltext(fulltext).await?
+            {
+                msg.restore_fulltext(text);
+            }
+        }
+    }
+    let compiled = CompiledFilters::new(vec![filter.clone()]);
+    let matching: Vec<_> = messages
+        .iter()
+        .filter(|(_, msg)| compiled.matches(msg))
+        .collect();
+
+    let mut msg = MessageBuilder::new();
+    msg.write("🧪 ")?;
+    msg.write(bold(tr!(cx.locale, "test.title")))?;
+    msg.write("\n\n")?;
+
+    let Some(oldest) = messages.last().and_then(|(id, _)| id.time()) else {
+        msg.write(tr!(cx.locale, "test.no_messages"))?;
+        return Ok(msg.build());
+    };
+
+    msg.write(tr!(
+        cx.locale,
+        "test.result",
+        total = messages.len(),
+        since = oldest.format(tr!(cx.locale, "date.format")),
+        matching = matching.len()
+    ))?;
+    msg.write(if matching.is_empty() { "." } else { ":\n" })?;
+
+    for (id, message) in matching.iter().take(MAX_LISTED) {
+        msg.write("\n")?;
+        DigestItem::new(*id, message).write_to(&mut msg)?;
+    }
+
+    if matching.len() > MAX_LISTED {
+        let more = matching.len() - MAX_LISTED;
+        msg.write("\n")?;
+        msg.write(tr!(cx.locale, "test.more", count = more))?;
+    }
+
+    Ok(msg.build())
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestFilterSelection {
+    filters: Vec<Filter>,
+}
+
+impl TestFilterSelection {
+    pub(super) async fn handle_message(
+        self,
+        cx: HandleMessage<'_>,
+        channel: Option<SelectedChannel>,
+    ) -> HandlerResult {
+        match self
+            .filters
+            .iter()
+            .enumerate()
+            .match_action(cx.input(), cx.locale)
+        {
+            Some((_, filter)) => {
+                let (text, entities) = dry_run(cx, filter).await?;
+                cx.reset_dialogue(channel).await?;
+                respond!(cx, text, entities, reply_markup = remove_keyboard()).await
+            }
+            None => {
+                let text = tr!(
+                    cx.locale,
+                    "dialogue.select_rule",
+                    cancel = super::command_cancel::COMMAND.name
+                );
+                let reply_markup = self.filters.iter().enumerate().keyboard_markup(cx.locale);
+                respond!(cx, text, reply_markup).await
+            }
+        }
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    let dialogue = cx.get_dialogue().await?;
+
+    // a draft can be given as an expression
+    if let Some(input) = param.filter(|param| !param.trim().is_empty()) {
+        let expression = match filter_syntax::parse(input) {
+            Ok(expression) => expression,
+            Err(e) => {
+                let text = tr!(
+                    cx.locale,
+                    "error.invalid_expression",
+                    error = e.describe(cx.locale)
+                );
+                return respond!(cx, text).await;
+            }
+        };
+
+        let filter = Filter {
+            expression,
+            scope: Scope::default(),
+            updates: false,
+            instances: vec![],
+            snippets: false,
+        };
+        let (text, entities) = dry_run(cx, &filter).await?;
+        return respond!(cx, text, entities).await;
+    }
+
+    let recipient = cx.selected_chat(&dialogue.channel).await?;
+    let filters = cx.inner.database.get_filters(recipient).await?;
+
+    if let [filter] = &filters[..] {
+        let (text, entities) = dry_run(cx, filter).await?;
+        return respond!(cx, text, entities).await;
+    }
+
+    if filters.is_empty() {
+        let target = SelectedChannel::chat_selection_accusative(&dialogue.channel, cx.locale);
+        let (text, entities) = tr_msg!(
+            cx.locale,
+            "test.no_rules",
+            target = target,
+            command = COMMAND.name
+        )
+        .to_message()?;
+        return respond!(cx, text, entities, reply_markup = remove_keyboard()).await;
+    }
+
+    let mut msg = MessageBuilder::new();
+
+    let chat = SelectedChannel::chat_selection(&dialogue.channel, cx.locale);
+    msg.writeln(tr_msg!(cx.locale, "selection.current", chat = chat))?;
+    msg.write(tr!(cx.locale, "test.question"))?;
+    msg.write("\n\n")?;
+
+    for (i, f) in filters.iter().enumerate() {
+        msg.writeln(bold(tr!(cx.locale, "rule.number", number = i + 1)))?;
+        msg.writeln(f.describe(cx.locale))?;
+    }
+
+    let (text, entities) = msg.build();
+    let reply_markup = filters.iter().enumerate().keyboard_markup(cx.locale);
+    let state = TestFilterSelection { filters };
+
+    cx.update_dialogue(state, dialogue.channel).await?;
+    respond!(cx, text, entities, reply_markup).await
+}
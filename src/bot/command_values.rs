@@ -0,0 +1,141 @@
+use telegram_message_builder::{MessageBuilder, bold, code};
+
+use super::keyboard::{Button, Choice};
+use super::{Command, HandleMessage, HandlerResult};
+use crate::locale::Locale;
+use crate::types::Tag;
+
+pub const COMMAND: Command = Command {
+    name: "werte",
+
+    group_admin: true,
+    group_member: true,
+    private_chat: true,
+    admin: true,
+    owner: false,
+};
+
+/// Maximum number of values listed by the command
+const MAX_LISTED: usize = 50;
+
+/// Number of values offered as buttons when entering a value
+pub(super) const MAX_SUGGESTIONS: usize = 8;
+
+/// A known value, offered as a button
+impl<'a> Choice<'a> for &'a str {
+    type Action = Self;
+
+    fn button(&self, _: Locale) -> Button<'a, Self::Action> {
+        Button::Text {
+            text: (*self).into(),
+            action: |x| x,
+        }
+    }
+}
+
+/// The known values of the tag that contain the query (ignoring the case), most frequent first
+pub(super) async fn known_values(
+    cx: HandleMessage<'_>,
+    tag: Tag,
+    query: &str,
+) -> HandlerResult<Vec<(String, u64)>> {
+    if !tag.has_known_values() {
+        return Ok(vec![]);
+    }
+
+    let query = query.trim().to_lowercase();
+    let mut values = cx.inner.database.get_tag_values(tag).await?;
+    values.retain(|(value, _)| value.to_lowercase().contains(&query));
+    Ok(values)
+}
+
+/// A few example values for the tag, preferably the most frequent known ones
+pub(super) async fn examples(cx: HandleMessage<'_>, tag: Tag) -> HandlerResult<Vec<String>> {
+    const EXAMPLES: usize = 4;
+
+    let mut values = known_values(cx, tag, "").await?;
+    values.truncate(EXAMPLES);
+
+    if values.is_empty() {
+        Ok(cx.inner.branding.examples(tag).to_vec())
+    } else {
+        Ok(values.into_iter().map(|(value, _)| value).collect())
+    }
+}
+
+pub async fn handle_command(cx: HandleMessage<'_>, param: Option<&str>) -> HandlerResult {
+    let mut msg = MessageBuilder::new();
+    let keys: Vec<_> = Tag::TAGS
+        .iter()
+        .filter(|tag| tag.has_known_values())
+        .map(|tag| tag.key())
+        .collect();
+
+    let (key, query) = match param.map(str::trim).filter(|param| !param.is_empty()) {
+        Some(param) => param.split_once(' ').unwrap_or((param, "")),
+        None => {
+            msg.write(tr_msg!(
+                cx.locale,
+                "values.usage",
+                example = code(format!("/{} gremium", COMMAND.name)),
+                example_query = code(format!("/{} gremium Bezirk", COMMAND.name)),
+                tags = keys.join(", ")
+            ))?;
+
+            let (text, entities) = msg.build();
+            return respond!(cx, text, entities).await;
+        }
+    };
+
+    let Some(tag) = Tag::from_key(key) else {
+        let text = tr!(
+            cx.locale,
+            "values.unknown_tag",
+            tag = key,
+            tags = keys.join(", ")
+        );
+        return respond!(cx, text).await;
+    };
+
+    if !tag.has_known_values() {
+        let text = tr!(
+            cx.locale,
+            "values.not_collected",
+            tag = tag.label(cx.locale),
+            tags = keys.join(", ")
+        );
+        return respond!(cx, text).await;
+    }
+
+    let values = known_values(cx, tag, query).await?;
+
+    msg.write("📚 ")?;
+    let title = tr!(cx.locale, "values.title", tag = tag.label(cx.locale));
+    msg.write(bold(title))?;
+    msg.write("\n\n")?;
+
+    if values.is_empty() {
+        msg.write(if query.is_empty() {
+            tr!(cx.locale, "values.none")
+        } else {
+            tr!(cx.locale, "values.none_matching")
+        })?;
+    } else {
+        for (value, count) in values.iter().take(MAX_LISTED) {
+            msg.write("• ")?;
+            msg.write(code(value))?;
+            writeln!(msg, " ({count}×)")?;
+        }
+
+        if values.len() > MAX_LISTED {
+            let more = values.len() - MAX_LISTED;
+            msg.writeln(tr!(cx.locale, "test.more", count = more))?;
+        }
+
+        msg.write("\n")?;
+        msg.write(tr!(cx.locale, "values.copy"))?;
+    }
+
+    let (text, entities) = msg.build();
+    respond!(cx, text, entities).await
+}
@@ -0,0 +1,76 @@
+//! Inline mode: typing `@Bot Radweg` in any chat offers the matching recent notifications,
+//! which are then sent by the user including their buttons
+
+use frankenstein::AsyncTelegramApi;
+use frankenstein::inline_mode::{
+    InlineQuery, InlineQueryResult, InlineQueryResultArticle, InputTextMessageContent,
+};
+use frankenstein::methods::AnswerInlineQueryParams;
+use frankenstein::types::ReplyMarkup;
+
+use super::command_search::search;
+use super::{HandlerResult, MessageHandler};
+use crate::database::StreamId;
+use crate::types::Message;
+
+/// Maximum number of results per answer, as allowed by Telegram
+const RESULTS_PER_ANSWER: usize = 50;
+
+/// How long Telegram may reuse an answer for the same query, in seconds
+const CACHE_TIME: u32 = 60;
+
+/// The notification's headline as title, and its other lines as description
+fn article(id: StreamId, message: &Message) -> InlineQueryResult {
+    let request = &message.request;
+    let mut lines = request.text.lines().filter(|line| !line.trim().is_empty());
+    let title = lines.next().unwrap_or_default();
+    let description = lines.collect::<Vec<_>>().join(" · ");
+
+    let content = InputTextMessageContent::builder()
+        .message_text(&request.text)
+        .maybe_entities(request.entities.clone())
+        .maybe_link_preview_options(request.link_preview_options.clone())
+        .build();
+
+    let reply_markup = match &request.reply_markup {
+        Some(ReplyMarkup::InlineKeyboardMarkup(keyboard)) => Some(keyboard.clone()),
+        _ => None,
+    };
+
+    let article = InlineQueryResultArticle::builder()
+        .id(id.to_string())
+        .title(title)
+        .input_message_content(content)
+        .maybe_reply_markup(reply_markup)
+        .description(description)
+        .build();
+
+    InlineQueryResult::Article(article)
+}
+
+/// Answers with the notifications matching the query. The offset is the number of results
+/// that have already been sent.
+pub(super) async fn answer(handler: &MessageHandler, query: &InlineQuery) -> HandlerResult {
+    let entries = handler.search_index.entries(&handler.database).await?;
+    let matching = search(&entries, &query.query);
+
+    let offset: usize = query.offset.parse().unwrap_or_default();
+    let results = matching
+        .iter()
+        .skip(offset)
+        .take(RESULTS_PER_ANSWER)
+        .map(|entry| article(entry.id, &entry.message))
+        .collect();
+
+    let next_offset = offset + RESULTS_PER_ANSWER;
+    let params = AnswerInlineQueryParams::builder()
+        .inline_query_id(&query.id)
+        .results(results)
+        .cache_time(CACHE_TIME)
+        .is_personal(false)
+        .maybe_next_offset((next_offset < matching.len()).then(|| next_offset.to_string()))
+        .build();
+
+    handler.bot.answer_inline_query(&params).await?;
+    Ok(())
+}
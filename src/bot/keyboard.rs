@@ -1,18 +1,70 @@
-//! Provides an abstraction over reply keyboards
+//! Provides an abstraction over keyboards: inline keyboards, whose buttons are identified by
+//! compact callback data, and reply keyboards where inline buttons aren't possible
 
 use std::borrow::Cow;
 
 use frankenstein::types::{
-    ForceReply, KeyboardButton, KeyboardButtonRequestChat, Message, ReplyKeyboardMarkup,
-    ReplyKeyboardRemove, ReplyMarkup,
+    ForceReply, InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton,
+    KeyboardButtonRequestChat, Message, ReplyKeyboardMarkup, ReplyKeyboardRemove, ReplyMarkup,
 };
 
 use super::SelectedChannel;
+use crate::locale::Locale;
+
+/// What the user sent in response to a keyboard
+#[derive(Clone, Copy, Debug)]
+pub enum Input<'a> {
+    Message(&'a Message),
+    /// the callback data of a tapped inline button, without the nonce
+    Callback(&'a str),
+    /// a tapped inline button of a previous dialogue step, which matches no button
+    Outdated,
+}
+
+/// Length of the nonce that precedes the callback data, see [`tag_callback_data`]
+const NONCE_LEN: usize = 8;
+
+/// Prepends the nonce of the dialogue step to the callback data of the inline buttons, so
+/// that a tap on a keyboard of another step or dialogue is recognized, even if a button
+/// has the same text
+pub fn tag_callback_data(markup: &mut ReplyMarkup, nonce: u32) {
+    if let ReplyMarkup::InlineKeyboardMarkup(markup) = markup {
+        let nonce = format!("{nonce:08x}");
+        for button in markup.inline_keyboard.iter_mut().flatten() {
+            if let Some(data) = &mut button.callback_data {
+                data.insert_str(0, &nonce);
+            }
+        }
+    }
+}
+
+/// The input for the callback data of a tapped button, given the nonce of the current
+/// dialogue step
+pub fn callback_input(data: &str, nonce: u32) -> Input<'_> {
+    match data.split_at_checked(NONCE_LEN) {
+        Some((tag, data)) if u32::from_str_radix(tag, 16) == Ok(nonce) => Input::Callback(data),
+        _ => Input::Outdated,
+    }
+}
+
+/// Identifies an inline button by a hash of its text in the default locale, so that it
+/// doesn't depend on the language of whoever taps it. Buttons of other dialogue steps are
+/// told apart by a nonce, see [`tag_callback_data`].
+fn callback_data<'a, C: Choice<'a>>(choice: &C) -> String {
+    let (Button::Text { text, .. } | Button::RequestChat { text, .. }) =
+        choice.button(Locale::default());
+
+    // FNV-1a, as the data has to stay the same across restarts and Rust releases
+    let hash = text.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    format!("{hash:08x}")
+}
 
 pub trait Choice<'a>: Sized {
     type Action: 'a;
 
-    fn button(&self) -> Button<'a, Self>;
+    fn button(&self, locale: Locale) -> Button<'a, Self>;
 }
 
 pub enum Button<'a, C: Choice<'a>> {
@@ -44,14 +96,34 @@ impl<'a, C: Choice<'a>> Button<'a, C> {
         }
     }
 
-    fn match_action(&self, option: C, msg: &Message) -> Option<C::Action> {
+    /// Request chat buttons are only possible in reply keyboards
+    fn inline_button(&self, option: &C) -> Option<InlineKeyboardButton> {
         match self {
-            Self::Text { text, action } => {
+            Self::Text { text, .. } => {
+                let button = InlineKeyboardButton::builder()
+                    .text(text.as_ref())
+                    .callback_data(callback_data(option))
+                    .build();
+                Some(button)
+            }
+            Self::RequestChat { .. } => None,
+        }
+    }
+
+    fn match_action(&self, option: C, input: Input) -> Option<C::Action> {
+        match (self, input) {
+            (Self::Text { text, action }, Input::Message(msg)) => {
                 (msg.text.as_deref() == Some(text.as_ref())).then(|| action(option))
             }
-            Self::RequestChat {
-                request_id, action, ..
-            } => {
+            (Self::Text { action, .. }, Input::Callback(data)) => {
+                (data == callback_data(&option)).then(|| action(option))
+            }
+            (
+                Self::RequestChat {
+                    request_id, action, ..
+                },
+                Input::Message(msg),
+            ) => {
                 if let Some(chat_shared) = &msg.chat_shared {
                     (chat_shared.request_id == *request_id).then(|| {
                         let channel = SelectedChannel {
@@ -65,33 +137,53 @@ impl<'a, C: Choice<'a>> Button<'a, C> {
                     None
                 }
             }
+            (Self::RequestChat { .. }, Input::Callback(_)) | (_, Input::Outdated) => None,
         }
     }
 }
 
+/// The buttons are labeled in the given locale, so the same locale is needed to match them
 pub trait Choices<A> {
-    fn match_action(self, message: &Message) -> Option<A>;
+    fn match_action(self, input: Input, locale: Locale) -> Option<A>;
 
-    fn keyboard_markup(self) -> ReplyMarkup;
+    /// An inline keyboard, unless some of the buttons require a reply keyboard
+    fn keyboard_markup(self, locale: Locale) -> ReplyMarkup;
+}
+
+const BUTTONS_PER_ROW: usize = 2;
+
+fn rows<T>(buttons: impl IntoIterator<Item = T>) -> Vec<Vec<T>> {
+    let mut keyboard: Vec<Vec<T>> = vec![];
+    for b in buttons {
+        match keyboard.last_mut() {
+            Some(x) if x.len() < BUTTONS_PER_ROW => x.push(b),
+            _ => keyboard.push(vec![b]),
+        }
+    }
+    keyboard
 }
 
 impl<'a, B: Choice<'a>, T: IntoIterator<Item = B>> Choices<B::Action> for T {
-    fn match_action(self, message: &Message) -> Option<B::Action> {
+    fn match_action(self, input: Input, locale: Locale) -> Option<B::Action> {
         self.into_iter()
-            .find_map(|x| x.button().match_action(x, message))
+            .find_map(|x| x.button(locale).match_action(x, input))
     }
 
-    fn keyboard_markup(self) -> ReplyMarkup {
-        const BUTTONS_PER_ROW: usize = 2;
-        let mut keyboard: Vec<Vec<KeyboardButton>> = vec![];
-        for button in self {
-            let b = button.button().keyboard_button();
-            match keyboard.last_mut() {
-                Some(x) if x.len() < BUTTONS_PER_ROW => x.push(b),
-                _ => keyboard.push(vec![b]),
-            }
+    fn keyboard_markup(self, locale: Locale) -> ReplyMarkup {
+        let buttons: Vec<_> = self.into_iter().map(|x| (x.button(locale), x)).collect();
+
+        let inline_buttons: Option<Vec<_>> = buttons
+            .iter()
+            .map(|(button, option)| button.inline_button(option))
+            .collect();
+        if let Some(inline_buttons) = inline_buttons {
+            let keyboard = InlineKeyboardMarkup::builder()
+                .inline_keyboard(rows(inline_buttons))
+                .build();
+            return ReplyMarkup::InlineKeyboardMarkup(keyboard);
         }
 
+        let keyboard = rows(buttons.iter().map(|(button, _)| button.keyboard_button()));
         let keyboard = ReplyKeyboardMarkup::builder()
             .keyboard(keyboard)
             .one_time_keyboard(true)
@@ -115,3 +207,94 @@ pub fn force_reply(placeholder: &str) -> ReplyMarkup {
             .build(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use frankenstein::types::ReplyMarkup;
+
+    use super::{Button, Choice, Choices, Input, callback_input, tag_callback_data};
+    use crate::locale::Locale;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Next;
+
+    impl<'a> Choice<'a> for Next {
+        type Action = Self;
+
+        fn button(&self, locale: Locale) -> Button<'a, Self> {
+            Button::Text {
+                text: tr!(locale, "search.next").into(),
+                action: |x| x,
+            }
+        }
+    }
+
+    #[test]
+    fn test_inline_keyboard() {
+        let choices = ["🔁 Gilt für: Vorlagen", "✅ Speichern"];
+        let ReplyMarkup::InlineKeyboardMarkup(markup) = choices.keyboard_markup(Locale::De) else {
+            panic!("expected an inline keyboard");
+        };
+        let data = |i: usize| markup.inline_keyboard[0][i].callback_data.clone().unwrap();
+
+        assert!(data(0).len() <= 64);
+        assert_eq!(
+            choices.match_action(Input::Callback(&data(1)), Locale::De),
+            Some("✅ Speichern")
+        );
+
+        // a button of an outdated keyboard doesn't match the current state
+        let current = ["🔁 Gilt für: Sitzungen", "✅ Speichern"];
+        assert_eq!(
+            current.match_action(Input::Callback(&data(0)), Locale::De),
+            None
+        );
+        assert_eq!(
+            current.match_action(Input::Callback(&data(1)), Locale::De),
+            Some("✅ Speichern")
+        );
+    }
+
+    #[test]
+    fn test_callback_data_is_language_independent() {
+        // in a group without a chat language, members see the keyboard in their own language
+        let ReplyMarkup::InlineKeyboardMarkup(markup) = [Next].keyboard_markup(Locale::En) else {
+            panic!("expected an inline keyboard");
+        };
+        let data = markup.inline_keyboard[0][0].callback_data.clone().unwrap();
+
+        assert_eq!(
+            [Next].match_action(Input::Callback(&data), Locale::De),
+            Some(Next)
+        );
+        // the data is the same across restarts and Rust releases
+        assert_eq!(data, "dfec579b");
+    }
+
+    #[test]
+    fn test_nonce() {
+        let choices = ["✅ Speichern"];
+        let mut markup = choices.keyboard_markup(Locale::De);
+        tag_callback_data(&mut markup, 0xabc);
+        let ReplyMarkup::InlineKeyboardMarkup(keyboard) = &markup else {
+            panic!("expected an inline keyboard");
+        };
+        let data = keyboard.inline_keyboard[0][0]
+            .callback_data
+            .as_deref()
+            .unwrap();
+        assert!(data.starts_with("00000abc"));
+
+        let input = callback_input(data, 0xabc);
+        assert_eq!(
+            choices.match_action(input, Locale::De),
+            Some("✅ Speichern")
+        );
+
+        // the same button in another dialogue step doesn't match
+        let input = callback_input(data, 0xabd);
+        assert!(matches!(input, Input::Outdated));
+        assert_eq!(choices.match_action(input, Locale::De), None);
+        assert!(matches!(callback_input("abc", 0xabc), Input::Outdated));
+    }
+}
@@ -8,11 +8,7 @@ macro_rules! respond {
         } else {
             None
         };
-        let thread_id = $this.message
-            .is_topic_message
-            .unwrap_or(false)
-            .then_some($this.message.message_thread_id)
-            .flatten();
+        let thread_id = $this.recipient().thread_id;
         let params = ::frankenstein::methods::SendMessageParams::builder()
             .chat_id($this.chat_id())
             .maybe_message_thread_id(thread_id)
@@ -21,9 +17,6 @@ macro_rules! respond {
             $(.$p(respond!(@param $p $($v)?)))*
             .build();
 
-        async move {
-            ::frankenstein::AsyncTelegramApi::send_message(&$this.inner.bot, &params).await?;
-            crate::bot::HandlerResult::Ok(())
-        }
+        $this.send(params)
     }};
 }
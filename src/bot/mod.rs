@@ -1,49 +1,100 @@
 #[macro_use]
 mod macros;
 
+mod command_announcement;
+mod command_backfill;
 mod command_cancel;
+mod command_channels;
+mod command_chats;
+mod command_delivery;
+mod command_edit_rule;
 mod command_help;
+mod command_language;
 mod command_new_rule;
+mod command_permissions;
 mod command_privacy;
 mod command_remove_all_rules;
 mod command_remove_rule;
 mod command_rules;
+mod command_search;
 mod command_start;
+mod command_statistics;
 mod command_target;
+mod command_test_rule;
+mod command_values;
+mod inline_query;
 mod keyboard;
 
-use std::fmt::Display;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 
+use bot_utils::Recipient;
 use bot_utils::command::{CommandParser, ParsedCommand};
 use bot_utils::updates::UpdateHandler;
 use frankenstein::AsyncTelegramApi;
+use frankenstein::inline_mode::InlineQuery;
 use frankenstein::methods::{
-    GetChatAdministratorsParams, SetMyCommandsParams, SetMyDescriptionParams,
+    AnswerCallbackQueryParams, EditMessageReplyMarkupParams, EditMessageTextParams,
+    GetChatAdministratorsParams, SendMessageParams, SetMyCommandsParams, SetMyDescriptionParams,
     SetMyShortDescriptionParams,
 };
-use frankenstein::types::{AllowedUpdate, BotCommand, BotCommandScope, ChatMemberUpdated, Message};
+use frankenstein::types::{
+    AllowedUpdate, BotCommand, BotCommandScope, BotCommandScopeChat, CallbackQuery, ChatMember,
+    ChatMemberUpdated, ChatType, MaybeInaccessibleMessage, Message, ReplyMarkup, User,
+};
 use serde::{Deserialize, Serialize};
-use telegram_message_builder::{Error as MessageBuilderError, WriteToMessage, concat, text_link};
+use telegram_message_builder::{
+    Error as MessageBuilderError, MessageBuilder, WriteToMessage, concat, text_link,
+};
 use tokio::sync::oneshot;
 
+use self::command_announcement::ConfirmAnnouncement;
+use self::command_channels::ChannelListSelection;
+use self::command_delivery::DeliverySelection;
+use self::command_edit_rule::{EditFilterSelection, RuleEditor};
+use self::command_language::LanguageSelection;
 use self::command_new_rule::{PatternInput, TagSelection};
+use self::command_permissions::PermissionSelection;
 use self::command_remove_all_rules::ConfirmRemoveAllFilters;
 use self::command_remove_rule::RemoveFilterSelection;
+use self::command_search::{SearchIndex, SearchResults};
 use self::command_target::ChannelSelection;
-use self::keyboard::remove_keyboard;
+use self::command_test_rule::TestFilterSelection;
+use self::keyboard::{Input, callback_input, remove_keyboard, tag_callback_data};
+use crate::allris::Instance;
+use crate::branding::Branding;
 use crate::database::{self, SharedDatabaseConnection};
+use crate::locale::Locale;
+
+/// Joins names to an enumeration like "A, B und C"
+fn enumerate_names<'a>(names: impl IntoIterator<Item = &'a str>, locale: Locale) -> String {
+    let names: Vec<_> = names.into_iter().collect();
+    match names.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{}{}{last}", rest.join(", "), tr!(locale, "list.and")),
+        None => String::new(),
+    }
+}
 
-const SHORT_DESCRIPTION: &str = "Dieser Bot benachrichtigt dich, wenn im Ratsinformationssystem der Stadt Bonn neue Vorlagen veröffentlicht werden.";
+fn description(instances: &[Instance], locale: Locale) -> String {
+    let names = enumerate_names(instances.iter().map(|i| i.name.as_str()), locale);
+    let key = if instances.len() == 1 {
+        "description.one"
+    } else {
+        "description.many"
+    };
+
+    tr!(locale, key, names = names)
+}
 
 #[derive(Debug, thiserror::Error)]
 enum Error {
     #[error("User {0} is not admin of channel {1}")]
     NotChannelAdmin(i64, i64),
+    #[error("Sender is not admin of group {0}")]
+    NotGroupAdmin(i64),
     #[error("Unexpected message")]
     UnexpectedMessage,
-    #[error("Topics not yet supported")]
-    TopicsNotSupported,
     #[error("Unknown command {0}")]
     UnknownCommand(String),
     #[error("Telegram error: {0}")]
@@ -61,7 +112,10 @@ macro_rules! commands {
         async fn handle_command(cx: HandleMessage<'_>, cmd: &str, param: Option<&str>) -> HandlerResult {
             let cmd = cmd.to_ascii_lowercase();
             match cmd.as_str() {
-                $(cmd if cmd == $cmd::COMMAND.name => $cmd::handle_command(cx, param).await,)+
+                $(cmd if cmd == $cmd::COMMAND.name && (!$cmd::COMMAND.owner || cx.is_owner()) => {
+                    cx.check_permission(&$cmd::COMMAND).await?;
+                    $cmd::handle_command(cx, param).await
+                })+
                 _ => Err(Error::UnknownCommand(cmd))
             }
         }
@@ -105,33 +159,52 @@ macro_rules! states {
 
 struct Command {
     name: &'static str,
-    description: &'static str,
     group_admin: bool,
     group_member: bool,
     private_chat: bool,
 
-    #[allow(unused)]
+    /// listed in the owner's private chat, if the owner is given by the user id
     admin: bool,
+    /// only available to the bot's owner
+    owner: bool,
 }
 
-impl Display for Command {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "/{} – {}", self.name, self.description)
+impl Command {
+    fn description(&self, locale: Locale) -> &'static str {
+        locale.text(&format!("command.{}", self.name))
+    }
+
+    /// The command with its description, as listed in the help
+    fn line(&self, locale: Locale) -> String {
+        format!("/{} – {}\n", self.name, self.description(locale))
     }
 }
 
 commands! {
     command_new_rule,
     command_rules,
+    command_edit_rule,
     command_remove_rule,
     command_remove_all_rules,
+    command_test_rule,
+    command_values,
+    command_search,
 
     command_target,
+    command_channels,
+    command_delivery,
 
     command_cancel,
     command_help,
     command_start,
+    command_language,
+    command_permissions,
     command_privacy,
+
+    command_backfill,
+    command_statistics,
+    command_chats,
+    command_announcement,
 }
 
 states! {
@@ -140,7 +213,53 @@ states! {
     PatternInput,
     TagSelection,
     ChannelSelection,
-    RemoveFilterSelection
+    ChannelListSelection,
+    RemoveFilterSelection,
+    TestFilterSelection,
+    EditFilterSelection,
+    RuleEditor,
+    DeliverySelection,
+    LanguageSelection,
+    PermissionSelection,
+    ConfirmAnnouncement,
+    SearchResults
+}
+
+impl DialogueState {
+    /// The command that started the dialogue, whose permissions apply to the whole dialogue
+    fn command(&self) -> Option<&'static Command> {
+        let command = match self {
+            Self::Initial => return None,
+            Self::ConfirmRemoveAllFilters(_) => &command_remove_all_rules::COMMAND,
+            Self::PatternInput(_) | Self::TagSelection(_) => &command_new_rule::COMMAND,
+            Self::ChannelSelection(_) => &command_target::COMMAND,
+            Self::ChannelListSelection(_) => &command_channels::COMMAND,
+            Self::RemoveFilterSelection(_) => &command_remove_rule::COMMAND,
+            Self::TestFilterSelection(_) => &command_test_rule::COMMAND,
+            Self::EditFilterSelection(_) | Self::RuleEditor(_) => &command_edit_rule::COMMAND,
+            Self::DeliverySelection(_) => &command_delivery::COMMAND,
+            Self::LanguageSelection(_) => &command_language::COMMAND,
+            Self::PermissionSelection(_) => &command_permissions::COMMAND,
+            Self::ConfirmAnnouncement(_) => &command_announcement::COMMAND,
+            Self::SearchResults(_) => &command_search::COMMAND,
+        };
+
+        Some(command)
+    }
+}
+
+/// The bot's owner, given by the Telegram username or the numeric user id. Only the user id grants
+/// the owner commands, since a username may later be claimed by someone else.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Owner {
+    Username(String),
+    UserId(u64),
+}
+
+impl Owner {
+    fn is(&self, user: &User) -> bool {
+        matches!(self, Owner::UserId(id) if user.id == *id)
+    }
 }
 
 #[derive(Debug)]
@@ -148,7 +267,10 @@ struct MessageHandler {
     bot: crate::Bot,
     database: SharedDatabaseConnection,
     command_parser: CommandParser,
-    owner: Option<String>,
+    owner: Option<Owner>,
+    branding: Branding,
+    instances: Vec<Instance>,
+    search_index: SearchIndex,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -163,44 +285,70 @@ impl SelectedChannel {
         (self.chat_id + 1_000_000_000_000).unsigned_abs()
     }
 
-    fn hyperlink(&self) -> impl WriteToMessage + '_ {
+    fn hyperlink(&self, locale: Locale) -> impl WriteToMessage + '_ {
         let link = if let Some(username) = &self.username {
             format!("https://t.me/{username}")
         } else {
             format!("https://t.me/c/{}", self.channel_id())
         };
 
-        let title = telegram_message_builder::from_fn(|msg| {
-            if let Some(title) = &self.title {
-                msg.write(title)
-            } else if let Some(username) = &self.username {
-                msg.write(concat!("@", username))
-            } else {
-                msg.write("<unbekannt>")
-            }
+        let title = telegram_message_builder::from_fn(move |msg| {
+            let title = telegram_message_builder::from_fn(|msg| {
+                if let Some(title) = &self.title {
+                    msg.write(title)
+                } else if let Some(username) = &self.username {
+                    msg.write(concat!("@", username))
+                } else {
+                    msg.write(tr!(locale, "channel.unknown"))
+                }
+            });
+            msg.write(tr_msg!(locale, "quoted", text = title))
         });
 
-        text_link(link, concat!("„", title, "“"))
+        text_link(link, title)
     }
 
-    fn chat_selection(channel: &Option<Self>) -> impl WriteToMessage {
-        telegram_message_builder::from_fn(move |msg| match channel {
-            Some(channel) => {
-                msg.write("📢 ")?;
-                msg.write(channel.hyperlink())
-            }
-            None => msg.write("💬 Dieser Chat"),
-        })
+    fn chat_selection(channel: &Option<Self>, locale: Locale) -> ChatSelection<'_> {
+        ChatSelection {
+            channel,
+            locale,
+            accusative: false,
+        }
+    }
+
+    fn chat_selection_accusative(channel: &Option<Self>, locale: Locale) -> ChatSelection<'_> {
+        ChatSelection {
+            channel,
+            locale,
+            accusative: true,
+        }
     }
+}
+
+/// The selected chat as part of a sentence
+struct ChatSelection<'a> {
+    channel: &'a Option<SelectedChannel>,
+    locale: Locale,
+    accusative: bool,
+}
 
-    fn chat_selection_accusative(channel: &Option<Self>) -> impl WriteToMessage {
-        telegram_message_builder::from_fn(move |msg| match channel {
-            Some(channel) => {
-                msg.write("den Kanal ")?;
-                msg.write(channel.hyperlink())
+impl WriteToMessage for ChatSelection<'_> {
+    fn write_to(&self, msg: &mut MessageBuilder) -> Result<(), MessageBuilderError> {
+        let locale = self.locale;
+
+        match (self.channel, self.accusative) {
+            (Some(channel), false) => {
+                msg.write("📢 ")?;
+                msg.write(channel.hyperlink(locale))
             }
-            None => msg.write("diesen Chat"),
-        })
+            (Some(channel), true) => msg.write(tr_msg!(
+                locale,
+                "channel.accusative",
+                channel = channel.hyperlink(locale)
+            )),
+            (None, false) => msg.write(tr!(locale, "chat.this")),
+            (None, true) => msg.write(tr!(locale, "chat.this_accusative")),
+        }
     }
 }
 
@@ -208,12 +356,16 @@ impl SelectedChannel {
 struct Dialogue {
     channel: Option<SelectedChannel>,
     state: DialogueState,
+    /// changes with every step, so that the inline keyboards of other steps don't match
+    #[serde(default)]
+    nonce: u32,
 }
 
 impl MessageHandler {
     async fn set_my_commands(
         &self,
         scope: BotCommandScope,
+        locale: Locale,
         filter: impl Fn(&Command) -> bool,
     ) -> HandlerResult {
         let commands = commands()
@@ -223,7 +375,7 @@ impl MessageHandler {
             .map(|cmd| {
                 BotCommand::builder()
                     .command(cmd.name)
-                    .description(cmd.description)
+                    .description(cmd.description(locale))
                     .build()
             })
             .collect();
@@ -231,6 +383,7 @@ impl MessageHandler {
         let params = SetMyCommandsParams::builder()
             .scope(scope)
             .commands(commands)
+            .maybe_language_code(language_code(locale))
             .build();
 
         self.bot.set_my_commands(&params).await?;
@@ -239,32 +392,66 @@ impl MessageHandler {
     }
 
     pub async fn prepare_bot(&self) -> HandlerResult {
-        self.set_my_commands(BotCommandScope::AllPrivateChats, |cmd| cmd.private_chat)
+        for &locale in Locale::LOCALES {
+            self.set_my_commands(BotCommandScope::AllPrivateChats, locale, |cmd| {
+                cmd.private_chat
+            })
             .await?;
-        self.set_my_commands(BotCommandScope::AllGroupChats, |cmd| cmd.group_member)
+            self.set_my_commands(BotCommandScope::AllGroupChats, locale, |cmd| {
+                cmd.group_member
+            })
+            .await?;
+            self.set_my_commands(BotCommandScope::AllChatAdministrators, locale, |cmd| {
+                cmd.group_admin
+            })
             .await?;
-        self.set_my_commands(BotCommandScope::AllChatAdministrators, |cmd| {
-            cmd.group_admin
-        })
-        .await?;
 
-        let params = SetMyDescriptionParams::builder()
-            .description(SHORT_DESCRIPTION)
-            .build();
-        self.bot.set_my_description(&params).await?;
+            // the chat id of a private chat is the user id
+            if let Some(Owner::UserId(id)) = self.owner {
+                let scope = BotCommandScopeChat::builder().chat_id(id as i64).build();
+                self.set_my_commands(BotCommandScope::Chat(scope), locale, |cmd| cmd.admin)
+                    .await?;
+            }
 
-        let params = SetMyShortDescriptionParams::builder()
-            .short_description(SHORT_DESCRIPTION)
-            .build();
-        self.bot.set_my_short_description(&params).await?;
+            let description = description(&self.instances, locale);
+            let params = SetMyDescriptionParams::builder()
+                .description(description)
+                .maybe_language_code(language_code(locale))
+                .build();
+            self.bot.set_my_description(&params).await?;
+
+            let short_description = tr!(locale, "description.short", city = self.branding.city);
+            let params = SetMyShortDescriptionParams::builder()
+                .short_description(short_description)
+                .maybe_language_code(language_code(locale))
+                .build();
+            self.bot.set_my_short_description(&params).await?;
+        }
 
         Ok(())
     }
 
+    /// The locale chosen for the chat, or else the one of the user's app
+    async fn locale(&self, chat_id: i64, user: Option<&User>) -> Locale {
+        let chosen = self.database.get_locale(chat_id).await.unwrap_or_else(|e| {
+            log::warn!("Unable to get locale: {e}");
+            None
+        });
+
+        chosen
+            .or_else(|| {
+                user.and_then(|user| user.language_code.as_deref())
+                    .and_then(Locale::from_code)
+            })
+            .unwrap_or_default()
+    }
+
     async fn new(
         bot: crate::Bot,
         database: SharedDatabaseConnection,
-        owner: Option<String>,
+        owner: Option<Owner>,
+        branding: Branding,
+        instances: Vec<Instance>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let command_parser = CommandParser::new(bot.get_me().await?.result.username.as_deref());
 
@@ -273,6 +460,9 @@ impl MessageHandler {
             database,
             command_parser,
             owner,
+            branding,
+            instances,
+            search_index: SearchIndex::default(),
         };
 
         handler.prepare_bot().await?;
@@ -281,39 +471,175 @@ impl MessageHandler {
     }
 }
 
+/// The default locale is used for all languages without their own translation
+fn language_code(locale: Locale) -> Option<String> {
+    (locale != Locale::default()).then(|| locale.code().to_string())
+}
+
+/// Is not a Telegram error in the narrower sense: an edit didn't change the message
+fn is_not_modified(e: &frankenstein::Error) -> bool {
+    matches!(e, frankenstein::Error::Api(e) if e.description.contains("message is not modified"))
+}
+
 #[derive(Clone, Copy, Debug)]
 struct HandleMessage<'a> {
+    /// for a tapped inline button, the message with the keyboard
     message: &'a Message,
     inner: &'a MessageHandler,
+    callback: Option<&'a CallbackQuery>,
+    locale: Locale,
+    /// the nonce of the dialogue step, see [`Dialogue::nonce`]
+    nonce: &'a AtomicU32,
 }
 
-impl HandleMessage<'_> {
+impl<'a> HandleMessage<'a> {
+    async fn new(
+        message: &'a Message,
+        inner: &'a MessageHandler,
+        callback: Option<&'a CallbackQuery>,
+        nonce: &'a AtomicU32,
+    ) -> Self {
+        let user = match callback {
+            Some(query) => Some(&query.from),
+            None => message.from.as_deref(),
+        };
+        let locale = inner.locale(message.chat.id, user).await;
+
+        Self {
+            message,
+            inner,
+            callback,
+            locale,
+            nonce,
+        }
+    }
+
     async fn handle(self) {
-        let result = async {
+        if let Err(e) = self.process().await {
+            let _ = self.handle_error(e).await;
+        }
+    }
+
+    async fn process(self) -> HandlerResult {
+        if self.callback.is_none() {
             if let Some(new_chat_id) = self.message.migrate_to_chat_id {
                 return self.handle_migrate_to_chat_id(new_chat_id).await;
             }
 
-            if let Some(text) = &self.message.text {
-                if self.message.is_topic_message == Some(true) {
-                    return Err(Error::TopicsNotSupported);
-                }
-
-                if let Some(ParsedCommand { command, param, .. }) =
+            if let Some(text) = &self.message.text
+                && let Some(ParsedCommand { command, param, .. }) =
                     self.inner.command_parser.parse(text)
-                {
-                    return handle_command(self, command, param).await;
+            {
+                return handle_command(self, command, param).await;
+            }
+        }
+
+        let dialogue = self.get_dialogue().await?;
+
+        if let Some(command) = dialogue.state.command() {
+            self.check_permission(command).await?;
+        }
+
+        dialogue.state.handle_message(self, dialogue.channel).await
+    }
+
+    /// Handles a tapped inline button, returns whether the button is outdated
+    async fn handle_callback(self) -> bool {
+        match self.process().await {
+            Ok(()) => false,
+            Err(Error::UnexpectedMessage) => {
+                if let Err(e) = self.remove_inline_keyboard().await {
+                    log::warn!("Unable to remove outdated keyboard: {e}");
                 }
+                true
+            }
+            Err(e) => {
+                self.handle_error(e).await;
+                false
+            }
+        }
+    }
+
+    fn input(self) -> Input<'a> {
+        match self.callback {
+            Some(query) => {
+                let data = query.data.as_deref().unwrap_or_default();
+                callback_input(data, self.nonce.load(Ordering::Relaxed))
             }
+            None => Input::Message(self.message),
+        }
+    }
 
-            let dialogue = self.get_dialogue().await?;
+    /// The text sent by the user, not available for tapped buttons
+    fn text(self) -> Option<&'a str> {
+        match self.callback {
+            Some(_) => None,
+            None => self.message.text.as_deref(),
+        }
+    }
 
-            dialogue.state.handle_message(self, dialogue.channel).await
+    fn user(self) -> Option<&'a User> {
+        match self.callback {
+            Some(query) => Some(&query.from),
+            None => self.message.from.as_deref(),
         }
-        .await;
+    }
 
-        if let Err(e) = result {
-            let _ = self.handle_error(e).await;
+    /// Sends a response. For a tapped inline button, the message with the keyboard is edited
+    /// in place instead, unless the response asks for text input.
+    async fn send(self, mut params: SendMessageParams) -> HandlerResult {
+        if let Some(reply_markup) = &mut params.reply_markup {
+            tag_callback_data(reply_markup, self.nonce.load(Ordering::Relaxed));
+        }
+
+        if self.callback.is_some() {
+            let reply_markup = match &params.reply_markup {
+                None | Some(ReplyMarkup::ReplyKeyboardRemove(_)) => Some(None),
+                Some(ReplyMarkup::InlineKeyboardMarkup(markup)) => Some(Some(markup.clone())),
+                Some(_) => None,
+            };
+
+            if let Some(reply_markup) = reply_markup {
+                let params = EditMessageTextParams::builder()
+                    .chat_id(self.chat_id())
+                    .message_id(self.message.message_id)
+                    .text(params.text)
+                    .maybe_entities(params.entities)
+                    .maybe_link_preview_options(params.link_preview_options)
+                    .maybe_reply_markup(reply_markup)
+                    .build();
+
+                return match self.inner.bot.edit_message_text(&params).await {
+                    Err(e) if !is_not_modified(&e) => Err(e.into()),
+                    _ => Ok(()),
+                };
+            }
+
+            // the input is requested in a new message, replying to it
+            self.remove_inline_keyboard().await?;
+            params.reply_parameters = None;
+            if let Some(ReplyMarkup::ForceReply(force_reply)) = &mut params.reply_markup {
+                force_reply.selective = None;
+            }
+        }
+
+        self.inner.bot.send_message(&params).await?;
+        Ok(())
+    }
+
+    async fn remove_inline_keyboard(self) -> HandlerResult {
+        if self.message.reply_markup.is_none() {
+            return Ok(());
+        }
+
+        let params = EditMessageReplyMarkupParams::builder()
+            .chat_id(self.chat_id())
+            .message_id(self.message.message_id)
+            .build();
+
+        match self.inner.bot.edit_message_reply_markup(&params).await {
+            Err(e) if !is_not_modified(&e) => Err(e.into()),
+            _ => Ok(()),
         }
     }
 
@@ -323,19 +649,19 @@ impl HandleMessage<'_> {
                 _ = self.remove_dialogue().await;
                 _ = respond!(
                     self,
-                    text = "Du hast für diesen Channel nicht die notwendigen Rechte!",
+                    text = tr!(self.locale, "error.not_channel_admin"),
                     reply_markup = remove_keyboard()
                 )
                 .await;
                 true
             }
-            Error::TopicsNotSupported => {
-                _ = respond!(self, text = "Topics werden noch nicht unterstützt").await;
+            Error::NotGroupAdmin(_) => {
+                _ = respond!(self, text = tr!(self.locale, "error.not_group_admin")).await;
                 false
             }
             Error::UnexpectedMessage => false,
             Error::UnknownCommand(_) => {
-                _ = respond!(self, text = "Unbekannter Befehl!").await;
+                _ = respond!(self, text = tr!(self.locale, "error.unknown_command")).await;
                 false
             }
             Error::Telegram(_) => {
@@ -343,7 +669,7 @@ impl HandleMessage<'_> {
                 true
             }
             _ => {
-                _ = respond!(self, text = "Ein interner Fehler ist aufgetreten 😢").await;
+                _ = respond!(self, text = tr!(self.locale, "error.internal")).await;
                 true
             }
         };
@@ -363,23 +689,26 @@ impl HandleMessage<'_> {
         let dialogue = Dialogue {
             state: state.into(),
             channel,
+            nonce: rand::random(),
         };
 
         self.inner
             .database
-            .update_dialogue(self.chat_id(), &dialogue)
+            .update_dialogue(self.recipient(), &dialogue)
             .await?;
+        self.nonce.store(dialogue.nonce, Ordering::Relaxed);
 
         Ok(())
     }
 
     async fn get_dialogue(self) -> HandlerResult<Dialogue> {
-        let dialogue = self
+        let dialogue: Dialogue = self
             .inner
             .database
-            .get_dialogue(self.chat_id())
+            .get_dialogue(self.recipient())
             .await?
             .unwrap_or_default();
+        self.nonce.store(dialogue.nonce, Ordering::Relaxed);
 
         Ok(dialogue)
     }
@@ -390,7 +719,10 @@ impl HandleMessage<'_> {
     }
 
     async fn remove_dialogue(self) -> HandlerResult<()> {
-        self.inner.database.remove_dialogue(self.chat_id()).await?;
+        self.inner
+            .database
+            .remove_dialogue(self.recipient())
+            .await?;
         Ok(())
     }
 
@@ -407,11 +739,60 @@ impl HandleMessage<'_> {
         self.message.chat.id
     }
 
-    async fn selected_chat(self, channel: &Option<SelectedChannel>) -> HandlerResult<i64> {
+    /// The chat, or the topic of a forum the message was sent in
+    fn recipient(self) -> Recipient {
+        match self.message.message_thread_id {
+            Some(thread_id) if self.message.is_topic_message == Some(true) => {
+                Recipient::topic(self.chat_id(), thread_id)
+            }
+            _ => Recipient::from(self.chat_id()),
+        }
+    }
+
+    fn is_group(self) -> bool {
+        matches!(
+            self.message.chat.type_field,
+            ChatType::Group | ChatType::Supergroup
+        )
+    }
+
+    /// In groups, commands that aren't available to all members may only be used by the
+    /// administrators, unless the group allows all members to manage the rules
+    async fn check_permission(self, command: &Command) -> HandlerResult {
+        if command.group_member || !self.is_group() {
+            return Ok(());
+        }
+
+        // messages of anonymous administrators are sent on behalf of the group
+        if self.callback.is_none()
+            && let Some(sender_chat) = &self.message.sender_chat
+            && sender_chat.id == self.chat_id()
+        {
+            return Ok(());
+        }
+
+        if command.name != command_permissions::COMMAND.name
+            && self
+                .inner
+                .database
+                .get_member_managed(self.chat_id())
+                .await?
+        {
+            return Ok(());
+        }
+
+        match self.user() {
+            Some(user) if self.is_chat_admin(self.chat_id(), user.id).await? => Ok(()),
+            _ => Err(Error::NotGroupAdmin(self.chat_id())),
+        }
+    }
+
+    /// Whether the user is an administrator or the creator of the chat
+    async fn is_chat_admin(self, chat_id: i64, user_id: u64) -> HandlerResult<bool> {
         macro_rules! user {
             ($member:expr, $($variant:ident),+) => {
                 match $member {
-                    $(frankenstein::types::ChatMember::$variant(x) => {
+                    $(ChatMember::$variant(x) => {
                         Some(&x.user)
                     })+,
                     _ => None
@@ -419,28 +800,45 @@ impl HandleMessage<'_> {
             };
         }
 
-        if let Some(channel) = channel {
-            let params = GetChatAdministratorsParams::builder()
-                .chat_id(channel.chat_id)
-                .build();
+        let params = GetChatAdministratorsParams::builder()
+            .chat_id(chat_id)
+            .build();
 
-            let authorized = self
-                .inner
-                .bot
-                .get_chat_administrators(&params)
-                .await?
-                .result
-                .iter()
-                .filter_map(|member| user!(member, Administrator, Creator))
-                .any(|user| user.id.try_into() == Ok(self.chat_id()));
+        let is_admin = self
+            .inner
+            .bot
+            .get_chat_administrators(&params)
+            .await?
+            .result
+            .iter()
+            .filter_map(|member| user!(member, Administrator, Creator))
+            .any(|user| user.id == user_id);
+
+        Ok(is_admin)
+    }
+
+    fn is_owner(self) -> bool {
+        match (&self.inner.owner, self.user()) {
+            (Some(owner), Some(user)) => owner.is(user),
+            _ => false,
+        }
+    }
+
+    async fn selected_chat(self, channel: &Option<SelectedChannel>) -> HandlerResult<Recipient> {
+        if let Some(channel) = channel {
+            // channels are only selected in private chats, whose id is the user's
+            let authorized = match u64::try_from(self.chat_id()) {
+                Ok(user_id) => self.is_chat_admin(channel.chat_id, user_id).await?,
+                Err(_) => false,
+            };
 
             if authorized {
-                Ok(channel.chat_id)
+                Ok(Recipient::from(channel.chat_id))
             } else {
                 Err(Error::NotChannelAdmin(self.chat_id(), channel.chat_id))
             }
         } else {
-            Ok(self.chat_id())
+            Ok(self.recipient())
         }
     }
 }
@@ -450,12 +848,46 @@ pub struct ArcMessageHandler(Arc<MessageHandler>);
 
 impl UpdateHandler for ArcMessageHandler {
     async fn handle_message(self, message: Box<Message>) {
-        HandleMessage {
-            message: &message,
-            inner: &self.0,
+        let nonce = AtomicU32::default();
+        HandleMessage::new(&message, &self.0, None, &nonce)
+            .await
+            .handle()
+            .await
+    }
+
+    async fn handle_callback_query(self, query: Box<CallbackQuery>) {
+        let (outdated, locale) = match &query.message {
+            Some(MaybeInaccessibleMessage::Message(message)) => {
+                let nonce = AtomicU32::default();
+                let cx = HandleMessage::new(message, &self.0, Some(&query), &nonce).await;
+                (cx.handle_callback().await, cx.locale)
+            }
+            // too old to be edited
+            _ => {
+                let locale = query
+                    .from
+                    .language_code
+                    .as_deref()
+                    .and_then(Locale::from_code);
+                (true, locale.unwrap_or_default())
+            }
+        };
+
+        let text = outdated.then(|| tr!(locale, "error.outdated_selection"));
+        let params = AnswerCallbackQueryParams::builder()
+            .callback_query_id(&query.id)
+            .maybe_text(text)
+            .build();
+
+        if let Err(e) = self.0.bot.answer_callback_query(&params).await {
+            log::warn!("Unable to answer callback query: {e}");
+        }
+    }
+
+    async fn handle_inline_query(self, query: InlineQuery) {
+        if let Err(e) = inline_query::answer(&self.0, &query).await {
+            log::warn!("Unable to answer inline query: {e}");
         }
-        .handle()
-        .await
     }
 
     async fn handle_my_chat_member(self, update: ChatMemberUpdated) {
@@ -465,8 +897,21 @@ impl UpdateHandler for ArcMessageHandler {
             let chat_id = update.chat.id;
 
             let delete_chat = async {
-                self.0.database.remove_subscription(chat_id).await?;
-                self.0.database.remove_dialogue(chat_id).await?;
+                // the chat itself and the topics of a forum group
+                let mut recipients = self.0.database.get_active_chats().await?;
+                recipients.retain(|recipient| recipient.chat_id == chat_id);
+                if !recipients.contains(&chat_id.into()) {
+                    recipients.push(chat_id.into());
+                }
+                for recipient in recipients {
+                    self.0.database.remove_subscription(recipient).await?;
+                    self.0.database.remove_dialogue(recipient).await?;
+                }
+
+                self.0.database.set_locale(chat_id, None).await?;
+                self.0.database.set_member_managed(chat_id, false).await?;
+                self.0.database.remove_channels(chat_id).await?;
+                self.0.database.remove_shared_channel(chat_id).await?;
                 HandlerResult::Ok(())
             };
 
@@ -482,18 +927,44 @@ impl UpdateHandler for ArcMessageHandler {
 pub async fn run(
     bot: crate::Bot,
     database: SharedDatabaseConnection,
-    owner: Option<String>,
+    owner: Option<Owner>,
+    branding: Branding,
+    instances: Vec<Instance>,
     shutdown: oneshot::Receiver<()>,
 ) {
-    let message_handler = MessageHandler::new(bot.clone(), database, owner)
+    let message_handler = MessageHandler::new(bot.clone(), database, owner, branding, instances)
         .await
         .unwrap();
 
     bot_utils::updates::handle_updates(
         bot,
         ArcMessageHandler(Arc::new(message_handler)),
-        vec![AllowedUpdate::Message, AllowedUpdate::MyChatMember],
+        vec![
+            AllowedUpdate::Message,
+            AllowedUpdate::MyChatMember,
+            AllowedUpdate::CallbackQuery,
+            AllowedUpdate::InlineQuery,
+        ],
         shutdown,
     )
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::commands;
+    use crate::locale::Locale;
+
+    #[test]
+    fn test_command_descriptions() {
+        for command in commands() {
+            for &locale in Locale::LOCALES {
+                let key = format!("command.{}", command.name);
+                assert!(
+                    locale.get(&key).is_some(),
+                    "{key:?} is missing in {locale:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! The texts that depend on the municipality served by the bot and on its operator. They are
+//! loaded at startup from a JSON file given with `--branding`, e.g.
+//!
+//! ```json
+//! {
+//!     "city": "Köln",
+//!     "council_system": { "name": "Stadt Köln", "url": "https://ratsinformation.stadt-koeln.de/" },
+//!     "contact": "ratsbot@example.org",
+//!     "examples": { "titel": ["Bebauungsplan"] }
+//! }
+//! ```
+//!
+//! Missing fields keep the defaults, which are the texts for Bonn.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::allris::Instance;
+use crate::types::Tag;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CouncilSystem {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Branding {
+    /// the title of the help message
+    pub bot_name: String,
+    /// the municipality served by the bot, as used in its short description
+    pub city: String,
+    /// the Allris instance used if neither `--allris-url` nor `--oparl-url` is given
+    pub council_system: CouncilSystem,
+    /// how users can reach the operator, defaults to the owner's Telegram username
+    pub contact: Option<String>,
+    /// the privacy policy, formatted as Telegram HTML
+    pub privacy_policy: String,
+    /// example values for the tags without known values, keyed by the tags' keys
+    pub examples: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        let examples = [
+            ("titel", &["Bebauungsplan", "Haushaltssatzung"][..]),
+            (
+                "volltext",
+                &["Fahrradstraße", "Baumfällung", "Schulwegsicherung"],
+            ),
+        ];
+
+        Self {
+            bot_name: "Allris-Bot".into(),
+            city: "Bonn".into(),
+            council_system: CouncilSystem {
+                name: "Stadt Bonn".into(),
+                url: "https://www.bonn.sitzung-online.de/".into(),
+            },
+            contact: None,
+            privacy_policy: include_str!("bot/privacy.html").into(),
+            examples: examples
+                .into_iter()
+                .map(|(key, values)| (key.into(), values.iter().map(|&x| x.into()).collect()))
+                .collect(),
+        }
+    }
+}
+
+impl Branding {
+    /// Reads the branding from a JSON file, for use as a `clap` value parser
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        Self::parse(&json).map_err(|e| format!("{path}: {e}"))
+    }
+
+    fn parse(json: &str) -> Result<Self, String> {
+        let branding: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        if let Some(key) = branding
+            .examples
+            .keys()
+            .find(|k| Tag::from_key(k).is_none())
+        {
+            return Err(format!("unknown tag {key:?} in the examples"));
+        }
+
+        Ok(branding)
+    }
+
+    pub fn council_system(&self) -> Result<Instance, String> {
+        let CouncilSystem { name, url } = &self.council_system;
+        Instance::parse_allris(&format!("{name}={url}"))
+    }
+
+    /// Examples for a tag without known values
+    pub fn examples(&self, tag: Tag) -> &[String] {
+        self.examples
+            .iter()
+            .find(|(key, _)| Tag::from_key(key) == Some(tag))
+            .map_or(&[], |(_, values)| values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Branding;
+    use crate::types::Tag;
+
+    #[test]
+    fn test_parse() {
+        let branding = Branding::parse(r#"{"city": "Köln", "examples": {"Titel": ["Rat"]}}"#);
+        let branding = branding.unwrap();
+        assert_eq!(branding.city, "Köln");
+        assert_eq!(branding.bot_name, Branding::default().bot_name);
+        assert_eq!(branding.examples(Tag::Title), ["Rat"]);
+        assert!(branding.examples(Tag::Volltext).is_empty());
+
+        assert!(Branding::parse(r#"{"examples": {"farbe": ["rot"]}}"#).is_err());
+        assert!(Branding::parse(r#"{"stadt": "Köln"}"#).is_err());
+        assert!(Branding::default().council_system().is_ok());
+    }
+}
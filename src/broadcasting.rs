@@ -1,43 +1,109 @@
 use std::future;
+use std::sync::Arc;
 use std::time::Duration;
 
-use bot_utils::ChatId;
 use bot_utils::broadcasting::{Backend, NextUpdate};
+use bot_utils::{ChatId, Recipient};
+use chrono::Utc;
 use frankenstein::AsyncTelegramApi as _;
+use frankenstein::types::ReplyParameters;
 use futures_util::{Stream, StreamExt, stream};
-use regex::Regex;
+use telegram_message_builder::CHAR_LIMIT;
 use tokio::time::sleep;
 
-use crate::database::{self, ChatState, DatabaseConnection, SharedDatabaseConnection, StreamId};
+use crate::database::{
+    self, ChatState, DIGESTED, DatabaseConnection, SharedDatabaseConnection, StreamId,
+};
+use crate::digest::DigestItem;
+use crate::filter_cache::{CompiledFilters, FilterCache};
+use crate::locale::Locale;
 use crate::lru_cache::{CacheItem, Lru, LruCache};
-use crate::types::{Condition, Filter, Message};
+use crate::types::{Audience, DeliveryMode, Filter, Message, MessageKind, Tag};
 
-impl Condition {
-    fn matches(&self, message: &Message) -> bool {
-        let Ok(regex) = Regex::new(&self.pattern) else {
-            log::warn!("Invalid regex pattern!");
+impl Filter {
+    /// Whether the rule applies to the message, regardless of its expression
+    pub fn applies_to(&self, message: &Message) -> bool {
+        if !self.scope.covers(message.kind) {
             return false;
-        };
+        }
+
+        if message.kind == MessageKind::PaperUpdate && !self.updates {
+            return false;
+        }
 
-        let result = message
-            .tags
-            .iter()
-            .filter(|x| x.0 == self.tag)
-            .any(|x| regex.is_match(&x.1));
+        // messages from before multiple instances were supported are not tagged
+        if let Some(instance) = &message.instance
+            && !self.instances.is_empty()
+            && !self.instances.contains(instance)
+        {
+            return false;
+        }
 
-        result ^ self.negate
+        true
     }
 }
 
-impl Filter {
-    fn matches(&self, message: &Message) -> bool {
-        for condition in &self.conditions {
-            if !condition.matches(message) {
-                return false;
+/// Number of characters shown before and after a match in the full text
+const SNIPPET_CONTEXT: usize = 60;
+const MAX_SNIPPETS: usize = 3;
+
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let to = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(text.len(), |(i, _)| end + i);
+
+    let prefix = if from > 0 { "…" } else { "" };
+    let suffix = if to < text.len() { "…" } else { "" };
+    format!("{prefix}{}{suffix}", &text[from..to])
+}
+
+/// Passages of the full text that match one of the positive full-text conditions of those
+/// filters that match the message and have snippets enabled
+fn fulltext_snippets(filters: &CompiledFilters, message: &Message) -> Vec<String> {
+    let Some((_, text)) = message.tags.iter().find(|(tag, _)| *tag == Tag::Volltext) else {
+        return vec![];
+    };
+
+    let mut snippets = vec![];
+    for regex in filters.snippet_patterns(message) {
+        for m in regex.find_iter(text).filter(|m| !m.is_empty()) {
+            if snippets.len() == MAX_SNIPPETS {
+                return snippets;
+            }
+
+            let snippet = snippet(text, m.start(), m.end());
+            if !snippets.contains(&snippet) {
+                snippets.push(snippet);
             }
         }
+    }
 
-        true
+    snippets
+}
+
+/// Appends the snippets to the message text, as far as the character limit allows
+fn append_snippets(text: &mut String, snippets: &[String], locale: Locale) {
+    let header = format!("\n\n{}", tr!(locale, "snippets.header"));
+
+    let mut len = text.encode_utf16().count() + header.encode_utf16().count();
+    let mut appendix = header.clone();
+    for snippet in snippets {
+        let line = format!("\n„{snippet}“");
+        len += line.encode_utf16().count();
+        if len > CHAR_LIMIT {
+            break;
+        }
+        appendix.push_str(&line);
+    }
+
+    if appendix.len() > header.len() {
+        text.push_str(&appendix);
     }
 }
 
@@ -45,6 +111,7 @@ pub struct RedisBackend {
     pub bot: crate::Bot,
     pub db: SharedDatabaseConnection,
     pub cache: LruCache<StreamId, (StreamId, Message)>,
+    pub filters: FilterCache,
 }
 
 impl RedisBackend {
@@ -52,7 +119,12 @@ impl RedisBackend {
         let db = DatabaseConnection::new(db, None).into_shared();
         let cache = LruCache::new(Lru::new(30));
 
-        Self { bot, db, cache }
+        Self {
+            bot,
+            db,
+            cache,
+            filters: FilterCache::default(),
+        }
     }
 
     async fn get_next_entry(
@@ -60,14 +132,68 @@ impl RedisBackend {
         last_sent: StreamId,
     ) -> database::Result<Option<CacheItem<(StreamId, Message)>>> {
         self.cache
-            .get_some(last_sent, || self.db.get_next_message(last_sent))
+            .get_some(last_sent, async || {
+                let Some((id, mut message)) = self.db.get_next_message(last_sent).await? else {
+                    return Ok(None);
+                };
+
+                // without it, full-text conditions don't match
+                if let Some(fulltext) = &message.fulltext {
+                    match self.db.get_fulltext(fulltext).await? {
+                        Some(text) => message.restore_fulltext(text),
+                        None => log::warn!(
+                            "Full text of message {id} has expired, full-text conditions don't match it"
+                        ),
+                    }
+                }
+
+                Ok(Some((id, message)))
+            })
             .await
     }
 
-    async fn matches_filter(&self, chat: i64, msg: &Message) -> database::Result<bool> {
-        let filters = self.db.get_filters(chat).await?;
-        let matches = filters.iter().any(|filter| filter.matches(msg));
-        Ok(matches)
+    /// Returns the chat's filters and delivery mode, which are loaded together
+    async fn get_filters(
+        &self,
+        chat: Recipient,
+        filter_version: Option<u64>,
+    ) -> database::Result<(Arc<CompiledFilters>, DeliveryMode)> {
+        match self.filters.get(chat, filter_version) {
+            Some(cached) => Ok(cached),
+            None => {
+                let (filters, delivery, version) = self.db.get_versioned_filters(chat).await?;
+                Ok(self.filters.insert(chat, version, filters, delivery))
+            }
+        }
+    }
+
+    async fn matches_filter(
+        &self,
+        chat: Recipient,
+        filter_version: Option<u64>,
+        msg: &Message,
+    ) -> database::Result<bool> {
+        Ok(self.get_filters(chat, filter_version).await?.0.matches(msg))
+    }
+
+    async fn is_recipient(
+        &self,
+        chat: Recipient,
+        filter_version: Option<u64>,
+        msg: &Message,
+    ) -> database::Result<bool> {
+        match msg.audience {
+            Audience::Subscribers => self.matches_filter(chat, filter_version, msg).await,
+            Audience::RecipientsOf(original) => {
+                let delivery = self.db.get_delivery(original, chat).await?;
+                Ok(delivery.is_some())
+            }
+            Audience::Chat(target) => {
+                Ok(target == chat && self.matches_filter(chat, filter_version, msg).await?)
+            }
+            Audience::Direct(target) => Ok(target == chat),
+            Audience::Announcement(target) => Ok(target.includes(chat)),
+        }
     }
 }
 
@@ -80,18 +206,18 @@ impl Backend for RedisBackend {
 
     async fn acknowledge(
         &self,
-        chat_id: i64,
+        recipient: Recipient,
         message_id: Self::UpdateId,
     ) -> Result<bool, Self::Error> {
-        self.db.acknowledge_message(chat_id, message_id).await
+        self.db.acknowledge_message(recipient, message_id).await
     }
 
     async fn unacknowledge(
         &self,
-        chat_id: i64,
+        recipient: Recipient,
         message_id: Self::UpdateId,
     ) -> Result<bool, Self::Error> {
-        self.db.unacknowledge_message(chat_id, message_id).await
+        self.db.unacknowledge_message(recipient, message_id).await
     }
 
     async fn migrate_chat(
@@ -102,20 +228,50 @@ impl Backend for RedisBackend {
         self.db.migrate_chat(old_chat_id, new_chat_id).await
     }
 
-    async fn remove_chat(&self, chat_id: ChatId) -> Result<bool, Self::Error> {
-        self.db.remove_subscription(chat_id).await
+    async fn remove_chat(&self, recipient: Recipient) -> Result<bool, Self::Error> {
+        self.filters.remove(recipient);
+        self.db.remove_subscription(recipient).await
     }
 
-    async fn next_update(&self, chat: ChatId) -> Result<NextUpdate<Self>, Self::Error> {
-        let last_sent = match self.db.get_chat_state(chat).await? {
-            ChatState::Active { last_sent } => last_sent,
+    async fn next_update(&self, chat: Recipient) -> Result<NextUpdate<Self>, Self::Error> {
+        let (last_sent, filter_version) = match self.db.get_chat_state(chat).await? {
+            ChatState::Active {
+                last_sent,
+                filter_version,
+            } => (last_sent, filter_version),
             ChatState::Migrated { to } => return Ok(NextUpdate::Migrated { to }),
-            ChatState::Stopped => return Ok(NextUpdate::Stopped),
+            ChatState::Stopped => {
+                // e.g. the bot was removed from the chat
+                self.filters.remove(chat);
+                return Ok(NextUpdate::Stopped);
+            }
         };
 
         let update = match self.get_next_entry(last_sent).await? {
-            Some(msg) if self.matches_filter(chat, &msg.1).await? => {
-                NextUpdate::Ready { id: msg.0, msg }
+            Some(msg) if self.is_recipient(chat, filter_version, &msg.1).await? => {
+                let mode = match msg.1.kind {
+                    MessageKind::Digest | MessageKind::Announcement => DeliveryMode::Instant,
+                    _ => self.get_filters(chat, filter_version).await?.1,
+                };
+
+                if mode == DeliveryMode::Instant {
+                    NextUpdate::Ready { id: msg.0, msg }
+                } else {
+                    // collected for the digest. Adding it again after a restart does no harm,
+                    // so it is added before the message is acknowledged.
+                    let item = serde_json::to_string(&DigestItem::new(msg.0, &msg.1))?;
+                    let due = mode.next_due(Utc::now());
+                    self.db.add_to_digest(chat, msg.0, &item, due).await?;
+                    if msg.1.kind == MessageKind::Paper {
+                        self.db.record_delivery(msg.0, chat, DIGESTED).await?;
+                    }
+
+                    if self.acknowledge(chat, msg.0).await? {
+                        NextUpdate::Skipped { id: msg.0 }
+                    } else {
+                        NextUpdate::OutOfSync
+                    }
+                }
             }
             Some(msg) => {
                 if self.acknowledge(chat, msg.0).await? {
@@ -132,7 +288,7 @@ impl Backend for RedisBackend {
         Ok(update)
     }
 
-    fn receive_updates(&self) -> impl Stream<Item = (StreamId, Vec<ChatId>)> + 'static {
+    fn receive_updates(&self) -> impl Stream<Item = (StreamId, Vec<Recipient>)> + 'static {
         let db = self.db.get_dedicated();
 
         stream::unfold(
@@ -163,13 +319,148 @@ impl Backend for RedisBackend {
         .filter_map(future::ready)
     }
 
-    async fn send(&self, chat_id: i64, message: &Self::Message) -> Result<(), frankenstein::Error> {
-        let message = &message.1;
+    async fn send(
+        &self,
+        recipient: Recipient,
+        message: &Self::Message,
+    ) -> Result<(), frankenstein::Error> {
+        let (message_id, message) = &**message;
         let mut params = message.request.clone();
-        params.chat_id = chat_id.into();
+        params.chat_id = recipient.chat_id.into();
+        params.message_thread_id = recipient.thread_id;
+
+        if let Audience::RecipientsOf(original) = message.audience {
+            // reply to the original notification, unless it was part of a digest
+            match self.db.get_delivery(original, recipient).await {
+                Ok(None | Some(DIGESTED)) => (),
+                Ok(Some(reply_to)) => {
+                    let reply_parameters = ReplyParameters::builder()
+                        .message_id(reply_to)
+                        .allow_sending_without_reply(true)
+                        .build();
+                    params.reply_parameters = Some(reply_parameters);
+                }
+                Err(e) => log::warn!("Unable to get original message: {e}"),
+            }
+        }
+
+        if message.tags.iter().any(|(tag, _)| *tag == Tag::Volltext) {
+            // the filters have just been matched against the message
+            let filters = match self.filters.latest(recipient) {
+                Some(filters) => Ok(filters),
+                None => self
+                    .get_filters(recipient, None)
+                    .await
+                    .map(|(filters, _)| filters),
+            };
+
+            let snippets = match filters {
+                Ok(filters) => fulltext_snippets(&filters, message),
+                Err(e) => {
+                    log::warn!("Unable to get filters for snippets: {e}");
+                    vec![]
+                }
+            };
+
+            if !snippets.is_empty() {
+                let locale = match self.db.get_locale(recipient.chat_id).await {
+                    Ok(locale) => locale.unwrap_or_default(),
+                    Err(e) => {
+                        log::warn!("Unable to get locale: {e}");
+                        Locale::default()
+                    }
+                };
+                append_snippets(&mut params.text, &snippets, locale);
+            }
+        }
 
-        self.bot.send_message(&params).await?;
+        let sent = self.bot.send_message(&params).await?.result;
+
+        if message.kind == MessageKind::Paper {
+            let result = self
+                .db
+                .record_delivery(*message_id, recipient, sent.message_id)
+                .await;
+
+            if let Err(e) = result {
+                log::warn!("Unable to record delivery: {e}");
+            }
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use frankenstein::methods::SendMessageParams;
+
+    use super::{append_snippets, snippet};
+    use crate::filter_cache::CompiledFilters;
+    use crate::locale::Locale;
+    use crate::types::{Condition, Expr, Filter, MatchMode, Message, Scope, Tag};
+
+    #[test]
+    fn test_match_modes() {
+        let message = Message {
+            request: SendMessageParams::builder().chat_id(0).text("").build(),
+            tags: vec![(Tag::Gremium, "Ausschuss für Schule und Bildung".to_string())],
+            kind: Default::default(),
+            audience: Default::default(),
+            instance: None,
+            fulltext: None,
+        };
+        let matches = |mode, pattern: &str| {
+            let condition = Condition {
+                tag: Tag::Gremium,
+                pattern: pattern.to_string(),
+                negate: false,
+                mode,
+            };
+            let filter = Filter {
+                expression: Expr::Condition(condition),
+                scope: Scope::All,
+                updates: false,
+                instances: vec![],
+                snippets: false,
+            };
+            CompiledFilters::new(vec![filter]).matches(&message)
+        };
+
+        assert!(matches(MatchMode::Contains, "schule"));
+        assert!(!matches(MatchMode::Contains, "(?i)"));
+        assert!(matches(MatchMode::Word, "SCHULE"));
+        assert!(!matches(MatchMode::Word, "schul"));
+        assert!(matches(
+            MatchMode::Exact,
+            "ausschuss für schule und bildung"
+        ));
+        assert!(!matches(MatchMode::Exact, "Ausschuss"));
+        assert!(matches(MatchMode::Prefix, "ausschuss f"));
+        assert!(!matches(MatchMode::Prefix, "Schule"));
+        assert!(matches(MatchMode::Regex, "Schule|Sport"));
+        assert!(!matches(MatchMode::Regex, "schule"));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = "a".repeat(100) + "Fahrradstraße" + &"ö".repeat(100);
+        let start = 100;
+        let end = start + "Fahrradstraße".len();
+
+        let expected = format!("…{}Fahrradstraße{}…", "a".repeat(60), "ö".repeat(60));
+        assert_eq!(snippet(&text, start, end), expected);
+        assert_eq!(snippet("kurze Fahrradstraße", 6, 20), "kurze Fahrradstraße");
+    }
+
+    #[test]
+    fn test_append_snippets() {
+        // emojis count twice towards the limit, which is measured in UTF-16 code units
+        let mut text = "🚲".repeat(2000);
+        let snippets = ["a".repeat(50), "b".repeat(50)];
+        append_snippets(&mut text, &snippets, Locale::En);
+
+        assert!(text.ends_with(&format!("Matches in the full text:\n„{}“", snippets[0])));
+        assert!(text.encode_utf16().count() <= telegram_message_builder::CHAR_LIMIT);
+    }
+}
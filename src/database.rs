@@ -1,27 +1,147 @@
+use std::collections::HashMap;
 use std::fmt::{self, Debug};
+use std::str::FromStr;
 use std::time::Duration;
 
+use bot_utils::Recipient;
 use chrono::{DateTime, Utc};
 use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, Client, Cmd, FromRedisValue, RedisWrite, RetryMethod};
-use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tokio::time::{Instant, sleep_until};
 
-use crate::types::{Filter, Message};
+use crate::locale::Locale;
+use crate::types::{DeliveryMode, Filter, Message, Tag, TrackedPaper};
 
 const REGISTERED_CHATS_KEY: &str = "allrisbot:registered_chats";
 const KNOWN_ITEMS_KEY: &str = "allrisbot:known_items";
+const KNOWN_MEETINGS_KEY: &str = "allrisbot:known_meetings";
+const KNOWN_RESULTS_KEY: &str = "allrisbot:known_results";
+const PAPER_FINGERPRINTS_KEY: &str = "allrisbot:paper_fingerprints";
 const SCHEDULED_MESSAGES_KEY: &str = "allrisbot:scheduled_messages";
 const LAST_UPDATE_KEY: &str = "allrisbot:last_update";
+const UPDATE_PROGRESS_KEY: &str = "allrisbot:update_progress";
+const DIGEST_CHATS_KEY: &str = "allrisbot:digest_chats";
+/// the locales chosen for chats, see [`Locale`]
+const CHAT_LOCALES_KEY: &str = "allrisbot:chat_locales";
+/// counter that provides a new version number whenever the filters of a chat change
+const FILTER_VERSION_KEY: &str = "allrisbot:filter_version";
+/// the outcome of the last update of each Allris instance, see [`UpdateStatus`]
+const UPDATE_STATUS_KEY: &str = "allrisbot:update_status";
+/// groups in which all members may manage the rules, not only the administrators
+const MEMBER_MANAGED_CHATS_KEY: &str = "allrisbot:member_managed_chats";
+
+fn registered_chat_key(recipient: Recipient) -> String {
+    format!("allrisbot:registered_chats:{recipient}")
+}
+
+fn dialogue_key(recipient: Recipient) -> String {
+    format!("allrisbot:dialogue:{recipient}")
+}
+
+/// Hash of the channels a user has shared with the bot, indexed by their chat ids
+fn channels_key(user_id: i64) -> String {
+    format!("allrisbot:channels:{user_id}")
+}
+
+/// Set of the users who have shared a channel with the bot, the reverse of [`channels_key`]
+fn channel_users_key(channel_id: i64) -> String {
+    format!("allrisbot:channel_users:{channel_id}")
+}
+
+fn digest_key(recipient: Recipient) -> String {
+    format!("allrisbot:digest:{recipient}")
+}
+
+/// Parses the members of a set of chats, skipping malformed ones
+fn parse_recipients(members: Vec<String>) -> Vec<Recipient> {
+    members
+        .into_iter()
+        .filter_map(|member| match member.parse() {
+            Ok(recipient) => Some(recipient),
+            Err(e) => {
+                log::warn!("Ignoring malformed chat {member:?}: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Keys of the scraper's state are kept separately for each Allris instance
+fn instance_key(key: &str, instance: &str) -> String {
+    format!("{key}:{instance}")
+}
+
+fn tracked_consultation_key(consultation: &str) -> String {
+    format!("allrisbot:tracked_consultation:{consultation}")
+}
 
-fn registered_chat_key(chat_id: i64) -> String {
-    format!("allrisbot:registered_chats:{chat_id}")
+/// Hash of cached OParl objects, e.g. organizations, indexed by their url
+fn object_cache_key(namespace: &str) -> String {
+    format!("allrisbot:cache:{namespace}")
 }
 
-fn dialogue_key(chat_id: i64) -> String {
-    format!("allrisbot:dialogue:{chat_id}")
+fn deliveries_key(message_id: StreamId) -> String {
+    format!("allrisbot:deliveries:{message_id}")
+}
+
+/// The full text of a paper, see [`Message::fulltext`]
+fn fulltext_key(id: &str) -> String {
+    format!("allrisbot:fulltext:{id}")
+}
+
+/// Sorted set of the values seen for a tag, scored by their frequency
+fn tag_values_key(tag: Tag) -> String {
+    format!("allrisbot:tag_values:{}", tag.key())
+}
+
+/// Sorted set of the values seen for a tag, scored by the time they were last seen
+fn tag_values_seen_key(tag: Tag) -> String {
+    format!("allrisbot:tag_values_seen:{}", tag.key())
+}
+
+/// How long papers are tracked for results of their consultations, and how long the ids
+/// of sent notifications are kept to reference them in follow-up messages.
+const TRACKING_TTL_SECS: u64 = 60 * 60 * 24 * 180;
+
+/// How long the full texts are kept. Messages that are broadcast later on are matched
+/// without their full text, which is logged.
+const FULLTEXT_TTL_SECS: u64 = 60 * 60 * 24 * 30;
+
+/// How long tag values are remembered after they were last seen
+const TAG_VALUES_RETENTION_SECS: i64 = 60 * 60 * 24 * 730;
+
+/// Recorded instead of the Telegram message id for notifications that were collected for a
+/// digest, so that follow-ups reach the chat, although without replying
+pub const DIGESTED: i32 = 0;
+
+/// Up to how many unprocessed messages are counted for a chat, see [`ChatOverview`]
+pub const BACKLOG_LIMIT: u64 = 1000;
+/// How many chats are looked at per round trip when building the [`ChatOverview`]s
+const OVERVIEW_BATCH_SIZE: usize = 100;
+
+/// Separate namespaces for the ids of items that have already been handled by the scraper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnownItems {
+    Papers,
+    Meetings,
+    Results,
+}
+
+impl KnownItems {
+    fn legacy_key(self) -> &'static str {
+        match self {
+            KnownItems::Papers => KNOWN_ITEMS_KEY,
+            KnownItems::Meetings => KNOWN_MEETINGS_KEY,
+            KnownItems::Results => KNOWN_RESULTS_KEY,
+        }
+    }
+
+    fn key(self, instance: &str) -> String {
+        instance_key(self.legacy_key(), instance)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -56,6 +176,20 @@ pub struct StreamId(u64, u64);
 
 impl StreamId {
     const ZERO: Self = StreamId(0, 0);
+
+    /// The time the entry was added to the stream
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.0.try_into().ok()?)
+    }
+
+    /// The smallest possible id after this one, as `XRANGE` only excludes the start since
+    /// Redis 6.2
+    fn next(self) -> Self {
+        match self.1.checked_add(1) {
+            Some(sequence) => StreamId(self.0, sequence),
+            None => StreamId(self.0 + 1, 0),
+        }
+    }
 }
 
 impl fmt::Display for StreamId {
@@ -64,6 +198,36 @@ impl fmt::Display for StreamId {
     }
 }
 
+impl FromStr for StreamId {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (a, b) = s.split_once('-').ok_or("Stream ID has invalid format.")?;
+        let a = a.parse().map_err(|_| "Stream ID has invalid format.")?;
+        let b = b.parse().map_err(|_| "Stream ID has invalid format.")?;
+
+        Ok(Self(a, b))
+    }
+}
+
+impl Serialize for StreamId {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for StreamId {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 macro_rules! invalid_type_error {
     ($v:expr,$det:expr) => {
         return Err(redis::RedisError::from((
@@ -86,12 +250,10 @@ impl redis::FromRedisValue for StreamId {
         try_assign! {
             let redis::Value::BulkString(bytes) = v, else "Stream ID is not a bulk string";
             let Ok(string) = std::str::from_utf8(bytes), else "Could not convert from string.";
-            let Some((a, b)) = string.split_once('-'), else "Stream ID has invalid format.";
-            let Ok(a) = a.parse(), else "Stream ID has invalid format.";
-            let Ok(b) = b.parse(), else "Stream ID has invalid format.";
+            let Ok(id) = string.parse(), else "Stream ID has invalid format.";
         }
 
-        Ok(Self(a, b))
+        Ok(id)
     }
 }
 
@@ -154,13 +316,15 @@ impl DatabaseConnection {
     }
 
     async fn get_connection(&mut self) -> Result<&mut MultiplexedConnection> {
-        if self.connection.is_some() {
-            Ok(self.connection.as_mut().unwrap())
-        } else {
+        if self.connection.is_none() {
             let connection = self.client.get_multiplexed_async_connection().await?;
-            let connection_ref = self.connection.insert(connection);
-            Ok(connection_ref)
+            self.connection = Some(connection);
         }
+
+        Ok(self
+            .connection
+            .as_mut()
+            .expect("connection was just established"))
     }
 
     /// handles an error response
@@ -181,7 +345,7 @@ impl DatabaseConnection {
             RetryMethod::RetryImmediately if self.retry_counter == 1 => return Ok(()),
             RetryMethod::WaitAndRetry | RetryMethod::RetryImmediately => {
                 // reconnect once in a while if it doesn't work
-                if self.retry_counter % 3 == 0 {
+                if self.retry_counter.is_multiple_of(3) {
                     self.connection = None;
                 }
             }
@@ -338,50 +502,224 @@ macro_rules! implement_with_retry {
 }
 
 pub enum ChatState {
-    Active { last_sent: StreamId },
-    Migrated { to: i64 },
+    /// `filter_version` changes whenever the chat's filters or its delivery mode do. It is
+    /// missing if neither has changed since versions were introduced.
+    Active {
+        last_sent: StreamId,
+        filter_version: Option<u64>,
+    },
+    Migrated {
+        to: i64,
+    },
     Stopped,
 }
 
+/// The outcome of an update of an Allris instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    pub finished: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+/// A registered chat, as shown to the owner
+#[derive(Debug, Clone, Copy)]
+pub struct ChatOverview {
+    pub recipient: Recipient,
+    pub rules: usize,
+    /// the number of messages in the stream that the chat hasn't processed yet, up to
+    /// [`BACKLOG_LIMIT`]
+    pub backlog: u64,
+}
+
+/// A message serialized for the stream. The full text is stored under its own key, as
+/// everyone reading the stream would have to load it otherwise. It is written together
+/// with the stream entry, so that nothing is left behind if the message isn't added.
+struct StreamMessage {
+    serialized: String,
+    fulltext_key: String,
+    /// empty if there is no full text
+    fulltext: String,
+}
+
+impl StreamMessage {
+    fn new(message: &Message) -> Result<Self> {
+        let mut message = message.clone();
+        let id = format!("{:016x}", rand::random::<u64>());
+        let fulltext = message.take_fulltext().unwrap_or_default();
+        if !fulltext.is_empty() {
+            message.fulltext = Some(id.clone());
+        }
+
+        Ok(Self {
+            serialized: serde_json::to_string(&message)?,
+            fulltext_key: fulltext_key(&id),
+            fulltext,
+        })
+    }
+}
+
 // all operations are designed to be more or less idempotent, or at least not having severe consequences
 // if they are executed twice, so it's always good to retry if it fails.
 implement_with_retry! {
     DatabaseConnection, SharedDatabaseConnection;
 
-    pub async fn is_known_volfdnr(connection, volfdnr: &str) -> bool {
-        connection.sismember(KNOWN_ITEMS_KEY, volfdnr).await?
+    pub async fn is_known_item(connection, instance: &str, namespace: KnownItems, id: &str) -> bool {
+        connection.sismember(namespace.key(instance), id).await?
+    }
+
+    pub async fn add_known_item(connection, instance: &str, namespace: KnownItems, id: &str) -> () {
+        connection.sadd(namespace.key(instance), id).await?
     }
 
-    pub async fn add_known_volfdnr(connection, volfdnr: &str) -> () {
-        connection.sadd(KNOWN_ITEMS_KEY, volfdnr).await?
+    // Moves the scraper's state from the time before multiple instances were supported
+    // to the keys of the given instance, unless these are already in use.
+    pub async fn migrate_legacy_keys(connection, instance: &str) -> () {
+        let mut script = script!("migrate_legacy_keys.lua").prepare_invoke();
+        for namespace in [KnownItems::Papers, KnownItems::Meetings, KnownItems::Results] {
+            script.key(namespace.legacy_key()).key(namespace.key(instance));
+        }
+        for key in [PAPER_FINGERPRINTS_KEY, LAST_UPDATE_KEY] {
+            script.key(key).key(instance_key(key, instance));
+        }
+
+        script.invoke_async(connection).await?
     }
 
+    // Schedules a message, unless the item is already known. If `tracking` is given, the
+    // paper's consultations will be watched for results.
     pub async fn schedule_broadcast(
         connection,
-        volfdnr: &str,
-        message: &Message
+        instance: &str,
+        namespace: KnownItems,
+        id: &str,
+        message: &Message,
+        tracking: Option<(&[String], &TrackedPaper)>
     ) -> Option<StreamId> {
-        let serialized = serde_json::to_string(message)?;
+        let message = StreamMessage::new(message)?;
 
-        script!("schedule_broadcast.lua")
+        let mut script = script!("schedule_broadcast.lua").prepare_invoke();
+        script
+            .key(SCHEDULED_MESSAGES_KEY)
+            .key(namespace.key(instance))
+            .key(&message.fulltext_key)
+            .arg(id)
+            .arg(&message.serialized)
+            .arg(&message.fulltext)
+            .arg(FULLTEXT_TTL_SECS);
+
+        if let Some((consultations, paper)) = tracking {
+            for consultation in consultations {
+                script.key(tracked_consultation_key(consultation));
+            }
+            script.arg(serde_json::to_string(paper)?).arg(TRACKING_TTL_SECS);
+        }
+
+        script.invoke_async(connection).await?
+    }
+
+    pub async fn get_fingerprint(connection, instance: &str, volfdnr: &str) -> Option<String> {
+        connection.hget(instance_key(PAPER_FINGERPRINTS_KEY, instance), volfdnr).await?
+    }
+
+    // Replaces the stored fingerprint of a paper, if it still equals `expected`. If
+    // a message is given, it will be scheduled at the same time.
+    pub async fn update_fingerprint(
+        connection,
+        instance: &str,
+        volfdnr: &str,
+        expected: Option<&str>,
+        fingerprint: &str,
+        message: Option<&Message>
+    ) -> bool {
+        let mut script = script!("update_fingerprint.lua").prepare_invoke();
+        script
+            .key(instance_key(PAPER_FINGERPRINTS_KEY, instance))
             .key(SCHEDULED_MESSAGES_KEY)
-            .key(KNOWN_ITEMS_KEY)
             .arg(volfdnr)
-            .arg(&serialized)
-            .invoke_async(connection)
+            .arg(expected.unwrap_or_default())
+            .arg(fingerprint);
+
+        if let Some(message) = message {
+            let message = StreamMessage::new(message)?;
+            script
+                .key(&message.fulltext_key)
+                .arg(&message.serialized)
+                .arg(&message.fulltext)
+                .arg(FULLTEXT_TTL_SECS);
+        }
+
+        script.invoke_async(connection).await?
+    }
+
+    // Schedules a message without marking any item as known, e.g. when papers are replayed
+    pub async fn schedule_message(connection, message: &Message) -> StreamId {
+        let message = StreamMessage::new(message)?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if !message.fulltext.is_empty() {
+            pipe.set_ex(&message.fulltext_key, &message.fulltext, FULLTEXT_TTL_SECS)
+                .ignore();
+        }
+        pipe.cmd("XADD")
+            .arg(SCHEDULED_MESSAGES_KEY)
+            .arg("*")
+            .arg("message")
+            .arg(&message.serialized);
+
+        let (id,): (StreamId,) = pipe.query_async(connection).await?;
+        id
+    }
+
+    pub async fn get_tracked_consultation(
+        connection,
+        consultation: &str
+    ) -> Option<(StreamId, TrackedPaper)> {
+        let (message, paper): (Option<StreamId>, Option<String>) = connection
+            .hget(tracked_consultation_key(consultation), &["message", "paper"])
+            .await?;
+
+        match (message, paper) {
+            (Some(message), Some(paper)) => Some((message, serde_json::from_str(&paper)?)),
+            _ => None
+        }
+    }
+
+    // remembers the Telegram message id of a notification sent to a chat
+    pub async fn record_delivery(
+        connection,
+        message_id: StreamId,
+        recipient: Recipient,
+        telegram_message_id: i32
+    ) -> () {
+        let key = deliveries_key(message_id);
+
+        redis::pipe()
+            .atomic()
+            .add_command(Cmd::hset(&key, recipient.to_string(), telegram_message_id))
+            .ignore()
+            .add_command(Cmd::expire(&key, TRACKING_TTL_SECS as i64))
+            .ignore()
+            .query_async(connection)
             .await?
     }
 
+    // returns the Telegram message id of a notification sent to a chat, if it is known
+    pub async fn get_delivery(connection, message_id: StreamId, recipient: Recipient) -> Option<i32> {
+        connection.hget(deliveries_key(message_id), recipient.to_string()).await?
+    }
+
     pub async fn add_subscription(
         connection,
-        chat_id: i64,
+        recipient: Recipient,
         filter: &str
     ) -> bool {
         script!("add_subscription.lua")
             .key(SCHEDULED_MESSAGES_KEY)
             .key(REGISTERED_CHATS_KEY)
-            .key(registered_chat_key(chat_id))
-            .arg(chat_id)
+            .key(registered_chat_key(recipient))
+            .key(FILTER_VERSION_KEY)
+            .arg(recipient.to_string())
             .arg(filter)
             .invoke_async(connection)
             .await?
@@ -389,11 +727,11 @@ implement_with_retry! {
 
     pub async fn acknowledge_message (
         connection,
-        chat_id: i64,
+        recipient: Recipient,
         message_id: StreamId
     ) -> bool {
         script!("acknowledge_message.lua")
-            .key(registered_chat_key(chat_id))
+            .key(registered_chat_key(recipient))
             .key(SCHEDULED_MESSAGES_KEY)
             .arg(message_id)
             .invoke_async(connection)
@@ -407,10 +745,16 @@ implement_with_retry! {
     ) -> bool {
         script!("migrate_chat.lua")
             .key(REGISTERED_CHATS_KEY)
-            .key(registered_chat_key(old_chat_id))
-            .key(registered_chat_key(new_chat_id))
-            .key(dialogue_key(old_chat_id))
-            .key(dialogue_key(new_chat_id))
+            .key(registered_chat_key(Recipient::from(old_chat_id)))
+            .key(registered_chat_key(Recipient::from(new_chat_id)))
+            .key(dialogue_key(Recipient::from(old_chat_id)))
+            .key(dialogue_key(Recipient::from(new_chat_id)))
+            .key(digest_key(Recipient::from(old_chat_id)))
+            .key(digest_key(Recipient::from(new_chat_id)))
+            .key(DIGEST_CHATS_KEY)
+            .key(FILTER_VERSION_KEY)
+            .key(CHAT_LOCALES_KEY)
+            .key(MEMBER_MANAGED_CHATS_KEY)
             .arg(old_chat_id)
             .arg(new_chat_id)
             .invoke_async(connection)
@@ -419,22 +763,26 @@ implement_with_retry! {
 
     pub async fn unacknowledge_message (
         connection,
-        chat_id: i64,
+        recipient: Recipient,
         message_id: StreamId
     ) -> bool {
         script!("unacknowledge_message.lua")
-            .key(registered_chat_key(chat_id))
+            .key(registered_chat_key(recipient))
             .key(SCHEDULED_MESSAGES_KEY)
             .arg(message_id)
             .invoke_async(connection)
             .await?
     }
 
-    pub async fn remove_subscription(connection, chat_id: i64) -> bool {
+    pub async fn remove_subscription(connection, recipient: Recipient) -> bool {
         let [result] = redis::pipe()
             .atomic()
-            .add_command(Cmd::srem(REGISTERED_CHATS_KEY, chat_id))
-            .add_command(Cmd::del(registered_chat_key(chat_id)))
+            .add_command(Cmd::srem(REGISTERED_CHATS_KEY, recipient.to_string()))
+            .add_command(Cmd::del(registered_chat_key(recipient)))
+            .ignore()
+            .add_command(Cmd::del(digest_key(recipient)))
+            .ignore()
+            .add_command(Cmd::zrem(DIGEST_CHATS_KEY, recipient.to_string()))
             .ignore()
             .query_async(connection)
             .await?;
@@ -442,12 +790,13 @@ implement_with_retry! {
         result
     }
 
-    pub async fn get_active_chats(connection) -> Vec<i64> {
-        connection.smembers(REGISTERED_CHATS_KEY).await?
+    pub async fn get_active_chats(connection) -> Vec<Recipient> {
+        let members: Vec<String> = connection.smembers(REGISTERED_CHATS_KEY).await?;
+        parse_recipients(members)
     }
 
-    pub async fn get_filters(connection, chat_id: i64) -> Vec<Filter> {
-        let content : Option<String> = connection.hget(registered_chat_key(chat_id), "filter").await?;
+    pub async fn get_filters(connection, recipient: Recipient) -> Vec<Filter> {
+        let content : Option<String> = connection.hget(registered_chat_key(recipient), "filter").await?;
 
         match content {
             Some(filter) => serde_json::from_str(&filter)?,
@@ -455,9 +804,28 @@ implement_with_retry! {
         }
     }
 
+    /// Returns the filters along with their version, see [`ChatState::Active`]
+    // Returns the filters and the delivery mode, which share a version, see [`ChatState`]
+    pub async fn get_versioned_filters(connection, recipient: Recipient) -> (Vec<Filter>, DeliveryMode, Option<u64>) {
+        let (content, delivery, version): (Option<String>, Option<String>, Option<u64>) = connection
+            .hget(registered_chat_key(recipient), &["filter", "delivery", "filter_version"])
+            .await?;
+
+        let filters = match content {
+            Some(filter) => serde_json::from_str(&filter)?,
+            None => vec![]
+        };
+        let delivery = match delivery {
+            Some(mode) => serde_json::from_str(&mode)?,
+            None => DeliveryMode::default()
+        };
+
+        (filters, delivery, version)
+    }
+
     #[reset_connection_on_error]
-    pub async fn update_filter<T>(connection, chat_id: i64, update: &impl Fn(&mut Vec<Filter>) -> T) -> T {
-        let key = registered_chat_key(chat_id);
+    pub async fn update_filter<T>(connection, recipient: Recipient, update: &impl Fn(&mut Vec<Filter>) -> T) -> T {
+        let key = registered_chat_key(recipient);
         let script_content = include_str!("redis_scripts/add_subscription.lua");
 
         loop {
@@ -481,8 +849,10 @@ implement_with_retry! {
                 if current_filters.is_some() {
                     redis::pipe()
                         .atomic()
-                        .add_command(Cmd::srem(REGISTERED_CHATS_KEY, chat_id))
-                        .add_command(Cmd::del(registered_chat_key(chat_id)))
+                        .add_command(Cmd::srem(REGISTERED_CHATS_KEY, recipient.to_string()))
+                        .add_command(Cmd::del(registered_chat_key(recipient)))
+                        .add_command(Cmd::del(digest_key(recipient)))
+                        .add_command(Cmd::zrem(DIGEST_CHATS_KEY, recipient.to_string()))
                         .query_async(connection)
                         .await?
                 } else {
@@ -493,7 +863,7 @@ implement_with_retry! {
                 let filter_str = serde_json::to_string(&filters)?;
 
                 let mut script = redis::cmd("EVAL");
-                script.arg(script_content).arg(3).arg(&[SCHEDULED_MESSAGES_KEY,REGISTERED_CHATS_KEY, &key]).arg(chat_id).arg(&filter_str);
+                script.arg(script_content).arg(4).arg(&[SCHEDULED_MESSAGES_KEY,REGISTERED_CHATS_KEY, &key, FILTER_VERSION_KEY]).arg(recipient.to_string()).arg(&filter_str);
 
                 redis::pipe()
                     .atomic()
@@ -508,6 +878,87 @@ implement_with_retry! {
         }
     }
 
+    pub async fn get_delivery_mode(connection, recipient: Recipient) -> DeliveryMode {
+        let content: Option<String> = connection.hget(registered_chat_key(recipient), "delivery").await?;
+
+        match content {
+            Some(mode) => serde_json::from_str(&mode)?,
+            None => DeliveryMode::default()
+        }
+    }
+
+    // Changes the delivery mode of a registered chat. A pending digest will be due at `due`.
+    pub async fn set_delivery_mode(
+        connection,
+        recipient: Recipient,
+        mode: DeliveryMode,
+        due: DateTime<Utc>
+    ) -> bool {
+        script!("set_delivery_mode.lua")
+            .key(REGISTERED_CHATS_KEY)
+            .key(registered_chat_key(recipient))
+            .key(DIGEST_CHATS_KEY)
+            .key(FILTER_VERSION_KEY)
+            .arg(recipient.to_string())
+            .arg(serde_json::to_string(&mode)?)
+            .arg(due.timestamp_millis())
+            .invoke_async(connection)
+            .await?
+    }
+
+    // Adds an item to the chat's digest, which will be due at `due` unless it is already scheduled.
+    // Adding the same item twice has no effect.
+    pub async fn add_to_digest(
+        connection,
+        recipient: Recipient,
+        message_id: StreamId,
+        item: &str,
+        due: DateTime<Utc>
+    ) -> () {
+        redis::pipe()
+            .atomic()
+            .add_command(Cmd::zadd(digest_key(recipient), item, message_id.0))
+            .ignore()
+            .add_command(redis::cmd("ZADD").arg(DIGEST_CHATS_KEY).arg("NX").arg(due.timestamp_millis()).arg(recipient.to_string()).to_owned())
+            .ignore()
+            .query_async(connection)
+            .await?
+    }
+
+    pub async fn get_due_digests(connection, now: DateTime<Utc>) -> Vec<Recipient> {
+        let members: Vec<String> = connection.zrangebyscore(DIGEST_CHATS_KEY, "-inf", now.timestamp_millis()).await?;
+        parse_recipients(members)
+    }
+
+    pub async fn get_digest_items(connection, recipient: Recipient) -> Vec<String> {
+        connection.zrange(digest_key(recipient), 0, -1).await?
+    }
+
+    // Schedules the digest messages for the chat and removes the included items. If further items
+    // have been added in the meantime, the next digest will be due at `next_due`.
+    pub async fn schedule_digest(
+        connection,
+        recipient: Recipient,
+        messages: &[Message],
+        items: &[String],
+        next_due: DateTime<Utc>
+    ) -> () {
+        let mut script = script!("schedule_digest.lua").prepare_invoke();
+        script
+            .key(SCHEDULED_MESSAGES_KEY)
+            .key(digest_key(recipient))
+            .key(DIGEST_CHATS_KEY)
+            .arg(recipient.to_string())
+            .arg(next_due.timestamp_millis())
+            .arg(messages.len());
+
+        for message in messages {
+            script.arg(serde_json::to_string(message)?);
+        }
+
+        script.arg(items).invoke_async(connection).await?
+    }
+
     pub async fn current_message_id(
         connection
     ) -> StreamId {
@@ -545,12 +996,70 @@ implement_with_retry! {
             .and_then(|(_, v)| v.into_iter().next())
     }
 
-    pub async fn set_last_update(connection, timestamp: DateTime<Utc>) -> () {
-        connection.set(LAST_UPDATE_KEY, timestamp.timestamp_millis()).await?
+    /// Returns the latest messages of the stream, newest first
+    pub async fn get_recent_messages(connection, count: usize) -> Vec<(StreamId, Message)> {
+        redis::cmd("XREVRANGE")
+            .arg(SCHEDULED_MESSAGES_KEY)
+            .arg("+")
+            .arg("-")
+            .arg("COUNT")
+            .arg(count)
+            .query_async(connection)
+            .await?
+    }
+
+    /// Returns the separately stored full text of a message, unless it has expired
+    pub async fn get_fulltext(connection, id: &str) -> Option<String> {
+        connection.get(fulltext_key(id)).await?
     }
 
-    pub async fn get_last_update(connection) -> Option<DateTime<Utc>> {
-        if let Some(timestamp) = connection.get(LAST_UPDATE_KEY).await? {
+    /// Returns the message with the given id, unless it has been trimmed from the stream
+    pub async fn get_message(connection, id: StreamId) -> Option<Message> {
+        let messages: Vec<(StreamId, Message)> = redis::cmd("XRANGE")
+            .arg(SCHEDULED_MESSAGES_KEY)
+            .arg(id)
+            .arg(id)
+            .query_async(connection)
+            .await?;
+        messages.into_iter().next().map(|(_, message)| message)
+    }
+
+    // Counts the values of the tags that have known values, and remembers when each value was
+    // last seen. Values that haven't been seen for a long time are forgotten.
+    pub async fn record_tag_values(connection, tags: &[(Tag, String)], now: DateTime<Utc>) -> () {
+        let mut recorded: Vec<&(Tag, String)> = vec![];
+        for tag in tags.iter().filter(|(tag, _)| tag.has_known_values()) {
+            if !recorded.contains(&tag) {
+                recorded.push(tag);
+            }
+        }
+
+        let mut script = script!("record_tag_values.lua").prepare_invoke();
+        script
+            .arg(now.timestamp())
+            .arg(now.timestamp() - TAG_VALUES_RETENTION_SECS);
+
+        for (tag, value) in recorded {
+            script
+                .key(tag_values_key(*tag))
+                .key(tag_values_seen_key(*tag))
+                .arg(value);
+        }
+
+        script.invoke_async(connection).await?
+    }
+
+    /// Returns the known values of the tag with their frequency, most frequent first
+    pub async fn get_tag_values(connection, tag: Tag) -> Vec<(String, u64)> {
+        connection.zrevrange_withscores(tag_values_key(tag), 0, -1).await?
+    }
+
+    pub async fn set_last_update(connection, instance: &str, timestamp: DateTime<Utc>) -> () {
+        connection.set(instance_key(LAST_UPDATE_KEY, instance), timestamp.timestamp_millis()).await?
+    }
+
+    pub async fn get_last_update(connection, instance: &str) -> Option<DateTime<Utc>> {
+        if let Some(timestamp) = connection.get(instance_key(LAST_UPDATE_KEY, instance)).await? {
             match DateTime::from_timestamp_millis(timestamp) {
                 Some(d) => Some(d),
                 None => invalid_type_error!(timestamp, "timestamp out of range")
@@ -561,14 +1070,113 @@ implement_with_retry! {
         }
     }
 
+    // the progress of an interrupted update: its start time (field "started") and for each
+    // paper list, the url of the next page to be processed (empty once completed)
+    pub async fn get_update_progress(connection, instance: &str) -> HashMap<String, String> {
+        connection.hgetall(instance_key(UPDATE_PROGRESS_KEY, instance)).await?
+    }
+
+    pub async fn set_update_progress(connection, instance: &str, field: &str, value: &str) -> () {
+        connection.hset(instance_key(UPDATE_PROGRESS_KEY, instance), field, value).await?
+    }
+
+    // advances the last update timestamp and discards the progress of the update
+    pub async fn finish_update(connection, instance: &str, timestamp: DateTime<Utc>) -> () {
+        redis::pipe()
+            .atomic()
+            .add_command(Cmd::set(instance_key(LAST_UPDATE_KEY, instance), timestamp.timestamp_millis()))
+            .ignore()
+            .add_command(Cmd::del(instance_key(UPDATE_PROGRESS_KEY, instance)))
+            .ignore()
+            .query_async(connection)
+            .await?
+    }
+
+    pub async fn set_update_status(connection, instance: &str, status: &UpdateStatus) -> () {
+        connection.hset(UPDATE_STATUS_KEY, instance, serde_json::to_string(status)?).await?
+    }
+
+    /// Returns the outcome of the last update of each instance, keyed by its name
+    pub async fn get_update_status(connection) -> HashMap<String, UpdateStatus> {
+        let entries: HashMap<String, String> = connection.hgetall(UPDATE_STATUS_KEY).await?;
+
+        let mut status = HashMap::new();
+        for (instance, json) in entries {
+            status.insert(instance, serde_json::from_str(&json)?);
+        }
+        status
+    }
+
+    pub async fn get_stream_length(connection) -> u64 {
+        redis::cmd("XLEN")
+            .arg(SCHEDULED_MESSAGES_KEY)
+            .query_async(connection)
+            .await?
+    }
+
+    pub async fn get_chat_overviews(connection) -> Vec<ChatOverview> {
+        let members: Vec<String> = connection.smembers(REGISTERED_CHATS_KEY).await?;
+        let recipients = parse_recipients(members);
+
+        // the chats are looked at in batches of short commands, so that counting the backlogs
+        // doesn't block Redis for everyone else
+        let mut overviews = vec![];
+        for batch in recipients.chunks(OVERVIEW_BATCH_SIZE) {
+            let mut pipe = redis::pipe();
+            for &recipient in batch {
+                pipe.hget(registered_chat_key(recipient), &["filter", "last_sent"]);
+            }
+            let chats: Vec<(Option<String>, Option<StreamId>)> =
+                pipe.query_async(connection).await?;
+
+            let mut pipe = redis::pipe();
+            for last_sent in chats.iter().filter_map(|(_, last_sent)| *last_sent) {
+                pipe.cmd("XRANGE")
+                    .arg(SCHEDULED_MESSAGES_KEY)
+                    .arg(last_sent.next())
+                    .arg("+")
+                    .arg("COUNT")
+                    .arg(BACKLOG_LIMIT);
+            }
+            let entries: Vec<Vec<redis::Value>> = pipe.query_async(connection).await?;
+            let mut entries = entries.into_iter();
+
+            for (&recipient, (filters, last_sent)) in batch.iter().zip(chats) {
+                // the rules are only counted, so they don't need to be parsed
+                let rules: Vec<serde::de::IgnoredAny> = match filters {
+                    Some(filters) => serde_json::from_str(&filters)?,
+                    None => vec![],
+                };
+                let backlog = match last_sent {
+                    Some(_) => entries.next().map_or(0, |entries| entries.len() as u64),
+                    None => 0,
+                };
+                overviews.push(ChatOverview { recipient, rules: rules.len(), backlog });
+            }
+        }
+        overviews
+    }
+
+    pub async fn get_cached_object(connection, namespace: &str, id: &str) -> Option<String> {
+        connection.hget(object_cache_key(namespace), id).await?
+    }
+
+    pub async fn cache_object(connection, namespace: &str, id: &str, value: &str) -> () {
+        connection.hset(object_cache_key(namespace), id, value).await?
+    }
+
+    pub async fn remove_cached_object(connection, namespace: &str, id: &str) -> () {
+        connection.hdel(object_cache_key(namespace), id).await?
+    }
+
     pub async fn get_chat_state(
         connection,
-        chat_id: i64,
+        recipient: Recipient,
     ) -> ChatState {
-        let (last_sent, migrated) = connection.hget(registered_chat_key(chat_id), &["last_sent", "migrated"]).await?;
+        let (last_sent, migrated, filter_version) = connection.hget(registered_chat_key(recipient), &["last_sent", "migrated", "filter_version"]).await?;
 
         if let Some(last_sent) = last_sent {
-            ChatState::Active {  last_sent }
+            ChatState::Active { last_sent, filter_version }
         } else if let Some(to) = migrated {
             ChatState::Migrated { to }
         } else {
@@ -576,23 +1184,113 @@ implement_with_retry! {
         }
     }
 
-    pub async fn update_dialogue(connection, chat_id: i64, dialogue: &impl Serialize) -> () {
+    pub async fn update_dialogue(connection, recipient: Recipient, dialogue: &impl Serialize) -> () {
         let string = serde_json::to_string(dialogue)?;
-        connection.set_ex(dialogue_key(chat_id), &string, 60 * 60 * 24).await?
+        connection.set_ex(dialogue_key(recipient), &string, 60 * 60 * 24).await?
+    }
+
+    pub async fn remove_dialogue(connection, recipient: Recipient) -> () {
+        connection.del(dialogue_key(recipient)).await?
+    }
+
+    pub async fn get_locale(connection, chat_id: i64) -> Option<Locale> {
+        let code: Option<String> = connection.hget(CHAT_LOCALES_KEY, chat_id).await?;
+        code.as_deref().and_then(Locale::from_code)
+    }
+
+    /// Sets the locale of the chat, or removes it to follow the users' language again
+    pub async fn set_locale(connection, chat_id: i64, locale: Option<Locale>) -> () {
+        match locale {
+            Some(locale) => connection.hset(CHAT_LOCALES_KEY, chat_id, locale.code()).await?,
+            None => connection.hdel(CHAT_LOCALES_KEY, chat_id).await?,
+        }
+    }
+
+    /// Whether all members of the group may manage its rules, not only the administrators
+    pub async fn get_member_managed(connection, chat_id: i64) -> bool {
+        connection.sismember(MEMBER_MANAGED_CHATS_KEY, chat_id).await?
+    }
+
+    pub async fn set_member_managed(connection, chat_id: i64, member_managed: bool) -> () {
+        if member_managed {
+            connection.sadd(MEMBER_MANAGED_CHATS_KEY, chat_id).await?
+        } else {
+            connection.srem(MEMBER_MANAGED_CHATS_KEY, chat_id).await?
+        }
+    }
+
+    /// Remembers a channel shared by the user, or updates its title and username
+    pub async fn add_channel(connection, user_id: i64, channel_id: i64, channel: &impl Serialize) -> () {
+        let string = serde_json::to_string(channel)?;
+        redis::pipe()
+            .atomic()
+            .add_command(Cmd::hset(channels_key(user_id), channel_id, &string))
+            .ignore()
+            .add_command(Cmd::sadd(channel_users_key(channel_id), user_id))
+            .ignore()
+            .query_async(connection)
+            .await?
     }
 
-    pub async fn remove_dialogue(connection, chat_id: i64) -> () {
-        connection.del(dialogue_key(chat_id)).await?
+    pub async fn get_channels<C: DeserializeOwned>(connection, user_id: i64) -> Vec<C> {
+        let entries: Vec<(i64, String)> = connection.hgetall(channels_key(user_id)).await?;
+
+        let mut channels = vec![];
+        for (_, string) in entries {
+            channels.push(serde_json::from_str(&string)?);
+        }
+        channels
+    }
+
+    pub async fn remove_channel(connection, user_id: i64, channel_id: i64) -> () {
+        redis::pipe()
+            .atomic()
+            .add_command(Cmd::hdel(channels_key(user_id), channel_id))
+            .ignore()
+            .add_command(Cmd::srem(channel_users_key(channel_id), user_id))
+            .ignore()
+            .query_async(connection)
+            .await?
+    }
+
+    /// Forgets all channels the user has shared
+    pub async fn remove_channels(connection, user_id: i64) -> () {
+        let channel_ids: Vec<i64> = connection.hkeys(channels_key(user_id)).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for channel_id in channel_ids {
+            pipe.srem(channel_users_key(channel_id), user_id).ignore();
+        }
+        pipe.del(channels_key(user_id))
+            .ignore()
+            .query_async(connection)
+            .await?
+    }
+
+    /// Removes the channel from the lists of all users who have shared it
+    pub async fn remove_shared_channel(connection, channel_id: i64) -> () {
+        let user_ids: Vec<i64> = connection.smembers(channel_users_key(channel_id)).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for user_id in user_ids {
+            pipe.hdel(channels_key(user_id), channel_id).ignore();
+        }
+        pipe.del(channel_users_key(channel_id))
+            .ignore()
+            .query_async(connection)
+            .await?
     }
 
-    pub async fn get_dialogue<D: DeserializeOwned>(connection, chat_id: i64) -> Option<D> {
-        let string : Option<String> = connection.get(dialogue_key(chat_id)).await?;
+    pub async fn get_dialogue<D: DeserializeOwned>(connection, recipient: Recipient) -> Option<D> {
+        let string : Option<String> = connection.get(dialogue_key(recipient)).await?;
         if let Some(string) = string {
             match serde_json::from_str(&string) {
                 Ok(deserialized) => Some(deserialized),
                 Err(e) => {
-                    log::warn!("Deleting malformed dialogue for chat {chat_id}");
-                    let _ : redis::RedisResult<()> = connection.del(dialogue_key(chat_id)).await;
+                    log::warn!("Deleting malformed dialogue for chat {recipient}");
+                    let _ : redis::RedisResult<()> = connection.del(dialogue_key(recipient)).await;
                     return Err(e.into());
                 }
             }
@@ -0,0 +1,276 @@
+//! Combines the notifications for chats that don't want them delivered instantly.
+//!
+//! The broadcaster adds such notifications to the chat's digest instead of sending
+//! them (see [`RedisBackend`](crate::broadcasting::RedisBackend)). Once a digest is
+//! due, it is scheduled as one or more messages addressed directly to the chat.
+
+use std::time::Duration;
+
+use bot_utils::Recipient;
+use chrono::{DateTime, Datelike, Days, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Europe::Berlin;
+use frankenstein::methods::SendMessageParams;
+use frankenstein::types::{LinkPreviewOptions, MessageEntity, ReplyMarkup};
+use serde::{Deserialize, Serialize};
+use telegram_message_builder::{MessageBuilder, WriteToMessage, bold, text_link};
+use tokio::time::{MissedTickBehavior, interval};
+
+use crate::database::{self, DatabaseConnection, StreamId};
+use crate::locale::Locale;
+use crate::types::{Audience, DeliveryMode, Message, MessageKind};
+
+/// Headlines are shortened to this number of characters
+const MAX_HEADLINE_CHARS: usize = 200;
+
+/// A notification as listed in a digest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DigestItem {
+    /// the id of the original notification, keeping the items unique
+    pub id: StreamId,
+    pub kind: MessageKind,
+    /// the first line of the notification. Notifications are written in German, as the
+    /// stream is shared by all chats, so only the digest's header is translated.
+    pub headline: String,
+    pub link: Option<String>,
+}
+
+impl DigestItem {
+    pub fn new(id: StreamId, message: &Message) -> Self {
+        // the first line of a notification serves as its headline
+        let first_line = message.request.text.lines().next().unwrap_or_default();
+        let mut headline: String = first_line.chars().take(MAX_HEADLINE_CHARS).collect();
+        if headline.len() < first_line.len() {
+            headline.push('…');
+        }
+
+        let link = match &message.request.reply_markup {
+            Some(ReplyMarkup::InlineKeyboardMarkup(keyboard)) => keyboard
+                .inline_keyboard
+                .iter()
+                .flatten()
+                .find_map(|button| button.url.clone()),
+            _ => None,
+        };
+
+        Self {
+            id,
+            kind: message.kind,
+            headline,
+            link,
+        }
+    }
+}
+
+impl WriteToMessage for DigestItem {
+    fn write_to(&self, msg: &mut MessageBuilder) -> Result<(), telegram_message_builder::Error> {
+        // the headlines of the other kinds already start with an emoji
+        if matches!(self.kind, MessageKind::Paper) {
+            msg.write("📄 ")?;
+        }
+
+        match &self.link {
+            Some(link) => msg.write(text_link(link, &self.headline)),
+            None => msg.write(&self.headline),
+        }
+    }
+}
+
+impl DeliveryMode {
+    /// The point in time a digest is due, if its first item was added at `now`
+    pub fn next_due(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let local = now.with_timezone(&Berlin);
+
+        let at_hour = |days: u64, hour: u32| {
+            let date = local.date_naive() + Days::new(days);
+            let time = NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or_default();
+            // on a DST change, the hour might be skipped
+            Berlin
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map_or(now, |t| t.with_timezone(&Utc))
+        };
+
+        let next_after = |hour: u32, days: u64| {
+            let due = at_hour(days, hour);
+            if due > now {
+                due
+            } else {
+                at_hour(days + 1, hour)
+            }
+        };
+
+        match self {
+            Self::Instant => now,
+            Self::Hourly => {
+                let hour = local.hour();
+                if hour == 23 {
+                    at_hour(1, 0)
+                } else {
+                    next_after(hour + 1, 0)
+                }
+            }
+            Self::Daily { hour } => next_after(hour, 0),
+            Self::Weekly { weekday, hour } => {
+                let days = (7 + weekday.num_days_from_monday()
+                    - local.weekday().num_days_from_monday())
+                    % 7;
+                let due = at_hour(days.into(), hour);
+                if due > now {
+                    due
+                } else {
+                    at_hour(u64::from(days) + 7, hour)
+                }
+            }
+        }
+    }
+}
+
+/// Formats the items as one or more messages, each within the character limit
+pub fn format_digest(items: &[DigestItem], locale: Locale) -> Vec<(String, Vec<MessageEntity>)> {
+    let mut messages = vec![];
+    let mut msg = MessageBuilder::new();
+
+    let header = match items.len() {
+        1 => tr!(locale, "digest.header.one").to_string(),
+        n => tr!(locale, "digest.header.many", count = n),
+    };
+    msg.writeln(bold(header))
+        .expect("header is shorter than the limit");
+
+    for item in items {
+        let backup = msg.clone();
+        if msg
+            .write("\n")
+            .and_then(|()| item.write_to(&mut msg))
+            .is_err()
+        {
+            // continue in a new message
+            messages.push(backup.build());
+            msg = MessageBuilder::new();
+            if item.write_to(&mut msg).is_err() {
+                log::warn!("Digest item is too long, skipping: {}", item.headline);
+            }
+        }
+    }
+
+    messages.push(msg.build());
+    messages
+}
+
+async fn send_digest(
+    db: &mut DatabaseConnection,
+    recipient: Recipient,
+    now: DateTime<Utc>,
+) -> database::Result<()> {
+    let stored = db.get_digest_items(recipient).await?;
+    let items: Vec<DigestItem> = stored
+        .iter()
+        .filter_map(|item| match serde_json::from_str(item) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                log::warn!("Invalid digest item: {e}");
+                None
+            }
+        })
+        .collect();
+
+    let messages: Vec<Message> = if items.is_empty() {
+        vec![]
+    } else {
+        let locale = db.get_locale(recipient.chat_id).await?.unwrap_or_default();
+        format_digest(&items, locale)
+            .into_iter()
+            .map(|(text, entities)| Message {
+                request: SendMessageParams::builder()
+                    .chat_id(0)
+                    .text(text)
+                    .entities(entities)
+                    .link_preview_options(LinkPreviewOptions::builder().is_disabled(true).build())
+                    .build(),
+                tags: vec![],
+                kind: MessageKind::Digest,
+                audience: Audience::Direct(recipient),
+                instance: None,
+                fulltext: None,
+            })
+            .collect()
+    };
+
+    let next_due = db.get_delivery_mode(recipient).await?.next_due(now);
+    db.schedule_digest(recipient, &messages, &stored, next_due)
+        .await
+}
+
+/// Regularly checks for due digests and schedules them
+pub async fn digest_task(db: redis::Client) {
+    let mut interval = interval(Duration::from_secs(60));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let mut db_conn = DatabaseConnection::new(db.clone(), Some(Duration::from_secs(10)));
+        let now = Utc::now();
+
+        let chats = match db_conn.get_due_digests(now).await {
+            Ok(chats) => chats,
+            Err(e) => {
+                log::error!("Unable to get due digests: {e}");
+                continue;
+            }
+        };
+
+        for recipient in chats {
+            if let Err(e) = send_digest(&mut db_conn, recipient, now).await {
+                log::error!("Unable to schedule digest for chat {recipient}: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Weekday};
+
+    use super::*;
+
+    #[test]
+    fn test_next_due() {
+        // Thursday, 16:30 in Germany (summer time)
+        let now = Utc.with_ymd_and_hms(2025, 7, 10, 14, 30, 0).unwrap();
+        let utc = |d, h| Utc.with_ymd_and_hms(2025, 7, d, h, 0, 0).unwrap();
+
+        assert_eq!(DeliveryMode::Instant.next_due(now), now);
+        assert_eq!(DeliveryMode::Hourly.next_due(now), utc(10, 15));
+        assert_eq!(DeliveryMode::Daily { hour: 7 }.next_due(now), utc(11, 5));
+        assert_eq!(DeliveryMode::Daily { hour: 18 }.next_due(now), utc(10, 16));
+
+        let weekly = |weekday| DeliveryMode::Weekly { weekday, hour: 8 };
+        assert_eq!(weekly(Weekday::Mon).next_due(now), utc(14, 6));
+        assert_eq!(weekly(Weekday::Thu).next_due(now), utc(17, 6));
+        assert_eq!(weekly(Weekday::Fri).next_due(now), utc(11, 6));
+    }
+
+    #[test]
+    fn test_format_digest() {
+        let item = |i| DigestItem {
+            id: format!("{i}-0").parse().unwrap(),
+            kind: MessageKind::Paper,
+            headline: "x".repeat(100),
+            link: Some("https://example.org".into()),
+        };
+
+        let messages = format_digest(&[item(1), item(2)], Locale::De);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].1.len(), 3);
+
+        let items: Vec<_> = (0..100).map(item).collect();
+        let messages = format_digest(&items, Locale::En);
+        assert_eq!(messages.len(), 3);
+        assert!(
+            messages
+                .iter()
+                .all(|(text, _)| text.chars().count() <= 4096)
+        );
+    }
+}
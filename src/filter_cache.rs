@@ -0,0 +1,471 @@
+//! Keeps the filters of the chats in memory, prepared for matching many messages: the
+//! patterns are compiled only once, and the patterns for the same tag are combined in a
+//! `RegexSet`, so each value of a message is scanned only once per chat.
+//!
+//! Entries are invalidated by the filter version stored alongside the filters, see
+//! [`ChatState::Active`](crate::database::ChatState::Active).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bot_utils::Recipient;
+use regex::{Regex, RegexSet};
+
+use crate::types::{DeliveryMode, Expr, Filter, Message, Tag};
+
+/// The patterns of all conditions on one tag
+struct PatternSet {
+    tag: Tag,
+    matcher: Matcher,
+}
+
+enum Matcher {
+    Set(RegexSet),
+    /// used if the combined set is too large to be compiled
+    Separate(Vec<Regex>),
+}
+
+impl PatternSet {
+    fn new(tag: Tag, patterns: Vec<String>) -> Self {
+        let matcher = match RegexSet::new(&patterns) {
+            Ok(set) => Matcher::Set(set),
+            Err(e) => {
+                log::debug!("Unable to combine patterns, compiling them separately: {e}");
+                let regexes = patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
+                Matcher::Separate(regexes)
+            }
+        };
+
+        Self { tag, matcher }
+    }
+
+    /// Which of the patterns match any of the message's values for the tag
+    fn matches(&self, message: &Message) -> Vec<bool> {
+        let values = message.tags.iter().filter(|(tag, _)| *tag == self.tag);
+
+        match &self.matcher {
+            Matcher::Set(set) => {
+                let mut matched = vec![false; set.len()];
+                for (_, value) in values {
+                    for i in set.matches(value).iter() {
+                        matched[i] = true;
+                    }
+                }
+                matched
+            }
+            Matcher::Separate(regexes) => {
+                let values: Vec<_> = values.collect();
+                regexes
+                    .iter()
+                    .map(|regex| values.iter().any(|(_, value)| regex.is_match(value)))
+                    .collect()
+            }
+        }
+    }
+}
+
+enum CompiledExpr {
+    Condition {
+        set: usize,
+        pattern: usize,
+        negate: bool,
+    },
+    /// a condition with an invalid pattern, which never matches
+    Invalid,
+    And(Vec<CompiledExpr>),
+    Or(Vec<CompiledExpr>),
+    Not(Box<CompiledExpr>),
+}
+
+impl CompiledExpr {
+    fn matches(&self, sets: &[PatternSet], matched: &mut MatchedPatterns) -> bool {
+        match self {
+            CompiledExpr::Condition {
+                set,
+                pattern,
+                negate,
+            } => matched.get(sets, *set, *pattern) ^ negate,
+            CompiledExpr::Invalid => false,
+            CompiledExpr::And(exprs) => exprs.iter().all(|expr| expr.matches(sets, matched)),
+            CompiledExpr::Or(exprs) => exprs.iter().any(|expr| expr.matches(sets, matched)),
+            CompiledExpr::Not(expr) => !expr.matches(sets, matched),
+        }
+    }
+}
+
+/// The results of the pattern sets for one message, evaluated as needed
+struct MatchedPatterns<'a> {
+    message: &'a Message,
+    results: Vec<Option<Vec<bool>>>,
+}
+
+impl MatchedPatterns<'_> {
+    fn get(&mut self, sets: &[PatternSet], set: usize, pattern: usize) -> bool {
+        self.results[set].get_or_insert_with(|| sets[set].matches(self.message))[pattern]
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    /// the patterns of each set, with the index of each pattern
+    patterns: Vec<(Tag, HashMap<String, usize>)>,
+}
+
+impl Builder {
+    fn compile(&mut self, expr: &Expr) -> CompiledExpr {
+        let compile_all = |this: &mut Self, exprs: &[Expr]| -> Vec<CompiledExpr> {
+            exprs.iter().map(|expr| this.compile(expr)).collect()
+        };
+
+        match expr {
+            Expr::Condition(condition) => {
+                let regex = condition.regex();
+                if Regex::new(&regex).is_err() {
+                    log::warn!("Invalid regex pattern!");
+                    return CompiledExpr::Invalid;
+                }
+
+                let set = match self.patterns.iter().position(|(t, _)| *t == condition.tag) {
+                    Some(set) => set,
+                    None => {
+                        self.patterns.push((condition.tag, HashMap::new()));
+                        self.patterns.len() - 1
+                    }
+                };
+
+                let patterns = &mut self.patterns[set].1;
+                let next = patterns.len();
+                let pattern = *patterns.entry(regex.into_owned()).or_insert(next);
+
+                CompiledExpr::Condition {
+                    set,
+                    pattern,
+                    negate: condition.negate,
+                }
+            }
+            Expr::And(exprs) => CompiledExpr::And(compile_all(self, exprs)),
+            Expr::Or(exprs) => CompiledExpr::Or(compile_all(self, exprs)),
+            Expr::Not(expr) => CompiledExpr::Not(Box::new(self.compile(expr))),
+        }
+    }
+
+    fn build_sets(self) -> Vec<PatternSet> {
+        self.patterns
+            .into_iter()
+            .map(|(tag, patterns)| {
+                let mut patterns: Vec<_> = patterns.into_iter().collect();
+                patterns.sort_by_key(|(_, i)| *i);
+                let patterns = patterns.into_iter().map(|(p, _)| p).collect();
+                PatternSet::new(tag, patterns)
+            })
+            .collect()
+    }
+}
+
+struct CompiledFilter {
+    filter: Filter,
+    expr: CompiledExpr,
+    /// the positive full-text conditions, if the filter has snippets enabled
+    snippets: Vec<Regex>,
+}
+
+/// The filters of a chat, prepared for matching
+pub struct CompiledFilters {
+    filters: Vec<CompiledFilter>,
+    sets: Vec<PatternSet>,
+}
+
+impl CompiledFilters {
+    pub fn new(filters: Vec<Filter>) -> Self {
+        let mut builder = Builder::default();
+        let filters = filters
+            .into_iter()
+            .map(|filter| {
+                let expr = builder.compile(&filter.expression);
+                let snippets = if filter.snippets {
+                    filter
+                        .expression
+                        .positive_conditions()
+                        .into_iter()
+                        .filter(|condition| condition.tag == Tag::Volltext)
+                        .filter_map(|condition| Regex::new(&condition.regex()).ok())
+                        .collect()
+                } else {
+                    vec![]
+                };
+
+                CompiledFilter {
+                    filter,
+                    expr,
+                    snippets,
+                }
+            })
+            .collect();
+
+        Self {
+            filters,
+            sets: builder.build_sets(),
+        }
+    }
+
+    fn matching<'a>(&'a self, message: &'a Message) -> impl Iterator<Item = &'a CompiledFilter> {
+        let mut matched = MatchedPatterns {
+            message,
+            results: vec![None; self.sets.len()],
+        };
+
+        self.filters.iter().filter(move |compiled| {
+            compiled.filter.applies_to(message) && compiled.expr.matches(&self.sets, &mut matched)
+        })
+    }
+
+    /// Whether any of the filters matches the message
+    pub fn matches(&self, message: &Message) -> bool {
+        self.matching(message).next().is_some()
+    }
+
+    /// The patterns for snippets of the full text, taken from the filters that match the
+    /// message and have snippets enabled
+    pub fn snippet_patterns<'a>(&'a self, message: &'a Message) -> impl Iterator<Item = &'a Regex> {
+        self.matching(message)
+            .flat_map(|compiled| &compiled.snippets)
+    }
+}
+
+struct Entry {
+    version: Option<u64>,
+    filters: Arc<CompiledFilters>,
+    delivery: DeliveryMode,
+}
+
+/// The compiled filters and the delivery mode of all chats, along with their version
+#[derive(Default)]
+pub struct FilterCache {
+    entries: Mutex<HashMap<Recipient, Entry>>,
+}
+
+impl FilterCache {
+    /// Returns the chat's filters and delivery mode, if the cached ones have the given version
+    pub fn get(
+        &self,
+        recipient: Recipient,
+        version: Option<u64>,
+    ) -> Option<(Arc<CompiledFilters>, DeliveryMode)> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(&recipient)
+            .filter(|entry| entry.version == version)
+            .map(|entry| (entry.filters.clone(), entry.delivery))
+    }
+
+    /// Returns the chat's filters as they were matched most recently, regardless of
+    /// their version
+    pub fn latest(&self, recipient: Recipient) -> Option<Arc<CompiledFilters>> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(&recipient).map(|entry| entry.filters.clone())
+    }
+
+    pub fn insert(
+        &self,
+        recipient: Recipient,
+        version: Option<u64>,
+        filters: Vec<Filter>,
+        delivery: DeliveryMode,
+    ) -> (Arc<CompiledFilters>, DeliveryMode) {
+        let filters = Arc::new(CompiledFilters::new(filters));
+        let mut entries = self.entries.lock().unwrap();
+        let entry = Entry {
+            version,
+            filters: filters.clone(),
+            delivery,
+        };
+        entries.insert(recipient, entry);
+        (filters, delivery)
+    }
+
+    pub fn remove(&self, recipient: Recipient) {
+        self.entries.lock().unwrap().remove(&recipient);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use frankenstein::methods::SendMessageParams;
+    use regex::Regex;
+
+    use super::CompiledFilters;
+    use crate::filter_syntax::parse;
+    use crate::types::{Condition, Expr, Filter, Message, MessageKind, Scope, Tag};
+
+    // The stored filters used to be matched directly, compiling every pattern anew for each
+    // message. This serves as the reference for the compiled filters.
+    fn condition_matches(condition: &Condition, message: &Message) -> bool {
+        let Ok(regex) = Regex::new(&condition.regex()) else {
+            return false;
+        };
+
+        let result = message
+            .tags
+            .iter()
+            .filter(|x| x.0 == condition.tag)
+            .any(|x| regex.is_match(&x.1));
+
+        result ^ condition.negate
+    }
+
+    fn expr_matches(expr: &Expr, message: &Message) -> bool {
+        match expr {
+            Expr::Condition(condition) => condition_matches(condition, message),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr_matches(expr, message)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr_matches(expr, message)),
+            Expr::Not(expr) => !expr_matches(expr, message),
+        }
+    }
+
+    fn filter_matches(filter: &Filter, message: &Message) -> bool {
+        filter.applies_to(message) && expr_matches(&filter.expression, message)
+    }
+
+    fn filter(expression: &str) -> Filter {
+        Filter {
+            expression: parse(expression).unwrap(),
+            scope: Scope::Papers,
+            updates: false,
+            instances: vec![],
+            snippets: false,
+        }
+    }
+
+    fn message(tags: &[(Tag, &str)]) -> Message {
+        Message {
+            request: SendMessageParams::builder().chat_id(0).text("").build(),
+            tags: tags.iter().map(|(t, v)| (*t, v.to_string())).collect(),
+            kind: MessageKind::Paper,
+            audience: Default::default(),
+            instance: None,
+            fulltext: None,
+        }
+    }
+
+    fn sample_messages() -> Vec<Message> {
+        vec![
+            message(&[(Tag::Gremium, "Rat"), (Tag::Art, "Antrag")]),
+            message(&[
+                (Tag::Gremium, "Bezirksvertretung Beuel"),
+                (Tag::Art, "Anfrage"),
+            ]),
+            message(&[
+                (Tag::Gremium, "Schulausschuss"),
+                (Tag::Gremium, "Rat"),
+                (Tag::Art, "Beschlussvorlage"),
+                (Tag::Title, "Sanierung der Fahrradstraße"),
+            ]),
+            message(&[(Tag::Title, "Baumfällung"), (Tag::Dsnr, "252807")]),
+        ]
+    }
+
+    fn sample_filters() -> Vec<Vec<Filter>> {
+        [
+            vec!["gremium:Rat & (art:Antrag | art:Anfrage)"],
+            vec!["!gremium:Rat", "titel:Fahrrad"],
+            vec![r#"gremium:"^Rat$" & !(art:Antrag | titel:Baum)"#],
+            vec![r#"titel:"(""#, r#"dsnr:"^25""#],
+            vec!["art:.", "art:\".\" & gremium:Beuel"],
+        ]
+        .into_iter()
+        .map(|filters| filters.into_iter().map(filter).collect())
+        .collect()
+    }
+
+    #[test]
+    fn test_compiled_filters() {
+        for filters in sample_filters() {
+            let compiled = CompiledFilters::new(filters.clone());
+
+            for message in sample_messages() {
+                let expected = filters
+                    .iter()
+                    .any(|filter| filter_matches(filter, &message));
+                assert_eq!(compiled.matches(&message), expected, "{filters:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_snippet_patterns() {
+        let mut with_snippets = filter("volltext:Baum & !volltext:Fahrrad | titel:Baum");
+        with_snippets.snippets = true;
+        let compiled = CompiledFilters::new(vec![with_snippets, filter("volltext:Straße")]);
+
+        let patterns = |message: &Message| -> Vec<String> {
+            compiled
+                .snippet_patterns(message)
+                .map(|regex| regex.as_str().to_string())
+                .collect()
+        };
+
+        // only the positive full-text conditions of matching filters with snippets enabled
+        let matching = message(&[(Tag::Volltext, "Baumfällung in der Straße")]);
+        assert_eq!(patterns(&matching).len(), 1);
+        assert!(patterns(&matching)[0].contains("Baum"));
+
+        let other = message(&[(Tag::Volltext, "Straße")]);
+        assert!(patterns(&other).is_empty());
+    }
+
+    /// Compares the throughput with matching the stored filters directly. Run with
+    /// `cargo test --release bench_filters -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_filters() {
+        const CHATS: usize = 2000;
+
+        let chats: Vec<Vec<Filter>> = (0..CHATS)
+            .map(|i| {
+                let filters = &sample_filters()[i % 5];
+                let mut filters = filters.clone();
+                filters.push(filter(&format!(
+                    "titel:\"Bebauungsplan {i}\" | volltext:Baum{i}"
+                )));
+                filters
+            })
+            .collect();
+        let messages: Vec<_> = sample_messages().into_iter().cycle().take(100).collect();
+
+        let start = Instant::now();
+        let mut uncompiled = 0;
+        for filters in &chats {
+            // like before, every message is matched against freshly deserialized filters
+            for message in &messages {
+                let json = serde_json::to_string(filters).unwrap();
+                let filters: Vec<Filter> = serde_json::from_str(&json).unwrap();
+                uncompiled += filters.iter().any(|f| filter_matches(f, message)) as usize;
+            }
+        }
+        let uncompiled_time = start.elapsed();
+
+        let start = Instant::now();
+        let compiled_chats: Vec<_> = chats.into_iter().map(CompiledFilters::new).collect();
+        let mut compiled = 0;
+        for filters in &compiled_chats {
+            for message in &messages {
+                compiled += filters.matches(message) as usize;
+            }
+        }
+        let compiled_time = start.elapsed();
+
+        assert_eq!(uncompiled, compiled);
+
+        let rate = |time: std::time::Duration| (CHATS * messages.len()) as f64 / time.as_secs_f64();
+        println!(
+            "uncompiled: {uncompiled_time:?} ({:.0} matches/s)",
+            rate(uncompiled_time)
+        );
+        println!(
+            "compiled (incl. compilation): {compiled_time:?} ({:.0} matches/s)",
+            rate(compiled_time)
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! A compact textual syntax for filter expressions, e.g.
+//! `gremium:Rat & (art:Antrag | art:Anfrage) & !titel:"Bad Godesberg"`.
+//!
+//! `!` binds strongest, followed by `&` and `|`. Values have to be quoted if they contain
+//! anything but letters, digits and `-_.`. Within quotes, `\"` and `\\` stand for a quote
+//! and a backslash. The match mode is given right after the colon:
+//!
+//! - `titel:Schule` – contains the text
+//! - `titel:~Schule` – contains the text as a whole word
+//! - `titel:=Schule` – is equal to the text
+//! - `titel:^Schule` – starts with the text
+//! - `titel:/Schul(e|weg)/` – matches the regex, with `\/` standing for a slash
+
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::locale::Locale;
+use crate::types::{Condition, Expr, MatchMode, Tag};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// the given character was expected
+    Expected(char),
+    TagExpected,
+    UnknownTag(String),
+    ValueExpected,
+    RegexExpected,
+    /// an operator or the end of the expression was expected
+    EndExpected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// the position in characters at which the error was found
+    pub position: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub fn describe(&self, locale: Locale) -> String {
+        let error = match &self.kind {
+            ParseErrorKind::Expected(c) => tr!(locale, "parse.expected", token = c),
+            ParseErrorKind::TagExpected => tr!(locale, "parse.tag_expected").to_string(),
+            ParseErrorKind::UnknownTag(tag) => {
+                let tags: Vec<_> = Tag::TAGS.iter().map(Tag::key).collect();
+                tr!(
+                    locale,
+                    "parse.unknown_tag",
+                    tag = tag,
+                    tags = tags.join(", ")
+                )
+            }
+            ParseErrorKind::ValueExpected => tr!(locale, "parse.value_expected").to_string(),
+            ParseErrorKind::RegexExpected => tr!(locale, "parse.regex_expected").to_string(),
+            ParseErrorKind::EndExpected => tr!(locale, "parse.end_expected").to_string(),
+        };
+
+        tr!(
+            locale,
+            "parse.position",
+            error = error,
+            position = self.position + 1
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.describe(Locale::default()))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn is_bare(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+fn write_value(f: &mut Formatter<'_>, value: &str) -> std::fmt::Result {
+    if !value.is_empty() && value.chars().all(is_bare) {
+        return f.write_str(value);
+    }
+
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    f.write_str("\"")
+}
+
+fn write_condition(f: &mut Formatter<'_>, condition: &Condition) -> std::fmt::Result {
+    if condition.negate {
+        f.write_str("!")?;
+    }
+    write!(f, "{}:", condition.tag.key())?;
+
+    let marker = match condition.mode {
+        MatchMode::Contains => "",
+        MatchMode::Word => "~",
+        MatchMode::Exact => "=",
+        MatchMode::Prefix => "^",
+        MatchMode::Regex => return write_regex(f, &condition.pattern),
+    };
+    f.write_str(marker)?;
+    write_value(f, &condition.pattern)
+}
+
+fn write_regex(f: &mut Formatter<'_>, regex: &str) -> std::fmt::Result {
+    f.write_str("/")?;
+    let mut chars = regex.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // escape sequences are kept as they are
+            '\\' => {
+                write!(f, "{c}")?;
+                if let Some(c) = chars.next() {
+                    write!(f, "{c}")?;
+                }
+            }
+            '/' => f.write_str("\\/")?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("/")
+}
+
+impl Expr {
+    /// Writes the expression, with parentheses if it binds weaker than its context
+    fn write(&self, f: &mut Formatter<'_>, parent_precedence: u8) -> std::fmt::Result {
+        let (exprs, separator, precedence) = match self {
+            Expr::Condition(condition) => return write_condition(f, condition),
+            Expr::Not(expr) => {
+                f.write_str("!")?;
+                return expr.write(f, 3);
+            }
+            Expr::And(exprs) => (exprs, " & ", 2),
+            Expr::Or(exprs) => (exprs, " | ", 1),
+        };
+
+        let parens = precedence < parent_precedence || exprs.is_empty();
+        if parens {
+            f.write_str("(")?;
+        }
+        for (i, expr) in exprs.iter().enumerate() {
+            if i != 0 {
+                f.write_str(separator)?;
+            }
+            expr.write(f, precedence)?;
+        }
+        if parens {
+            f.write_str(")")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn error(&mut self, kind: ParseErrorKind) -> ParseError {
+        let offset = self.chars.peek().map_or(self.input.len(), |(i, _)| *i);
+        ParseError {
+            position: self.input[..offset].chars().count(),
+            kind,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    /// Consumes the character if it comes next, ignoring whitespace
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        self.chars.next_if(|(_, c)| *c == expected).is_some()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat('|') {
+            exprs.push(self.parse_and()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut exprs = vec![self.parse_unary()?];
+        while self.eat('&') {
+            exprs.push(self.parse_unary()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat('!') {
+            return Ok(match self.parse_unary()? {
+                Expr::Condition(condition) => Expr::Condition(Condition {
+                    negate: !condition.negate,
+                    ..condition
+                }),
+                expr => Expr::Not(Box::new(expr)),
+            });
+        }
+
+        if self.eat('(') {
+            let expr = self.parse_or()?;
+            if !self.eat(')') {
+                return Err(self.error(ParseErrorKind::Expected(')')));
+            }
+            return Ok(expr);
+        }
+
+        self.parse_condition().map(Expr::Condition)
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, ParseError> {
+        self.skip_whitespace();
+
+        let mut key = String::new();
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_alphabetic()) {
+            key.extend(c.to_lowercase());
+        }
+
+        if key.is_empty() {
+            return Err(self.error(ParseErrorKind::TagExpected));
+        }
+
+        let Some(tag) = Tag::from_key(&key) else {
+            return Err(self.error(ParseErrorKind::UnknownTag(key)));
+        };
+
+        if self.chars.next_if(|(_, c)| *c == ':').is_none() {
+            return Err(self.error(ParseErrorKind::Expected(':')));
+        }
+
+        let mode = match self
+            .chars
+            .next_if(|(_, c)| matches!(c, '~' | '=' | '^' | '/'))
+        {
+            Some((_, '~')) => MatchMode::Word,
+            Some((_, '=')) => MatchMode::Exact,
+            Some((_, '^')) => MatchMode::Prefix,
+            Some(_) => MatchMode::Regex,
+            None => MatchMode::Contains,
+        };
+
+        let pattern = if mode == MatchMode::Regex {
+            self.parse_regex()?
+        } else {
+            self.parse_value()?
+        };
+
+        Ok(Condition {
+            tag,
+            pattern,
+            negate: false,
+            mode,
+        })
+    }
+
+    /// Parses a regex up to the closing slash
+    fn parse_regex(&mut self) -> Result<String, ParseError> {
+        let mut regex = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some((_, '/')) if !regex.is_empty() => return Ok(regex),
+                Some((_, '/')) => return Err(self.error(ParseErrorKind::RegexExpected)),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, '/')) => regex.push('/'),
+                    Some((_, c)) => {
+                        regex.push('\\');
+                        regex.push(c);
+                    }
+                    None => break,
+                },
+                Some((_, c)) => regex.push(c),
+                None => break,
+            }
+        }
+
+        Err(self.error(ParseErrorKind::Expected('/')))
+    }
+
+    fn parse_value(&mut self) -> Result<String, ParseError> {
+        let mut value = String::new();
+
+        if self.chars.next_if(|(_, c)| *c == '"').is_none() {
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| is_bare(*c)) {
+                value.push(c);
+            }
+
+            if value.is_empty() {
+                return Err(self.error(ParseErrorKind::ValueExpected));
+            }
+            return Ok(value);
+        }
+
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(value),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, c)) => value.push(c),
+                    None => break,
+                },
+                Some((_, c)) => value.push(c),
+                None => break,
+            }
+        }
+
+        Err(self.error(ParseErrorKind::Expected('"')))
+    }
+}
+
+/// Parses an expression in the textual syntax
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        input,
+        chars: input.char_indices().peekable(),
+    };
+
+    let expr = parser.parse_or()?;
+
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() {
+        return Err(parser.error(ParseErrorKind::EndExpected));
+    }
+
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::types::{Condition, Expr, Filter, MatchMode, Tag};
+
+    fn condition_of(tag: Tag, pattern: &str) -> Condition {
+        Condition {
+            tag,
+            pattern: pattern.to_string(),
+            negate: false,
+            mode: MatchMode::Contains,
+        }
+    }
+
+    fn condition(tag: Tag, pattern: &str, negate: bool) -> Expr {
+        Expr::Condition(Condition {
+            tag,
+            pattern: pattern.to_string(),
+            negate,
+            mode: MatchMode::Contains,
+        })
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr = parse(r#"gremium:"Rat" & (art:Antrag | ART:Anfrage) & !titel:"a \"b\"|c""#);
+        let expected = Expr::And(vec![
+            condition(Tag::Gremium, "Rat", false),
+            Expr::Or(vec![
+                condition(Tag::Art, "Antrag", false),
+                condition(Tag::Art, "Anfrage", false),
+            ]),
+            condition(Tag::Title, "a \"b\"|c", true),
+        ]);
+        assert_eq!(expr, Ok(expected));
+
+        let expr = parse("!(dsnr:1 | volltext:Baum) | verfasser:SPD & beteiligt:61").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Or(vec![
+                Expr::Not(Box::new(Expr::Or(vec![
+                    condition(Tag::Dsnr, "1", false),
+                    condition(Tag::Volltext, "Baum", false),
+                ]))),
+                Expr::And(vec![
+                    condition(Tag::Verfasser, "SPD", false),
+                    condition(Tag::Beteiligt, "61", false),
+                ]),
+            ])
+        );
+
+        let modes =
+            parse(r"titel:~Schule & titel:=Rat & titel:^Schul & titel:/^a\/b\d+$/").unwrap();
+        let Expr::And(conditions) = modes else {
+            panic!("expected a conjunction");
+        };
+        let modes: Vec<_> = conditions
+            .iter()
+            .map(|expr| match expr {
+                Expr::Condition(c) => (c.mode, c.pattern.as_str()),
+                _ => panic!("expected a condition"),
+            })
+            .collect();
+        assert_eq!(
+            modes,
+            [
+                (MatchMode::Word, "Schule"),
+                (MatchMode::Exact, "Rat"),
+                (MatchMode::Prefix, "Schul"),
+                (MatchMode::Regex, r"^a/b\d+$"),
+            ]
+        );
+
+        assert_eq!(parse("titel:/a").unwrap_err().position, 8);
+        assert_eq!(parse("farbe:rot").unwrap_err().position, 5);
+        assert_eq!(parse("art:Antrag &").unwrap_err().position, 12);
+        assert_eq!(parse("(art:Antrag").unwrap_err().position, 11);
+        assert_eq!(parse("art:\"Antrag").unwrap_err().position, 11);
+        assert_eq!(parse("art:Antrag art:Anfrage").unwrap_err().position, 11);
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn test_print() {
+        let inputs = [
+            r#"gremium:Rat & (art:Antrag | art:Anfrage) & !titel:"a \"b\"|c""#,
+            "!(dsnr:1 | volltext:Baum) | verfasser:SPD & beteiligt:61",
+            "!(gremium:Rat & !art:Antrag)",
+            r#"titel:~Schule | !gremium:="Rat der Stadt" | dsnr:^25 | titel:/a\/(b|c)$/"#,
+        ];
+
+        for input in inputs {
+            assert_eq!(parse(input).unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_edit_conditions() {
+        let mut expr = parse("gremium:Rat & (art:Antrag | !(art:Anfrage)) & titel:Schule").unwrap();
+
+        expr.condition_mut(2).unwrap().negate = false;
+        assert_eq!(
+            expr.remove_condition(1).unwrap().pattern,
+            "Antrag".to_string()
+        );
+        assert_eq!(expr.to_string(), "gremium:Rat & art:Anfrage & titel:Schule");
+
+        expr.remove_condition(1);
+        assert_eq!(expr.to_string(), "gremium:Rat & titel:Schule");
+        assert!(expr.remove_condition(2).is_none());
+
+        let mut expr = parse("!(art:Antrag | art:Anfrage)").unwrap();
+        expr.remove_condition(0);
+        expr.remove_condition(0);
+        assert!(expr.is_empty());
+
+        expr.push_condition(condition_of(Tag::Art, "Antrag"));
+        expr.push_condition(condition_of(Tag::Gremium, "Rat"));
+        assert_eq!(expr.to_string(), "art:Antrag & gremium:Rat");
+
+        let mut expr = parse("art:Antrag | art:Anfrage").unwrap();
+        expr.push_condition(condition_of(Tag::Gremium, "Rat"));
+        assert_eq!(expr.to_string(), "(art:Antrag | art:Anfrage) & gremium:Rat");
+    }
+
+    #[test]
+    fn test_legacy_filter() {
+        let stored =
+            r#"{"conditions":[{"tag":"Art","pattern":"Antrag","negate":true}],"updates":true}"#;
+        let filter: Filter = serde_json::from_str(stored).unwrap();
+        let expected = Condition {
+            tag: Tag::Art,
+            pattern: "Antrag".to_string(),
+            negate: true,
+            mode: MatchMode::Regex,
+        };
+        assert_eq!(
+            filter.expression,
+            Expr::And(vec![Expr::Condition(expected)])
+        );
+        assert!(filter.updates);
+
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(serde_json::from_str::<Filter>(&json).unwrap(), filter);
+    }
+}
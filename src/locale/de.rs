@@ -0,0 +1,730 @@
+pub const TEXTS: &[(&str, &str)] = &[
+    ("language.name", "Deutsch"),
+    ("list.and", " und "),
+    // commands, keyed by their name
+    (
+        "command.neue_regel",
+        "Erstelle eine neue Benachrichtigungsregel",
+    ),
+    ("command.regeln", "Zeige alle bestehenden Regeln an"),
+    (
+        "command.regel_bearbeiten",
+        "Bearbeite die Bedingungen einer bestehenden Regel",
+    ),
+    ("command.regel_loeschen", "Lösche eine bestehende Regel"),
+    ("command.alle_regeln_loeschen", "Entferne alle Regeln"),
+    (
+        "command.regel_testen",
+        "Prüfe, welche der letzten Vorlagen eine Regel erfasst hätte",
+    ),
+    ("command.suche", "Durchsuche die letzten Benachrichtigungen"),
+    (
+        "command.werte",
+        "Zeige die bekannten Werte eines Merkmals an",
+    ),
+    (
+        "command.ziel",
+        "Lege fest, für welchen Chat du Benachrichtigungen konfigurieren möchtest",
+    ),
+    (
+        "command.zustellung",
+        "Lege fest, ob Benachrichtigungen sofort oder gesammelt zugestellt werden",
+    ),
+    ("command.abbrechen", "Brich den aktuellen Vorgang ab"),
+    ("command.hilfe", "Zeige die Hilfenachricht an"),
+    ("command.start", "Zeige die Hilfenachricht an"),
+    ("command.datenschutz", "Zeige die Datenschutzerklärung an"),
+    (
+        "command.sprache",
+        "Lege die Sprache des Bots für diesen Chat fest",
+    ),
+    (
+        "command.kanaele",
+        "Zeige deine Kanäle an und wechsle zwischen ihnen",
+    ),
+    (
+        "command.rechte",
+        "Lege fest, wer in dieser Gruppe die Regeln verwalten darf",
+    ),
+    (
+        "command.nachladen",
+        "Verschicke die Vorlagen eines vergangenen Zeitraums",
+    ),
+    ("command.statistik", "Zeige die Statistik des Bots an"),
+    (
+        "command.chats",
+        "Zeige die registrierten Chats und ihre Regeln an",
+    ),
+    (
+        "command.ankuendigung",
+        "Schicke eine Ankündigung an alle Chats",
+    ),
+    // bot description
+    (
+        "description.one",
+        "Dieser Bot benachrichtigt dich, wenn im Ratsinformationssystem {names} neue Vorlagen veröffentlicht werden.",
+    ),
+    (
+        "description.many",
+        "Dieser Bot benachrichtigt dich, wenn in den Ratsinformationssystemen {names} neue Vorlagen veröffentlicht werden.",
+    ),
+    (
+        "description.short",
+        "Benachrichtigungen über neue Vorlagen und Sitzungen in {city}",
+    ),
+    // help message
+    (
+        "help.intro.one",
+        "Dieser Bot benachrichtigt dich, wenn im Ratsinformationssystem ",
+    ),
+    (
+        "help.intro.many",
+        "Dieser Bot benachrichtigt dich, wenn in den Ratsinformationssystemen ",
+    ),
+    (
+        "help.intro.published",
+        " neue Vorlagen veröffentlicht werden – lege dazu ",
+    ),
+    ("help.intro.rules", "Regeln"),
+    (
+        "help.intro.end",
+        " fest, welche Vorlagen du erhalten willst.",
+    ),
+    ("help.rules.title", "🔧 Regeln verwalten"),
+    (
+        "help.rules.description",
+        "Du erhältst Benachrichtigungen für alle Vorlagen, auf die mindestens eine Regel zutrifft.",
+    ),
+    ("help.target.title", "📬 Ziel und Zustellung einstellen"),
+    (
+        "help.target.description",
+        "Der Bot kann Benachrichtigungen hier im Chat oder in einem deiner Kanäle senden, einzeln oder gesammelt als Zusammenfassung.",
+    ),
+    ("help.permissions.title", "🛡 Berechtigungen"),
+    (
+        "help.permissions.description",
+        "In Gruppen dürfen nur Administratoren die Regeln und Einstellungen ändern, sofern sie es nicht allen Mitgliedern erlauben.",
+    ),
+    ("help.misc.title", "🆘 Sonstiges"),
+    (
+        "help.misc.help",
+        "/{help} oder /{start} – Zeige diese Hilfe an",
+    ),
+    ("help.regex.title", "📚 Reguläre Ausdrücke (Regex)"),
+    (
+        "help.regex.text",
+        "Beim Erstellen einer Regel kannst du festlegen, dass ein bestimmtes Merkmal ein sogenanntes Regex-Pattern erfüllen muss. Gib dort einfach den Text ein, nach dem du filtern möchtest – das funktioniert in den meisten Fällen zuverlässig. Falls du komplexere Muster brauchst, helfen dir ",
+    ),
+    (
+        "help.regex.end",
+        " oder ChatGPT beim Ausprobieren und Erlernen von regulären Ausdrücken.",
+    ),
+    ("help.disclaimer.title", "⚖️ Hinweis"),
+    (
+        "help.disclaimer.text",
+        "Dieser Bot ist ein rein privates, nicht-kommerzielles Projekt zur automatischen Benachrichtigung über neue Vorlagen aus ALLRIS®-Systemen. Er steht weder in Verbindung zur Firma CC e-gov GmbH noch zu den Kommunen, die diese Systeme betreiben.",
+    ),
+    (
+        "help.disclaimer.warranty",
+        "Für Vollständigkeit, Richtigkeit oder Aktualität der bereitgestellten Informationen wird keine Gewähr übernommen.",
+    ),
+    ("help.about.title", "👨‍💻 Mehr Infos & Kontakt"),
+    (
+        "help.about.source",
+        "Der Quellcode dieses Bots ist öffentlich zugänglich: {url} (Version {version})",
+    ),
+    (
+        "help.about.contact",
+        "Fragen, Feedback oder Ideen? Schreib mir gern: {contact}",
+    ),
+    // tags
+    ("tag.titel", "Titel"),
+    ("tag.dsnr", "Drucksachen-Nummer"),
+    ("tag.art", "Art der Vorlage"),
+    ("tag.gremium", "Gremium"),
+    (
+        "tag.gremium.description",
+        "Gremien, die zur Beratung der Vorlage vorgesehen sind, bzw. das Gremium einer Sitzung",
+    ),
+    ("tag.verfasser", "Antrag- oder Fragesteller:in"),
+    (
+        "tag.verfasser.description",
+        "Personen oder Fraktionen, die den Antrag oder die Frage gestellt haben",
+    ),
+    ("tag.federführend", "Federführendes Amt"),
+    ("tag.beteiligt", "Beteiligtes Amt"),
+    (
+        "tag.beteiligt.description",
+        "jedes an der Vorlage beteiligte Amt; das schließt auch das federführende Amt mit ein",
+    ),
+    ("tag.volltext", "Volltext"),
+    (
+        "tag.volltext.description",
+        "der Text des Hauptdokuments (PDF) der Vorlage",
+    ),
+    // delivery modes
+    ("delivery.instant", "sofort"),
+    ("delivery.hourly", "stündlich"),
+    ("delivery.daily", "täglich um {hour} Uhr"),
+    ("delivery.weekly", "wöchentlich am {weekday} um {hour} Uhr"),
+    ("weekday.1", "Montag"),
+    ("weekday.2", "Dienstag"),
+    ("weekday.3", "Mittwoch"),
+    ("weekday.4", "Donnerstag"),
+    ("weekday.5", "Freitag"),
+    ("weekday.6", "Samstag"),
+    ("weekday.7", "Sonntag"),
+    // filters
+    ("scope.papers", "Vorlagen"),
+    ("scope.meetings", "Sitzungen"),
+    ("scope.all", "Vorlagen und Sitzungen"),
+    ("mode.contains", "enthält"),
+    (
+        "mode.contains.description",
+        "der Wert muss den Text enthalten",
+    ),
+    ("mode.word", "ganzes Wort"),
+    (
+        "mode.word.description",
+        "der Wert muss den Text als ganzes Wort enthalten",
+    ),
+    ("mode.exact", "exakt"),
+    (
+        "mode.exact.description",
+        "der Wert muss genau dem Text entsprechen",
+    ),
+    ("mode.prefix", "beginnt mit"),
+    (
+        "mode.prefix.description",
+        "der Wert muss mit dem Text beginnen",
+    ),
+    ("mode.regex", "Regex"),
+    (
+        "mode.regex.description",
+        "der Wert muss zu einem regulären Ausdruck (Regex) passen",
+    ),
+    ("condition.contains", "{label} enthält \"{pattern}\""),
+    (
+        "condition.contains.not",
+        "{label} enthält nicht \"{pattern}\"",
+    ),
+    ("condition.word", "{label} enthält das Wort \"{pattern}\""),
+    (
+        "condition.word.not",
+        "{label} enthält nicht das Wort \"{pattern}\"",
+    ),
+    ("condition.exact", "{label} ist \"{pattern}\""),
+    ("condition.exact.not", "{label} ist nicht \"{pattern}\""),
+    ("condition.prefix", "{label} beginnt mit \"{pattern}\""),
+    (
+        "condition.prefix.not",
+        "{label} beginnt nicht mit \"{pattern}\"",
+    ),
+    ("condition.regex", "{label} passt zum Regex \"{pattern}\""),
+    (
+        "condition.regex.not",
+        "{label} passt nicht zum Regex \"{pattern}\"",
+    ),
+    ("filter.all", "Alle {scope}"),
+    ("filter.scope", "• Gilt für {scope}"),
+    ("filter.instances", "• Nur aus {instances}"),
+    ("filter.updates", "• inkl. Aktualisierungen"),
+    ("filter.snippets", "• inkl. Textauszügen"),
+    // chats
+    ("quoted", "„{text}“"),
+    ("chat.this", "💬 Dieser Chat"),
+    ("chat.this_accusative", "diesen Chat"),
+    ("channel.accusative", "den Kanal {channel}"),
+    ("channel.unknown", "<unbekannt>"),
+    // errors
+    (
+        "error.not_channel_admin",
+        "Du hast für diesen Channel nicht die notwendigen Rechte!",
+    ),
+    (
+        "error.not_group_admin",
+        "⛔ Das dürfen in dieser Gruppe nur Administratoren.",
+    ),
+    ("error.unknown_command", "Unbekannter Befehl!"),
+    ("error.internal", "Ein interner Fehler ist aufgetreten 😢"),
+    (
+        "error.outdated_selection",
+        "Diese Auswahl ist nicht mehr aktuell.",
+    ),
+    // /abbrechen
+    ("cancel.done", "Befehl wurde abgebrochen!"),
+    ("cancel.inactive", "Es war kein Befehl aktiv"),
+    // /sprache
+    ("language.current", "Aktuelle Einstellung: {language}"),
+    (
+        "language.question",
+        "Welche Sprache soll der Bot in diesem Chat verwenden?",
+    ),
+    ("language.automatic", "🌐 Automatisch"),
+    (
+        "language.set",
+        "✅ Der Bot verwendet in diesem Chat ab jetzt Deutsch.",
+    ),
+    (
+        "language.automatic_set",
+        "✅ Der Bot richtet sich in diesem Chat ab jetzt nach der Sprache der Telegram-App.",
+    ),
+    (
+        "language.unknown",
+        "❌ Unbekannte Sprache „{code}“, möglich sind: {codes}",
+    ),
+    (
+        "dialogue.use_buttons",
+        "Bitte verwende die Schaltflächen oder sende /{cancel} zum Abbrechen.",
+    ),
+    // dialogues
+    ("dialogue.cancel", "Abbrechen"),
+    ("dialogue.cancelled", "Der Vorgang wurde abgebrochen!"),
+    (
+        "dialogue.select_rule",
+        "Bitte nutze die Schaltflächen, um eine Regel auszuwählen, oder sende /{cancel} zum Abbrechen.",
+    ),
+    (
+        "rules.none",
+        "Zur Zeit sind keine Regeln für {target} aktiv!",
+    ),
+    (
+        "rules.none_create",
+        "Zur Zeit sind keine Regeln für {target} aktiv! Lege zuerst mit /{new_rule} eine Regel an.",
+    ),
+    // /zustellung
+    ("delivery.button.instant", "⚡ Sofort"),
+    ("delivery.button.hourly", "🕐 Stündlich"),
+    ("delivery.button.daily", "📅 Täglich"),
+    ("delivery.button.weekly", "🗓️ Wöchentlich"),
+    ("delivery.button.hour", "{hour} Uhr"),
+    (
+        "delivery.question.mode",
+        "Wie sollen die Benachrichtigungen zugestellt werden?",
+    ),
+    (
+        "delivery.question.weekday",
+        "An welchem Tag soll die Zusammenfassung kommen?",
+    ),
+    (
+        "delivery.question.hour",
+        "Um wie viel Uhr soll die Zusammenfassung kommen?",
+    ),
+    (
+        "delivery.current",
+        "Benachrichtigungen für {target} werden zur Zeit {mode} zugestellt.\n\nAlternativ können sie gesammelt werden und stündlich, täglich oder wöchentlich als Zusammenfassung kommen. {question}",
+    ),
+    (
+        "delivery.saved.instant",
+        "✅ Benachrichtigungen für {target} werden ab jetzt sofort zugestellt.",
+    ),
+    (
+        "delivery.saved.digest",
+        "✅ Benachrichtigungen für {target} werden ab jetzt {mode} als Zusammenfassung zugestellt.",
+    ),
+    // /alle_regeln_loeschen
+    ("remove_all.confirm", "⚠️ Ja, alles löschen!"),
+    ("remove_all.done", "✅ Deine Regeln wurden gelöscht!"),
+    (
+        "remove_all.failed",
+        "❌ Die Regeln konnten leider nicht gelöscht werden. Bitte versuche es erneut.",
+    ),
+    (
+        "remove_all.question",
+        "🗑️ Du bist dabei, alle Regeln für {target} zu entfernen.\n\nBist du sicher? Danach bekommst du erst mal keine Benachrichtigungen mehr.",
+    ),
+    // rules
+    ("rule.number", "Regel {number}"),
+    ("selection.current", "Aktuelle Auswahl: {chat}"),
+    ("rules.empty", "Es sind keine Regeln für {target} aktiv."),
+    (
+        "rules.list",
+        "Zur Zeit sind die folgenden Regeln für {target} aktiv:",
+    ),
+    (
+        "remove_rule.question",
+        "Wähle eine der folgenden Regeln zum Löschen aus:",
+    ),
+    ("remove_rule.done", "✅ Die Regel wurde gelöscht!"),
+    (
+        "remove_rule.failed",
+        "❌ Die Regel konnte leider nicht gelöscht werden. Bitte versuche es erneut.",
+    ),
+    // filter expressions
+    ("parse.position", "{error} (an Position {position})"),
+    ("parse.expected", "„{token}“ erwartet"),
+    (
+        "parse.tag_expected",
+        "Merkmal erwartet, z. B. „gremium:Rat“",
+    ),
+    (
+        "parse.unknown_tag",
+        "Unbekanntes Merkmal „{tag}“, möglich sind: {tags}",
+    ),
+    ("parse.value_expected", "Wert erwartet"),
+    ("parse.regex_expected", "Regex erwartet"),
+    (
+        "parse.end_expected",
+        "„&“, „|“ oder Ende des Ausdrucks erwartet",
+    ),
+    (
+        "error.invalid_expression",
+        "❌ Ungültiger Ausdruck: {error}",
+    ),
+    // /regel_testen
+    ("date.format", "%d.%m.%Y"),
+    ("test.title", "Testergebnis"),
+    (
+        "test.no_messages",
+        "Es liegen noch keine Benachrichtigungen vor, mit denen die Regel getestet werden könnte.",
+    ),
+    (
+        "test.result",
+        "Von den letzten {total} Benachrichtigungen (seit {since}) hätte die Regel {matching} erfasst",
+    ),
+    ("test.more", "… und {count} weitere"),
+    (
+        "test.no_rules",
+        "Zur Zeit sind keine Regeln für {target} aktiv! Du kannst aber einen Entwurf testen, z. B. /{command} gremium:Rat & art:Antrag",
+    ),
+    (
+        "test.question",
+        "Wähle eine der folgenden Regeln zum Testen aus:",
+    ),
+    // /neue_regel
+    ("new_rule.title", "Regel erstellen"),
+    (
+        "new_rule.intro",
+        "Wähle ein Merkmal für die erste Bedingung oder tippe auf „Speichern“, um die Regel sofort ohne Bedingungen (alle Vorlagen werden erfasst) anzulegen. Über „Gilt für“ legst du fest, ob die Regel Vorlagen, Sitzungen oder beides erfasst, über „Aktualisierungen“, ob du auch über Änderungen an bereits gemeldeten Vorlagen informiert werden möchtest, und über „Vergleich“, ob der Wert einen Text enthalten, ihm genau entsprechen oder zu einem regulären Ausdruck passen soll. Mit „Testen“ siehst du, welche der letzten Vorlagen die Regel erfasst hätte.",
+    ),
+    (
+        "new_rule.instances_hint",
+        " Tippe auf ein Ratsinformationssystem, um es für die Regel an- oder abzuwählen.",
+    ),
+    (
+        "new_rule.expression",
+        "Alternativ kannst du die Regel auch direkt als Ausdruck angeben, z. B. {example}. Mit „&“ (und), „|“ (oder), „!“ (nicht) und Klammern lassen sich Bedingungen beliebig verknüpfen.",
+    ),
+    ("new_rule.selected_chat", "Ausgewählter Chat: {chat}"),
+    ("new_rule.example", "Beispiel: {example}"),
+    ("new_rule.button.save", "✅ Speichern"),
+    ("new_rule.button.test", "🧪 Testen"),
+    ("new_rule.button.scope", "🔁 Gilt für: {scope}"),
+    ("new_rule.button.updates_on", "🔄 Aktualisierungen: an"),
+    ("new_rule.button.updates_off", "🔄 Aktualisierungen: aus"),
+    ("new_rule.button.snippets_on", "✂️ Textauszüge: an"),
+    ("new_rule.button.snippets_off", "✂️ Textauszüge: aus"),
+    ("new_rule.button.mode", "🔤 Vergleich: {mode}"),
+    (
+        "new_rule.saved",
+        "✅ Die Regel für {target} wurde gespeichert und ist nun aktiv!",
+    ),
+    (
+        "new_rule.expression_parsed",
+        "Aktuelle Regel:\n\n{summary}\nLege fest, wofür die Regel gelten soll, ergänze weitere Merkmale oder tippe auf „Speichern“.{hint}",
+    ),
+    ("new_rule.scope", "Die Regel gilt nun für {scope}."),
+    (
+        "new_rule.mode",
+        "Die nächste Bedingung vergleicht nun so: {description}.",
+    ),
+    (
+        "new_rule.updates_on",
+        "Du wirst auch über Änderungen an bereits gemeldeten Vorlagen informiert.",
+    ),
+    (
+        "new_rule.updates_off",
+        "Du wirst nicht über Änderungen an bereits gemeldeten Vorlagen informiert.",
+    ),
+    (
+        "new_rule.snippets_on",
+        "Benachrichtigungen enthalten die passenden Stellen aus dem Volltext.",
+    ),
+    (
+        "new_rule.snippets_off",
+        "Benachrichtigungen enthalten keine Stellen aus dem Volltext.",
+    ),
+    (
+        "new_rule.instances_empty",
+        "Mindestens ein Ratsinformationssystem muss ausgewählt bleiben.",
+    ),
+    (
+        "new_rule.tag_selected",
+        "Du hast das Merkmal {tag} gewählt.",
+    ),
+    (
+        "new_rule.suggestions",
+        "Tippe auf einen der häufigsten Werte oder gib einen eigenen ein. Mit „?Text“ suchst du nach weiteren bekannten Werten.",
+    ),
+    ("new_rule.examples", "Mögliche Werte sind beispielsweise: "),
+    (
+        "new_rule.value",
+        "Gib nun den Wert ein ({description}). Um die Bedingung umzudrehen, beginne mit einem Ausrufezeichen – dann werden alle Vorlagen, auf die sie zutrifft, ausgeschlossen.",
+    ),
+    (
+        "new_rule.invalid_tag",
+        "Bitte wähle ein gültiges Merkmal aus, oder sende /{cancel} zum Abbrechen.",
+    ),
+    (
+        "new_rule.search_empty",
+        "Es sind keine passenden Werte bekannt. Gib einen eigenen Wert ein oder suche erneut mit „?Text“.",
+    ),
+    (
+        "new_rule.search",
+        "Tippe auf einen der passenden Werte oder gib einen eigenen ein.",
+    ),
+    (
+        "new_rule.snippets_hint",
+        " Über „Textauszüge“ legst du fest, ob die passenden Stellen aus dem Volltext in den Benachrichtigungen angezeigt werden.",
+    ),
+    (
+        "new_rule.condition_added",
+        "Bedingung hinzugefügt – aktuelle Regel:\n\n{summary}\nWähle ein weiteres Merkmal oder tippe auf „Speichern“.{hint}",
+    ),
+    ("input.text", "Text"),
+    ("input.regex", "Regex-Pattern"),
+    (
+        "pattern.newline",
+        "❌ Ungültige Eingabe: Zeilenumbrüche sind nicht erlaubt. Bitte versuche es erneut.",
+    ),
+    (
+        "pattern.too_big",
+        "❌ Ungültiges Regex-Pattern: Das Pattern ist zu groß. Bitte versuche es erneut.",
+    ),
+    (
+        "pattern.invalid",
+        "❌ Ungültiges Regex-Pattern. Bitte versuche es erneut. Tipp: Frage ChatGPT um Hilfe.",
+    ),
+    // /regel_bearbeiten
+    (
+        "edit_rule.select",
+        "Wähle eine der folgenden Regeln zum Bearbeiten aus:",
+    ),
+    ("edit_rule.title", "Regel {number} bearbeiten"),
+    ("edit_rule.conditions", "Bedingungen:"),
+    (
+        "edit_rule.overview",
+        "Wähle eine Bedingung aus, um sie zu ändern, oder füge eine neue hinzu. Änderungen werden erst mit „Speichern“ übernommen.",
+    ),
+    ("edit_rule.condition", "Bedingung {number}: {condition}"),
+    ("edit_rule.question", "Was möchtest du ändern?"),
+    (
+        "edit_rule.new_condition",
+        "Wähle das Merkmal für die neue Bedingung.",
+    ),
+    (
+        "edit_rule.value",
+        "Gib den neuen Wert für das Merkmal {tag} ein ({description}).",
+    ),
+    ("edit_rule.example", " Zum Beispiel: {example}"),
+    (
+        "edit_rule.negate_hint",
+        " Um die Bedingung umzudrehen, beginne mit einem Ausrufezeichen.",
+    ),
+    ("edit_rule.saved", "✅ Die Regel wurde gespeichert!"),
+    (
+        "edit_rule.conflict",
+        "❌ Die Regel wurde in der Zwischenzeit geändert oder gelöscht, daher wurden deine Änderungen nicht gespeichert. Bitte versuche es erneut.",
+    ),
+    ("edit_rule.value_changed", "Der Wert wurde geändert."),
+    (
+        "edit_rule.condition_added",
+        "Die Bedingung wurde hinzugefügt.",
+    ),
+    ("edit_rule.negated", "Die Bedingung wurde umgedreht."),
+    (
+        "edit_rule.mode_changed",
+        "Die Vergleichsart wurde geändert.",
+    ),
+    ("edit_rule.removed", "Die Bedingung wurde entfernt."),
+    ("edit_rule.button.condition", "✏️ Bedingung {number}"),
+    ("edit_rule.button.add", "➕ Bedingung hinzufügen"),
+    ("edit_rule.button.negate", "🔁 Umdrehen"),
+    ("edit_rule.button.change_value", "📝 Wert ändern"),
+    ("edit_rule.button.remove", "🗑️ Entfernen"),
+    ("edit_rule.button.back", "⬅️ Zurück"),
+    // /ziel
+    ("target.button.this_chat", "Dieser Chat"),
+    ("target.button.channel", "Channel auswählen"),
+    (
+        "target.use_buttons",
+        "Bitte verwende die Schaltflächen, um einen Chat auszuwählen, oder sende /{cancel} zum Abbrechen.",
+    ),
+    (
+        "target.selected",
+        "✅ Der Kanal {channel} wurde ausgewählt!\n\nDu kannst nun die Einstellungen für diesen Channel ändern. Führe /{command} erneut aus, um die Auswahl zu ändern oder zurückzusetzen.",
+    ),
+    (
+        "target.reset",
+        "✅ Du kannst nun wieder Einstellungen für diesen privaten Chat vornehmen.",
+    ),
+    (
+        "target.private_only",
+        "Dieser Befehl wird nur in privaten Chats unterstützt!",
+    ),
+    (
+        "target.question_switch",
+        "Du kannst zu diesem privaten Chat zurückwechseln oder einen anderen Kanal wählen:",
+    ),
+    (
+        "target.question",
+        "Du kannst stattdessen auch einen Kanal auswählen:",
+    ),
+    // /werte
+    (
+        "values.usage",
+        "Gib ein Merkmal an, z. B. {example}, oder grenze die Werte zusätzlich ein, z. B. {example_query}.\n\nMögliche Merkmale sind: {tags}",
+    ),
+    (
+        "values.unknown_tag",
+        "❌ Unbekanntes Merkmal „{tag}“, möglich sind: {tags}",
+    ),
+    (
+        "values.not_collected",
+        "Für das Merkmal „{tag}“ werden keine Werte gesammelt, da sie sich fast immer unterscheiden. Möglich sind: {tags}",
+    ),
+    ("values.title", "Bekannte Werte für „{tag}“"),
+    ("values.none", "Bisher sind keine Werte bekannt."),
+    (
+        "values.none_matching",
+        "Es sind keine passenden Werte bekannt.",
+    ),
+    ("values.copy", "Tippe auf einen Wert, um ihn zu kopieren."),
+    // /nachladen
+    (
+        "backfill.usage",
+        "Verwendung: /{command} VON [BIS] [alle] [erneut]\n\nDie Daten werden im Format TT.MM.JJJJ angegeben. Die Vorlagen werden an den ausgewählten Chat geschickt, soweit sie auf dessen Regeln passen. Mit „alle“ gehen sie an alle Chats mit passenden Regeln, mit „erneut“ werden auch bereits verschickte Vorlagen berücksichtigt.",
+    ),
+    ("backfill.loading", "⏳ Die Vorlagen werden geladen …"),
+    (
+        "backfill.scheduled",
+        "✅ {instance}: {count} Vorlagen eingeplant",
+    ),
+    (
+        "backfill.failed",
+        "❌ {instance}: Fehler beim Laden der Vorlagen",
+    ),
+    // /datenschutz
+    (
+        "privacy.contact",
+        "Bei Fragen kontaktiere mich direkt: {contact}",
+    ),
+    // chat kinds
+    ("chat_kind.private", "Privatchats"),
+    ("chat_kind.group", "Gruppen"),
+    ("chat_kind.supergroup", "Supergruppen und Kanäle"),
+    ("chat_kind.topic", "Themen in Foren"),
+    ("datetime.format", "%d.%m.%Y %H:%M"),
+    // /statistik
+    ("statistics.title", "📊 Statistik"),
+    ("statistics.chats", "Registrierte Chats: {count}"),
+    (
+        "statistics.rules",
+        "Regeln: {count}, höchstens {max} in einem Chat",
+    ),
+    ("statistics.stream", "Nachrichten im Stream: {count}"),
+    (
+        "statistics.backlog",
+        "Chats mit ausstehenden Nachrichten: {chats}",
+    ),
+    ("statistics.updates", "Letzte Aktualisierung"),
+    (
+        "statistics.no_update",
+        "⏳ {instance}: noch nicht aktualisiert",
+    ),
+    // /chats
+    ("chats.title", "Registrierte Chats: {count}"),
+    ("chats.line", "{rules} Regeln, {backlog} ausstehend"),
+    (
+        "chats.inspect",
+        "Mit /{command} CHAT_ID werden die Regeln eines Chats angezeigt.",
+    ),
+    (
+        "chats.usage",
+        "Verwendung: /{command} [CHAT_ID]\n\nFür ein Thema wird CHAT_ID:THEMA_ID angegeben.",
+    ),
+    ("chats.not_registered", "Dieser Chat ist nicht registriert."),
+    ("chats.delivery", "Zustellung: {mode}"),
+    ("chats.backlog", "Ausstehende Nachrichten: {backlog}"),
+    // /ankuendigung
+    (
+        "announcement.usage",
+        "Verwendung: /{command} [privat|gruppen] TEXT\n\nDie Ankündigung geht an alle registrierten Chats, mit „privat“ nur an Privatchats und mit „gruppen“ nur an Gruppen, Kanäle und Themen.",
+    ),
+    ("announcement.all", "alle Chats"),
+    ("announcement.private", "Privatchats"),
+    ("announcement.groups", "Gruppen, Kanäle und Themen"),
+    (
+        "announcement.question",
+        "Soll diese Ankündigung an {count} Chats ({target}) geschickt werden?",
+    ),
+    ("announcement.confirm", "📣 Senden"),
+    (
+        "announcement.scheduled",
+        "✅ Die Ankündigung wird verschickt.",
+    ),
+    // /rechte
+    ("permissions.admins", "🛡 Nur Administratoren"),
+    ("permissions.members", "👥 Alle Mitglieder"),
+    (
+        "permissions.current",
+        "Regeln und Einstellungen verwalten dürfen: {who}",
+    ),
+    (
+        "permissions.question",
+        "Wer soll sie in dieser Gruppe verwalten dürfen?",
+    ),
+    (
+        "permissions.set",
+        "✅ Regeln und Einstellungen verwalten dürfen ab jetzt: {who}",
+    ),
+    (
+        "permissions.only_groups",
+        "Diese Einstellung gibt es nur in Gruppen.",
+    ),
+    // /kanaele
+    ("channels.title", "📢 Deine Kanäle"),
+    ("channels.active", ": {count} Regeln"),
+    ("channels.inactive", ": keine Regeln"),
+    ("channels.current", " – aktuell ausgewählt"),
+    (
+        "channels.question",
+        "Für welchen Chat möchtest du Einstellungen vornehmen?",
+    ),
+    (
+        "channels.none",
+        "Du hast noch keinen Kanal ausgewählt. Mit /{command} kannst du einen Kanal hinzufügen.",
+    ),
+    // /suche
+    (
+        "search.usage",
+        "Gib einen Suchbegriff an, z. B. /{command} Radweg",
+    ),
+    ("search.title", "Suchergebnisse für „{query}“"),
+    ("search.page", "{count} Treffer, Seite {page} von {pages}:"),
+    (
+        "search.select",
+        "Tippe auf eine Nummer, um die Benachrichtigung mit allen Links zu erhalten.",
+    ),
+    ("search.previous", "◀️ Zurück"),
+    ("search.next", "Weiter ▶️"),
+    (
+        "search.no_results",
+        "Keine der Benachrichtigungen seit {since} passt zu „{query}“.",
+    ),
+    (
+        "search.expired",
+        "Diese Benachrichtigung ist nicht mehr verfügbar.",
+    ),
+    // digests
+    (
+        "digest.header.one",
+        "📬 Zusammenfassung: 1 Benachrichtigung",
+    ),
+    (
+        "digest.header.many",
+        "📬 Zusammenfassung: {count} Benachrichtigungen",
+    ),
+    // snippets of the full text in notifications
+    ("snippets.header", "🔎 Fundstellen im Volltext:"),
+];
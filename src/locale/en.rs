@@ -0,0 +1,679 @@
+pub const TEXTS: &[(&str, &str)] = &[
+    ("language.name", "English"),
+    ("list.and", " and "),
+    // commands, keyed by their name
+    ("command.neue_regel", "Create a new notification rule"),
+    ("command.regeln", "Show all existing rules"),
+    (
+        "command.regel_bearbeiten",
+        "Edit the conditions of an existing rule",
+    ),
+    ("command.regel_loeschen", "Delete an existing rule"),
+    ("command.alle_regeln_loeschen", "Remove all rules"),
+    (
+        "command.regel_testen",
+        "Check which of the recent papers a rule would have matched",
+    ),
+    ("command.suche", "Search the recent notifications"),
+    ("command.werte", "Show the known values of a property"),
+    (
+        "command.ziel",
+        "Choose the chat you want to configure notifications for",
+    ),
+    (
+        "command.zustellung",
+        "Choose whether notifications are delivered instantly or collected",
+    ),
+    ("command.abbrechen", "Cancel the current operation"),
+    ("command.hilfe", "Show the help message"),
+    ("command.start", "Show the help message"),
+    ("command.datenschutz", "Show the privacy policy"),
+    (
+        "command.sprache",
+        "Set the language of the bot for this chat",
+    ),
+    (
+        "command.kanaele",
+        "Show your channels and switch between them",
+    ),
+    (
+        "command.rechte",
+        "Choose who may manage the rules in this group",
+    ),
+    ("command.nachladen", "Send the papers of a past period"),
+    ("command.statistik", "Show the bot's statistics"),
+    ("command.chats", "Show the registered chats and their rules"),
+    ("command.ankuendigung", "Send an announcement to all chats"),
+    // bot description
+    (
+        "description.one",
+        "This bot notifies you when new papers are published in the council information system {names}.",
+    ),
+    (
+        "description.many",
+        "This bot notifies you when new papers are published in the council information systems {names}.",
+    ),
+    (
+        "description.short",
+        "Notifications about new papers and meetings in {city}",
+    ),
+    // help message
+    (
+        "help.intro.one",
+        "This bot notifies you when new papers are published in the council information system ",
+    ),
+    (
+        "help.intro.many",
+        "This bot notifies you when new papers are published in the council information systems ",
+    ),
+    ("help.intro.published", " – just define "),
+    ("help.intro.rules", "rules"),
+    ("help.intro.end", " for the papers you want to receive."),
+    ("help.rules.title", "🔧 Managing rules"),
+    (
+        "help.rules.description",
+        "You receive notifications for all papers that match at least one rule.",
+    ),
+    ("help.target.title", "📬 Target and delivery"),
+    (
+        "help.target.description",
+        "The bot can send notifications here in the chat or in one of your channels, one by one or collected as a digest.",
+    ),
+    ("help.permissions.title", "🛡 Permissions"),
+    (
+        "help.permissions.description",
+        "In groups, only administrators may change the rules and settings, unless they allow all members to do so.",
+    ),
+    ("help.misc.title", "🆘 Miscellaneous"),
+    ("help.misc.help", "/{help} or /{start} – Show this help"),
+    ("help.regex.title", "📚 Regular expressions (regex)"),
+    (
+        "help.regex.text",
+        "When creating a rule, you can require a property to match a so-called regex pattern. Just enter the text you want to filter for – in most cases, that works reliably. If you need more complex patterns, ",
+    ),
+    (
+        "help.regex.end",
+        " or ChatGPT help you to try out and learn regular expressions.",
+    ),
+    ("help.disclaimer.title", "⚖️ Note"),
+    (
+        "help.disclaimer.text",
+        "This bot is a purely private, non-commercial project that notifies about new papers from ALLRIS® systems. It is affiliated neither with CC e-gov GmbH nor with the municipalities operating these systems.",
+    ),
+    (
+        "help.disclaimer.warranty",
+        "No guarantee is given for the completeness, correctness or timeliness of the information provided.",
+    ),
+    ("help.about.title", "👨‍💻 More info & contact"),
+    (
+        "help.about.source",
+        "The source code of this bot is publicly available: {url} (version {version})",
+    ),
+    (
+        "help.about.contact",
+        "Questions, feedback or ideas? Feel free to write to me: {contact}",
+    ),
+    // tags
+    ("tag.titel", "Title"),
+    ("tag.dsnr", "Document number"),
+    ("tag.art", "Type of paper"),
+    ("tag.gremium", "Committee"),
+    (
+        "tag.gremium.description",
+        "committees scheduled to discuss the paper, or the committee of a meeting",
+    ),
+    ("tag.verfasser", "Applicant or questioner"),
+    (
+        "tag.verfasser.description",
+        "persons or parliamentary groups who submitted the motion or the question",
+    ),
+    ("tag.federführend", "Lead department"),
+    ("tag.beteiligt", "Involved department"),
+    (
+        "tag.beteiligt.description",
+        "every department involved in the paper, including the lead department",
+    ),
+    ("tag.volltext", "Full text"),
+    (
+        "tag.volltext.description",
+        "the text of the paper's main document (PDF)",
+    ),
+    // delivery modes
+    ("delivery.instant", "instantly"),
+    ("delivery.hourly", "hourly"),
+    ("delivery.daily", "daily at {hour}:00"),
+    ("delivery.weekly", "weekly on {weekday} at {hour}:00"),
+    ("weekday.1", "Monday"),
+    ("weekday.2", "Tuesday"),
+    ("weekday.3", "Wednesday"),
+    ("weekday.4", "Thursday"),
+    ("weekday.5", "Friday"),
+    ("weekday.6", "Saturday"),
+    ("weekday.7", "Sunday"),
+    // filters
+    ("scope.papers", "papers"),
+    ("scope.meetings", "meetings"),
+    ("scope.all", "papers and meetings"),
+    ("mode.contains", "contains"),
+    (
+        "mode.contains.description",
+        "the value must contain the text",
+    ),
+    ("mode.word", "whole word"),
+    (
+        "mode.word.description",
+        "the value must contain the text as a whole word",
+    ),
+    ("mode.exact", "exact"),
+    (
+        "mode.exact.description",
+        "the value must be exactly the text",
+    ),
+    ("mode.prefix", "starts with"),
+    (
+        "mode.prefix.description",
+        "the value must start with the text",
+    ),
+    ("mode.regex", "Regex"),
+    (
+        "mode.regex.description",
+        "the value must match a regular expression (regex)",
+    ),
+    ("condition.contains", "{label} contains \"{pattern}\""),
+    (
+        "condition.contains.not",
+        "{label} does not contain \"{pattern}\"",
+    ),
+    ("condition.word", "{label} contains the word \"{pattern}\""),
+    (
+        "condition.word.not",
+        "{label} does not contain the word \"{pattern}\"",
+    ),
+    ("condition.exact", "{label} is \"{pattern}\""),
+    ("condition.exact.not", "{label} is not \"{pattern}\""),
+    ("condition.prefix", "{label} starts with \"{pattern}\""),
+    (
+        "condition.prefix.not",
+        "{label} does not start with \"{pattern}\"",
+    ),
+    ("condition.regex", "{label} matches the regex \"{pattern}\""),
+    (
+        "condition.regex.not",
+        "{label} does not match the regex \"{pattern}\"",
+    ),
+    ("filter.all", "All {scope}"),
+    ("filter.scope", "• Applies to {scope}"),
+    ("filter.instances", "• Only from {instances}"),
+    ("filter.updates", "• incl. updates"),
+    ("filter.snippets", "• incl. text excerpts"),
+    // chats
+    ("quoted", "“{text}”"),
+    ("chat.this", "💬 This chat"),
+    ("chat.this_accusative", "this chat"),
+    ("channel.accusative", "the channel {channel}"),
+    ("channel.unknown", "<unknown>"),
+    // errors
+    (
+        "error.not_channel_admin",
+        "You don't have the required permissions for this channel!",
+    ),
+    (
+        "error.not_group_admin",
+        "⛔ Only administrators may do this in this group.",
+    ),
+    ("error.unknown_command", "Unknown command!"),
+    ("error.internal", "An internal error occurred 😢"),
+    (
+        "error.outdated_selection",
+        "This selection is no longer valid.",
+    ),
+    // /abbrechen
+    ("cancel.done", "The command was cancelled!"),
+    ("cancel.inactive", "No command was active"),
+    // /sprache
+    ("language.current", "Current setting: {language}"),
+    (
+        "language.question",
+        "Which language should the bot use in this chat?",
+    ),
+    ("language.automatic", "🌐 Automatic"),
+    (
+        "language.set",
+        "✅ From now on, the bot uses English in this chat.",
+    ),
+    (
+        "language.automatic_set",
+        "✅ From now on, the bot follows the language of the Telegram app in this chat.",
+    ),
+    (
+        "language.unknown",
+        "❌ Unknown language “{code}”, possible are: {codes}",
+    ),
+    (
+        "dialogue.use_buttons",
+        "Please use the buttons or send /{cancel} to cancel.",
+    ),
+    // dialogues
+    ("dialogue.cancel", "Cancel"),
+    ("dialogue.cancelled", "The operation was cancelled!"),
+    (
+        "dialogue.select_rule",
+        "Please use the buttons to select a rule, or send /{cancel} to cancel.",
+    ),
+    (
+        "rules.none",
+        "There are currently no rules active for {target}!",
+    ),
+    (
+        "rules.none_create",
+        "There are currently no rules active for {target}! First create a rule with /{new_rule}.",
+    ),
+    // /zustellung
+    ("delivery.button.instant", "⚡ Instantly"),
+    ("delivery.button.hourly", "🕐 Hourly"),
+    ("delivery.button.daily", "📅 Daily"),
+    ("delivery.button.weekly", "🗓️ Weekly"),
+    ("delivery.button.hour", "{hour}:00"),
+    (
+        "delivery.question.mode",
+        "How should the notifications be delivered?",
+    ),
+    (
+        "delivery.question.weekday",
+        "On which day should the digest arrive?",
+    ),
+    (
+        "delivery.question.hour",
+        "At what time should the digest arrive?",
+    ),
+    (
+        "delivery.current",
+        "Notifications for {target} are currently delivered {mode}.\n\nAlternatively, they can be collected and arrive as an hourly, daily or weekly digest. {question}",
+    ),
+    (
+        "delivery.saved.instant",
+        "✅ From now on, notifications for {target} are delivered instantly.",
+    ),
+    (
+        "delivery.saved.digest",
+        "✅ From now on, notifications for {target} are delivered {mode} as a digest.",
+    ),
+    // /alle_regeln_loeschen
+    ("remove_all.confirm", "⚠️ Yes, delete everything!"),
+    ("remove_all.done", "✅ Your rules have been deleted!"),
+    (
+        "remove_all.failed",
+        "❌ Unfortunately, the rules could not be deleted. Please try again.",
+    ),
+    (
+        "remove_all.question",
+        "🗑️ You are about to remove all rules for {target}.\n\nAre you sure? You won't receive any notifications afterwards.",
+    ),
+    // rules
+    ("rule.number", "Rule {number}"),
+    ("selection.current", "Current selection: {chat}"),
+    ("rules.empty", "There are no rules active for {target}."),
+    (
+        "rules.list",
+        "The following rules are currently active for {target}:",
+    ),
+    (
+        "remove_rule.question",
+        "Choose one of the following rules to delete:",
+    ),
+    ("remove_rule.done", "✅ The rule has been deleted!"),
+    (
+        "remove_rule.failed",
+        "❌ Unfortunately, the rule could not be deleted. Please try again.",
+    ),
+    // filter expressions
+    ("parse.position", "{error} (at position {position})"),
+    ("parse.expected", "“{token}” expected"),
+    (
+        "parse.tag_expected",
+        "Property expected, e.g. “gremium:Rat”",
+    ),
+    (
+        "parse.unknown_tag",
+        "Unknown property “{tag}”, possible are: {tags}",
+    ),
+    ("parse.value_expected", "Value expected"),
+    ("parse.regex_expected", "Regex expected"),
+    (
+        "parse.end_expected",
+        "“&”, “|” or end of expression expected",
+    ),
+    ("error.invalid_expression", "❌ Invalid expression: {error}"),
+    // /regel_testen
+    ("date.format", "%Y-%m-%d"),
+    ("test.title", "Test result"),
+    (
+        "test.no_messages",
+        "There are no notifications yet that the rule could be tested against.",
+    ),
+    (
+        "test.result",
+        "Of the last {total} notifications (since {since}), the rule would have matched {matching}",
+    ),
+    ("test.more", "… and {count} more"),
+    (
+        "test.no_rules",
+        "There are currently no rules active for {target}! But you can test a draft, e.g. /{command} gremium:Rat & art:Antrag",
+    ),
+    (
+        "test.question",
+        "Choose one of the following rules to test:",
+    ),
+    // /neue_regel
+    ("new_rule.title", "Create a rule"),
+    (
+        "new_rule.intro",
+        "Choose a property for the first condition or tap “Save” to create the rule right away without conditions (all papers are matched). “Applies to” sets whether the rule matches papers, meetings or both, “Updates” whether you also want to be informed about changes to papers that were already reported, and “Comparison” whether the value should contain a text, be exactly the text or match a regular expression. With “Test”, you see which of the recent papers the rule would have matched.",
+    ),
+    (
+        "new_rule.instances_hint",
+        " Tap a council information system to select or deselect it for the rule.",
+    ),
+    (
+        "new_rule.expression",
+        "Alternatively, you can enter the rule directly as an expression, e.g. {example}. With “&” (and), “|” (or), “!” (not) and parentheses, conditions can be combined in any way.",
+    ),
+    ("new_rule.selected_chat", "Selected chat: {chat}"),
+    ("new_rule.example", "Example: {example}"),
+    ("new_rule.button.save", "✅ Save"),
+    ("new_rule.button.test", "🧪 Test"),
+    ("new_rule.button.scope", "🔁 Applies to: {scope}"),
+    ("new_rule.button.updates_on", "🔄 Updates: on"),
+    ("new_rule.button.updates_off", "🔄 Updates: off"),
+    ("new_rule.button.snippets_on", "✂️ Text excerpts: on"),
+    ("new_rule.button.snippets_off", "✂️ Text excerpts: off"),
+    ("new_rule.button.mode", "🔤 Comparison: {mode}"),
+    (
+        "new_rule.saved",
+        "✅ The rule for {target} has been saved and is now active!",
+    ),
+    (
+        "new_rule.expression_parsed",
+        "Current rule:\n\n{summary}\nSet what the rule applies to, add further properties or tap “Save”.{hint}",
+    ),
+    ("new_rule.scope", "The rule now applies to {scope}."),
+    (
+        "new_rule.mode",
+        "The next condition now compares like this: {description}.",
+    ),
+    (
+        "new_rule.updates_on",
+        "You will also be informed about changes to papers that were already reported.",
+    ),
+    (
+        "new_rule.updates_off",
+        "You will not be informed about changes to papers that were already reported.",
+    ),
+    (
+        "new_rule.snippets_on",
+        "Notifications contain the matching passages from the full text.",
+    ),
+    (
+        "new_rule.snippets_off",
+        "Notifications don't contain passages from the full text.",
+    ),
+    (
+        "new_rule.instances_empty",
+        "At least one council information system must remain selected.",
+    ),
+    (
+        "new_rule.tag_selected",
+        "You have chosen the property {tag}.",
+    ),
+    (
+        "new_rule.suggestions",
+        "Tap one of the most common values or enter your own. With “?text”, you search for further known values.",
+    ),
+    ("new_rule.examples", "Possible values are, for example: "),
+    (
+        "new_rule.value",
+        "Now enter the value ({description}). To invert the condition, start with an exclamation mark – then all papers it applies to are excluded.",
+    ),
+    (
+        "new_rule.invalid_tag",
+        "Please choose a valid property, or send /{cancel} to cancel.",
+    ),
+    (
+        "new_rule.search_empty",
+        "No matching values are known. Enter your own value or search again with “?text”.",
+    ),
+    (
+        "new_rule.search",
+        "Tap one of the matching values or enter your own.",
+    ),
+    (
+        "new_rule.snippets_hint",
+        " “Text excerpts” sets whether the matching passages from the full text are shown in the notifications.",
+    ),
+    (
+        "new_rule.condition_added",
+        "Condition added – current rule:\n\n{summary}\nChoose another property or tap “Save”.{hint}",
+    ),
+    ("input.text", "Text"),
+    ("input.regex", "Regex pattern"),
+    (
+        "pattern.newline",
+        "❌ Invalid input: line breaks are not allowed. Please try again.",
+    ),
+    (
+        "pattern.too_big",
+        "❌ Invalid regex pattern: the pattern is too big. Please try again.",
+    ),
+    (
+        "pattern.invalid",
+        "❌ Invalid regex pattern. Please try again. Tip: ask ChatGPT for help.",
+    ),
+    // /regel_bearbeiten
+    (
+        "edit_rule.select",
+        "Choose one of the following rules to edit:",
+    ),
+    ("edit_rule.title", "Edit rule {number}"),
+    ("edit_rule.conditions", "Conditions:"),
+    (
+        "edit_rule.overview",
+        "Choose a condition to change it, or add a new one. Changes only take effect with “Save”.",
+    ),
+    ("edit_rule.condition", "Condition {number}: {condition}"),
+    ("edit_rule.question", "What do you want to change?"),
+    (
+        "edit_rule.new_condition",
+        "Choose the property for the new condition.",
+    ),
+    (
+        "edit_rule.value",
+        "Enter the new value for the property {tag} ({description}).",
+    ),
+    ("edit_rule.example", " For example: {example}"),
+    (
+        "edit_rule.negate_hint",
+        " To invert the condition, start with an exclamation mark.",
+    ),
+    ("edit_rule.saved", "✅ The rule has been saved!"),
+    (
+        "edit_rule.conflict",
+        "❌ The rule was changed or deleted in the meantime, so your changes were not saved. Please try again.",
+    ),
+    ("edit_rule.value_changed", "The value has been changed."),
+    ("edit_rule.condition_added", "The condition has been added."),
+    ("edit_rule.negated", "The condition has been inverted."),
+    ("edit_rule.mode_changed", "The comparison has been changed."),
+    ("edit_rule.removed", "The condition has been removed."),
+    ("edit_rule.button.condition", "✏️ Condition {number}"),
+    ("edit_rule.button.add", "➕ Add condition"),
+    ("edit_rule.button.negate", "🔁 Invert"),
+    ("edit_rule.button.change_value", "📝 Change value"),
+    ("edit_rule.button.remove", "🗑️ Remove"),
+    ("edit_rule.button.back", "⬅️ Back"),
+    // /ziel
+    ("target.button.this_chat", "This chat"),
+    ("target.button.channel", "Select channel"),
+    (
+        "target.use_buttons",
+        "Please use the buttons to select a chat, or send /{cancel} to cancel.",
+    ),
+    (
+        "target.selected",
+        "✅ The channel {channel} has been selected!\n\nYou can now change the settings for this channel. Run /{command} again to change or reset the selection.",
+    ),
+    (
+        "target.reset",
+        "✅ You can now change the settings for this private chat again.",
+    ),
+    (
+        "target.private_only",
+        "This command is only supported in private chats!",
+    ),
+    (
+        "target.question_switch",
+        "You can switch back to this private chat or choose another channel:",
+    ),
+    ("target.question", "You can also select a channel instead:"),
+    // /werte
+    (
+        "values.usage",
+        "Specify a property, e.g. {example}, or additionally narrow down the values, e.g. {example_query}.\n\nPossible properties are: {tags}",
+    ),
+    (
+        "values.unknown_tag",
+        "❌ Unknown property “{tag}”, possible are: {tags}",
+    ),
+    (
+        "values.not_collected",
+        "No values are collected for the property “{tag}”, as they almost always differ. Possible are: {tags}",
+    ),
+    ("values.title", "Known values for “{tag}”"),
+    ("values.none", "No values are known yet."),
+    ("values.none_matching", "No matching values are known."),
+    ("values.copy", "Tap a value to copy it."),
+    // /nachladen
+    (
+        "backfill.usage",
+        "Usage: /{command} FROM [UNTIL] [alle] [erneut]\n\nDates are given in the format DD.MM.YYYY or YYYY-MM-DD. The papers are sent to the selected chat, as far as they match its rules. With “alle”, they go to all chats with matching rules, with “erneut”, papers that were already sent are included as well.",
+    ),
+    ("backfill.loading", "⏳ Loading the papers …"),
+    (
+        "backfill.scheduled",
+        "✅ {instance}: {count} papers scheduled",
+    ),
+    (
+        "backfill.failed",
+        "❌ {instance}: error while loading the papers",
+    ),
+    // /datenschutz
+    (
+        "privacy.contact",
+        "If you have questions, contact me directly: {contact}",
+    ),
+    // chat kinds
+    ("chat_kind.private", "Private chats"),
+    ("chat_kind.group", "Groups"),
+    ("chat_kind.supergroup", "Supergroups and channels"),
+    ("chat_kind.topic", "Forum topics"),
+    ("datetime.format", "%Y-%m-%d %H:%M"),
+    // /statistik
+    ("statistics.title", "📊 Statistics"),
+    ("statistics.chats", "Registered chats: {count}"),
+    (
+        "statistics.rules",
+        "Rules: {count}, at most {max} in one chat",
+    ),
+    ("statistics.stream", "Messages in the stream: {count}"),
+    ("statistics.backlog", "Chats with pending messages: {chats}"),
+    ("statistics.updates", "Last update"),
+    ("statistics.no_update", "⏳ {instance}: not updated yet"),
+    // /chats
+    ("chats.title", "Registered chats: {count}"),
+    ("chats.line", "{rules} rules, {backlog} pending"),
+    (
+        "chats.inspect",
+        "Use /{command} CHAT_ID to show the rules of a chat.",
+    ),
+    (
+        "chats.usage",
+        "Usage: /{command} [CHAT_ID]\n\nFor a topic, give CHAT_ID:TOPIC_ID.",
+    ),
+    ("chats.not_registered", "This chat is not registered."),
+    ("chats.delivery", "Delivery: {mode}"),
+    ("chats.backlog", "Pending messages: {backlog}"),
+    // /ankuendigung
+    (
+        "announcement.usage",
+        "Usage: /{command} [privat|gruppen] TEXT\n\nThe announcement is sent to all registered chats, with “privat” only to private chats and with “gruppen” only to groups, channels and topics.",
+    ),
+    ("announcement.all", "all chats"),
+    ("announcement.private", "private chats"),
+    ("announcement.groups", "groups, channels and topics"),
+    (
+        "announcement.question",
+        "Send this announcement to {count} chats ({target})?",
+    ),
+    ("announcement.confirm", "📣 Send"),
+    (
+        "announcement.scheduled",
+        "✅ The announcement is being sent.",
+    ),
+    // /rechte
+    ("permissions.admins", "🛡 Administrators only"),
+    ("permissions.members", "👥 All members"),
+    (
+        "permissions.current",
+        "Rules and settings may be managed by: {who}",
+    ),
+    (
+        "permissions.question",
+        "Who should be allowed to manage them in this group?",
+    ),
+    (
+        "permissions.set",
+        "✅ Rules and settings may now be managed by: {who}",
+    ),
+    (
+        "permissions.only_groups",
+        "This setting is only available in groups.",
+    ),
+    // /kanaele
+    ("channels.title", "📢 Your channels"),
+    ("channels.active", ": {count} rules"),
+    ("channels.inactive", ": no rules"),
+    ("channels.current", " – currently selected"),
+    (
+        "channels.question",
+        "Which chat do you want to change the settings for?",
+    ),
+    (
+        "channels.none",
+        "You haven't selected a channel yet. Use /{command} to add one.",
+    ),
+    // /suche
+    (
+        "search.usage",
+        "Enter a search term, e.g. /{command} Radweg",
+    ),
+    ("search.title", "Search results for “{query}”"),
+    ("search.page", "{count} results, page {page} of {pages}:"),
+    (
+        "search.select",
+        "Tap a number to receive the notification with all links.",
+    ),
+    ("search.previous", "◀️ Back"),
+    ("search.next", "Next ▶️"),
+    (
+        "search.no_results",
+        "None of the notifications since {since} matches “{query}”.",
+    ),
+    (
+        "search.expired",
+        "This notification is no longer available.",
+    ),
+    // digests
+    ("digest.header.one", "📬 Digest: 1 notification"),
+    ("digest.header.many", "📬 Digest: {count} notifications"),
+    // snippets of the full text in notifications
+    ("snippets.header", "🔎 Matches in the full text:"),
+];
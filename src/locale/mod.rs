@@ -0,0 +1,218 @@
+//! The message catalogue: the texts of the bot in every supported language, looked up by key.
+//!
+//! Texts may contain placeholders like `{name}`, which are filled in by [`tr!`].
+
+use std::fmt::Display;
+
+use telegram_message_builder::{WriteToMessage, from_fn};
+
+mod de;
+mod en;
+
+/// Looks up a text in the catalogue of the locale, filling in the given placeholders
+macro_rules! tr {
+    ($locale:expr, $key:expr $(,)?) => {
+        $locale.text($key)
+    };
+    ($locale:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::locale::fill(
+            $locale.text($key),
+            &[$((::core::stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+/// Like [`tr!`], but the placeholders are filled with parts of a message, e.g. links
+macro_rules! tr_msg {
+    ($locale:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::locale::fill_message(
+            $locale.text($key),
+            [$((
+                ::core::stringify!($name),
+                &$value as &dyn ::telegram_message_builder::WriteToMessage,
+            )),+],
+        )
+    };
+}
+
+enum Segment<'t> {
+    Text(&'t str),
+    /// the index of the value for a placeholder
+    Value(usize),
+}
+
+/// Splits the text at the placeholders of the given names. Other braces are kept as they are.
+fn segments<'t>(text: &'t str, names: impl Fn(&str) -> Option<usize>) -> Vec<Segment<'t>> {
+    let mut segments = vec![];
+    let mut rest = text;
+    let mut literal = 0;
+
+    while let Some(start) = rest[literal..].find('{').map(|i| literal + i) {
+        let value = rest[start..]
+            .find('}')
+            .and_then(|end| Some((start + end, names(&rest[start + 1..start + end])?)));
+
+        match value {
+            Some((end, index)) => {
+                segments.push(Segment::Text(&rest[..start]));
+                segments.push(Segment::Value(index));
+                rest = &rest[end + 1..];
+                literal = 0;
+            }
+            None => literal = start + 1,
+        }
+    }
+
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+/// Fills in the placeholders in a single pass, so values containing braces are kept as they are
+pub fn fill(text: &str, values: &[(&str, &dyn Display)]) -> String {
+    let names = |name: &str| values.iter().position(|(n, _)| *n == name);
+
+    segments(text, names)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_string(),
+            Segment::Value(i) => values[i].1.to_string(),
+        })
+        .collect()
+}
+
+pub fn fill_message<'a, const N: usize>(
+    text: &'static str,
+    values: [(&'static str, &'a dyn WriteToMessage); N],
+) -> impl WriteToMessage + 'a {
+    from_fn(move |msg| {
+        let names = |name: &str| values.iter().position(|(n, _)| *n == name);
+
+        for segment in segments(text, names) {
+            match segment {
+                Segment::Text(text) => msg.write(text)?,
+                Segment::Value(i) => msg.write(values[i].1)?,
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    De,
+    En,
+}
+
+impl Locale {
+    pub const LOCALES: &[Self] = &[Locale::De, Locale::En];
+
+    /// The IETF language tag, as used by Telegram
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::De => "de",
+            Locale::En => "en",
+        }
+    }
+
+    /// Accepts regional variants like `en-GB` as well
+    pub fn from_code(code: &str) -> Option<Self> {
+        let language = code.split(['-', '_']).next()?;
+        Self::LOCALES
+            .iter()
+            .copied()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    fn catalogue(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::De => de::TEXTS,
+            Locale::En => en::TEXTS,
+        }
+    }
+
+    /// The text for the key, if it is in the catalogue of this locale
+    pub fn get(self, key: &str) -> Option<&'static str> {
+        self.catalogue()
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, text)| *text)
+    }
+
+    /// The text for the key, falling back to the default locale if it is missing
+    pub fn text(self, key: &str) -> &'static str {
+        self.get(key)
+            .or_else(|| Locale::default().get(key))
+            .unwrap_or_else(|| {
+                log::warn!("Missing text {key:?}");
+                ""
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{Locale, fill};
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}'))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogues_complete() {
+        let default = Locale::default().catalogue();
+
+        for &locale in Locale::LOCALES {
+            let catalogue = locale.catalogue();
+            let keys: BTreeSet<_> = catalogue.iter().map(|(key, _)| *key).collect();
+            assert_eq!(keys.len(), catalogue.len(), "duplicate keys in {locale:?}");
+
+            for (key, text) in default {
+                let translation = locale.get(key);
+                assert!(translation.is_some(), "{key:?} is missing in {locale:?}");
+                assert_eq!(
+                    placeholders(translation.unwrap()),
+                    placeholders(text),
+                    "placeholders of {key:?} differ in {locale:?}"
+                );
+            }
+
+            for key in keys {
+                assert!(
+                    Locale::default().get(key).is_some(),
+                    "{key:?} of {locale:?} is unknown"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(Locale::from_code("de"), Some(Locale::De));
+        assert_eq!(Locale::from_code("en-GB"), Some(Locale::En));
+        assert_eq!(Locale::from_code("EN"), Some(Locale::En));
+        assert_eq!(Locale::from_code("fr"), None);
+        assert_eq!(Locale::from_code(""), None);
+    }
+
+    #[test]
+    fn test_tr() {
+        assert_eq!(tr!(Locale::En, "error.unknown_command"), "Unknown command!");
+        assert_eq!(
+            tr!(Locale::De, "help.about.contact", contact = "@jemand"),
+            "Fragen, Feedback oder Ideen? Schreib mir gern: @jemand"
+        );
+        assert_eq!(
+            fill("{a} und {b}", &[("a", &"{b}"), ("b", &2)]),
+            "{b} und 2"
+        );
+        assert_eq!(fill("{unbekannt} {", &[]), "{unbekannt} {");
+    }
+}
@@ -79,22 +79,26 @@ impl<K: Eq + Hash + Clone, V, E: EvictionStrategy<K>> CacheInner<K, V, E> {
         }
     }
 
-    fn get_if_valid(&mut self, key: K, is_valid: impl FnOnce(&V) -> bool) -> Arc<OnceCell<V>> {
+    fn touch(&mut self, key: &K) {
         if let Some(evict) = self
             .eviction_strategy
-            .insert(&key, self.cache.contains_key(&key))
+            .insert(key, self.cache.contains_key(key))
         {
             self.cache.remove(&evict);
         }
+    }
+
+    fn get_if_valid(&mut self, key: K, is_valid: impl FnOnce(&V) -> bool) -> Arc<OnceCell<V>> {
+        self.touch(&key);
 
         match self.cache.entry(key) {
             Entry::Occupied(mut entry) => {
                 let cell = entry.get();
-                if let Some(val) = cell.get() {
-                    if !is_valid(val) {
-                        entry.insert(Default::default());
-                        return entry.get().clone();
-                    }
+                if let Some(val) = cell.get()
+                    && !is_valid(val)
+                {
+                    entry.insert(Default::default());
+                    return entry.get().clone();
                 }
 
                 cell.clone()
@@ -104,15 +108,15 @@ impl<K: Eq + Hash + Clone, V, E: EvictionStrategy<K>> CacheInner<K, V, E> {
     }
 
     fn get(&mut self, key: K) -> Arc<OnceCell<V>> {
-        if let Some(evict) = self
-            .eviction_strategy
-            .insert(&key, self.cache.contains_key(&key))
-        {
-            self.cache.remove(&evict);
-        }
-
+        self.touch(&key);
         self.cache.entry(key).or_default().clone()
     }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.touch(&key);
+        self.cache
+            .insert(key, Arc::new(OnceCell::new_with(Some(value))));
+    }
 }
 
 #[derive(Debug)]
@@ -170,6 +174,11 @@ impl<K: Eq + Hash + Clone, V, E: EvictionStrategy<K>> Cache<K, V, E> {
         Ok(CacheItem(cell))
     }
 
+    /// Replaces the value for the given key, e.g. after it has been refreshed
+    pub async fn insert(&self, key: K, value: V) {
+        self.inner.lock().await.insert(key, value)
+    }
+
     pub async fn get_some<Err>(
         &self,
         key: K,
@@ -235,6 +244,18 @@ mod tests {
         assert_eq!(*value2, 30); // Key 3 should still be present
     }
 
+    #[tokio::test]
+    async fn test_cache_replace() {
+        let cache = Cache::new(Lru::new(2));
+
+        cache.get(1, ok!(10)).await.unwrap();
+        cache.insert(1, 11).await;
+        cache.insert(2, 20).await;
+
+        assert_eq!(*cache.get(1, ok!(100)).await.unwrap(), 11);
+        assert_eq!(*cache.get(2, ok!(200)).await.unwrap(), 20);
+    }
+
     #[tokio::test]
     async fn test_lru_eviction_order() {
         let mut lru = Lru::new(2);
@@ -8,10 +8,17 @@
 //!
 //! The application uses a Redis database ([`database`] module) to store its state and scheduled notifications.
 
+#[macro_use]
+mod locale;
+
 mod allris;
 mod bot;
+mod branding;
 mod broadcasting;
 mod database;
+mod digest;
+mod filter_cache;
+mod filter_syntax;
 mod lru_cache;
 mod types;
 
@@ -19,32 +26,40 @@ use std::error::Error;
 use std::process::ExitCode;
 use std::time::Duration;
 
+use bot_utils::Recipient;
 use bot_utils::broadcasting::Broadcaster;
 use broadcasting::RedisBackend;
-use clap::Parser;
+use chrono::{NaiveDate, Utc};
+use clap::{Parser, Subcommand};
 use database::DatabaseConnection;
 use redis::{ConnectionInfo, IntoConnectionInfo};
 use tokio::sync::oneshot;
 use tracing_subscriber::EnvFilter;
 use url::Url;
 
-use crate::allris::AllrisUrl;
+use crate::allris::{Backfill, Instance};
+use crate::bot::Owner;
+use crate::branding::Branding;
 
 type Bot = frankenstein::client_reqwest::Bot;
 
 /// Telegram bot that notifies about newly published documents in the Allris 4 council information system.
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Telegram bot token
     #[arg(
         short = 't',
         long = "token",
         value_name = "TOKEN",
         env = "BOT_TOKEN",
-        hide_env_values = true
+        hide_env_values = true,
+        required = true
     )]
-    bot_token: String,
+    bot_token: Option<String>,
 
     /// URL of the Redis instance
     #[arg(
@@ -57,15 +72,16 @@ struct Args {
     )]
     redis_url: ConnectionInfo,
 
-    /// URL of the Allris 4 instance
-    #[arg(
-        short,
-        long,
-        value_name = "URL",
-        value_parser = AllrisUrl::parse,
-        default_value = "https://www.bonn.sitzung-online.de/"
-    )]
-    allris_url: AllrisUrl,
+    /// URL of an Allris 4 instance, optionally preceded by its display name. May be given
+    /// multiple times. The name identifies the instance and should not be changed later on.
+    /// [default, unless `--oparl-url` is given: the council system of the branding]
+    #[arg(short, long, value_name = "[NAME=]URL", value_parser = Instance::parse_allris)]
+    allris_url: Vec<Instance>,
+
+    /// URL of the `System` object of another OParl 1.1 server, e.g. SessionNet, optionally
+    /// preceded by its display name. May be given multiple times.
+    #[arg(long, value_name = "[NAME=]URL", value_parser = Instance::parse_oparl)]
+    oparl_url: Vec<Instance>,
 
     /// interval to check for new messages
     #[arg(short, long, value_name = "SECONDS", default_value_t = 900)]
@@ -75,9 +91,15 @@ struct Args {
     #[arg(long)]
     ignore_messages: bool,
 
-    /// Telegram username of the bot's owner
-    #[arg(short, long, value_parser = parse_owner_username)]
-    owner: Option<String>,
+    /// Telegram username or numeric user id of the bot's owner. The administration commands are
+    /// only available if the user id is given.
+    #[arg(short, long, value_name = "USERNAME|ID", value_parser = parse_owner)]
+    owner: Option<Owner>,
+
+    /// JSON file with the texts for the municipality and the operator, e.g. the privacy policy
+    /// [default: the texts for Bonn]
+    #[arg(long, value_name = "FILE", env = "BRANDING", value_parser = Branding::load)]
+    branding: Option<Branding>,
 
     /// increase verbosity
     #[arg(short, long, action = clap::ArgAction::Count)]
@@ -88,6 +110,34 @@ struct Args {
     quiet: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Schedules notifications for the papers of a past period and exits. The messages are
+    /// sent by the running bot, the regular updates are not affected.
+    Backfill {
+        /// first day of the period
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        from: NaiveDate,
+
+        /// last day of the period [default: today]
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        until: Option<NaiveDate>,
+
+        /// name of the instance [default: all instances]
+        #[arg(long, value_name = "NAME")]
+        instance: Option<String>,
+
+        /// also send papers that have already been announced
+        #[arg(long)]
+        include_known: bool,
+
+        /// send the papers only to this chat (or `CHAT_ID:TOPIC_ID` for a topic) instead of all
+        /// chats with matching filters
+        #[arg(long, value_name = "CHAT_ID", allow_negative_numbers = true)]
+        chat: Option<Recipient>,
+    },
+}
+
 fn parse_redis_url(input: &str) -> Result<ConnectionInfo, String> {
     let url = Url::parse(input).map_err(|e| e.to_string())?;
     url.into_connection_info().map_err(
@@ -97,15 +147,19 @@ fn parse_redis_url(input: &str) -> Result<ConnectionInfo, String> {
     )
 }
 
-fn parse_owner_username(mut input: &str) -> Result<String, String> {
+fn parse_owner(mut input: &str) -> Result<Owner, String> {
+    if let Ok(id) = input.parse() {
+        return Ok(Owner::UserId(id));
+    }
+
     if let Some(name) = input.strip_prefix('@') {
         input = name;
     }
 
     if input.chars().all(|x| x.is_ascii_alphanumeric() || x == '_') {
-        Ok(input.into())
+        Ok(Owner::Username(input.into()))
     } else {
-        Err("Not a valid Telegram username".into())
+        Err("Not a valid Telegram username or user id".into())
     }
 }
 
@@ -132,16 +186,107 @@ fn init_logging(args: &Args) {
         .init();
 }
 
+async fn run_backfill(
+    instances: &[Instance],
+    db: redis::Client,
+    only: Option<&str>,
+    backfill: &Backfill,
+) -> ExitCode {
+    if let Some(name) = only
+        && !instances.iter().any(|i| i.name == name)
+    {
+        eprintln!("Unknown instance: {name}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut db_conn = DatabaseConnection::new(db, Some(Duration::from_secs(10)));
+
+    // otherwise, the papers would be marked as known under the new keys only
+    if let Err(e) = db_conn.migrate_legacy_keys(&instances[0].name).await {
+        eprintln!("Migrating database keys failed: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut result = ExitCode::SUCCESS;
+    for instance in instances {
+        if only.is_some_and(|name| name != instance.name) {
+            continue;
+        }
+
+        match allris::backfill(instance, &mut db_conn, backfill).await {
+            Ok(count) => println!("{}: {count} papers scheduled", instance.name),
+            Err(e) => {
+                eprintln!("{}: backfill failed: {e}", instance.name);
+                result = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    result
+}
+
 #[tokio::main]
 async fn main() -> ExitCode {
     let args = Args::parse();
 
     init_logging(&args);
 
+    if let Some(Owner::Username(owner)) = &args.owner {
+        log::warn!(
+            "The owner @{owner} is given by username, the owner commands require the user id"
+        );
+    }
+
+    let mut branding = args.branding.unwrap_or_default();
+    if branding.contact.is_none()
+        && let Some(Owner::Username(owner)) = &args.owner
+    {
+        branding.contact = Some(format!("@{owner}"));
+    }
+
+    let mut instances: Vec<_> = args.allris_url.into_iter().chain(args.oparl_url).collect();
+    if instances.is_empty() {
+        match branding.council_system() {
+            Ok(instance) => instances.push(instance),
+            Err(e) => {
+                eprintln!("Invalid council system in the branding: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let mut names: Vec<_> = instances.iter().map(|i| &i.name).collect();
+    names.sort();
+    names.dedup();
+    if names.len() != instances.len() {
+        eprintln!("The names of the Allris instances must be unique");
+        return ExitCode::FAILURE;
+    }
+
     // this will actually not establish a database connection, and will also not fail
     // since `args.redis_url` is already of type `ConnectionInfo`
     let db_client = redis::Client::open(args.redis_url).unwrap();
-    let bot = frankenstein::client_reqwest::Bot::new(&args.bot_token);
+    allris::init_object_cache(db_client.clone());
+
+    if let Some(Command::Backfill {
+        from,
+        until,
+        instance,
+        include_known,
+        chat,
+    }) = args.command
+    {
+        let backfill = Backfill {
+            from,
+            until: until.unwrap_or_else(|| Utc::now().date_naive()),
+            include_known,
+            chat,
+        };
+        return run_backfill(&instances, db_client, instance.as_deref(), &backfill).await;
+    }
+
+    // the token is required unless a subcommand is given
+    let bot = frankenstein::client_reqwest::Bot::new(&args.bot_token.unwrap());
 
     // star bot, the unless `--ignore-messages` flag is set
     let bot_shutdown = if args.ignore_messages {
@@ -153,6 +298,8 @@ async fn main() -> ExitCode {
             bot.clone(),
             DatabaseConnection::new(db_client.clone(), Some(Duration::from_secs(6))).into_shared(),
             args.owner,
+            branding,
+            instances.clone(),
             rx,
         ));
 
@@ -161,12 +308,15 @@ async fn main() -> ExitCode {
 
     // start Allris scraper task
     let scraper_task = allris::scraper(
-        args.allris_url,
+        instances,
         Duration::from_secs(args.update_interval),
         db_client.clone(),
     );
     let scraper_handle = tokio::spawn(scraper_task);
 
+    // start the task scheduling the digests
+    let digest_handle = tokio::spawn(digest::digest_task(db_client.clone()));
+
     // start the broadcasting task
     let mut broadcaster = Broadcaster::new(RedisBackend::new(bot, db_client));
 
@@ -179,6 +329,7 @@ async fn main() -> ExitCode {
 
     // enqueueing messages is transactional, so we can safely abort the task
     scraper_handle.abort();
+    digest_handle.abort();
 
     // wait until message queue is empty, unless CTRL+C is pressed a second time
     // or 20 seconds have passed
@@ -206,19 +357,23 @@ async fn main() -> ExitCode {
 
 #[cfg(test)]
 mod tests {
-    use crate::parse_owner_username;
+    use crate::bot::Owner;
+    use crate::parse_owner;
 
     #[test]
-    fn test_parse_username() {
+    fn test_parse_owner() {
+        let username = |name: &str| Some(Owner::Username(name.into()));
         let test_cases = [
-            ("@abcD123", Some("abcD123")),
-            ("dsgz_sfdnj", Some("dsgz_sfdnj")),
+            ("@abcD123", username("abcD123")),
+            ("dsgz_sfdnj", username("dsgz_sfdnj")),
+            ("123456789", Some(Owner::UserId(123456789))),
             ("@@wrong", None),
             ("abc!", None),
+            ("-42", None),
         ];
 
         for (input, expected) in test_cases {
-            assert_eq!(parse_owner_username(input).ok().as_deref(), expected)
+            assert_eq!(parse_owner(input).ok(), expected)
         }
     }
 }
@@ -1,6 +1,7 @@
 -- KEYS[1] = SCHEDULED_MESSAGES_KEY
 -- KEYS[2] = REGISTERED_CHATS_KEY
 -- KEYS[3] = register_chat_key(chat_id)
+-- KEYS[4] = FILTER_VERSION_KEY
 -- ARGV[1] = chat_id
 -- ARGV[2] = filter
 
@@ -12,7 +13,7 @@ if entries and #entries > 0 then
 end
 
 local hset_result = redis.call("SADD", KEYS[2], ARGV[1])
-redis.call("HSET", KEYS[3], "filter", ARGV[2])
+redis.call("HSET", KEYS[3], "filter", ARGV[2], "filter_version", redis.call("INCR", KEYS[4]))
 redis.call("HSETNX", KEYS[3], "last_sent", last_entry_id)
 
 return hset_result
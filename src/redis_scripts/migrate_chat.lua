@@ -3,6 +3,12 @@
 -- KEYS[3] = registered_chat_key(new_chat_id)
 -- KEYS[4] = dialogue_key(old_chat_id)
 -- KEYS[5] = dialogue_key(new_chat_id)
+-- KEYS[6] = digest_key(old_chat_id)
+-- KEYS[7] = digest_key(new_chat_id)
+-- KEYS[8] = DIGEST_CHATS_KEY
+-- KEYS[9] = FILTER_VERSION_KEY
+-- KEYS[10] = CHAT_LOCALES_KEY
+-- KEYS[11] = MEMBER_MANAGED_CHATS_KEY
 -- ARGV[1] = old_chat_id
 -- ARGV[2] = new_chat_id
 
@@ -39,6 +45,18 @@ if old_dialogue then
     redis.call("SET", KEYS[5], old_dialogue, "NX", "EX", 60 * 60 * 24)
 end
 
+-- the chosen language applies regardless of any subscription
+local old_locale = redis.call("HGET", KEYS[10], ARGV[1])
+if old_locale then
+    redis.call("HSETNX", KEYS[10], ARGV[2], old_locale)
+    redis.call("HDEL", KEYS[10], ARGV[1])
+end
+
+-- as does the permission for all members to manage the rules
+if redis.call("SREM", KEYS[11], ARGV[1]) == 1 then
+    redis.call("SADD", KEYS[11], ARGV[2])
+end
+
 local old_chat_removed = redis.call("SREM", KEYS[1], ARGV[1])
 
 if old_chat_removed < 1 then
@@ -47,6 +65,7 @@ end
 
 local old_last_sent = redis.call("HGET", KEYS[2], "last_sent")
 local old_filter = redis.call("HGET", KEYS[2], "filter")
+local old_delivery = redis.call("HGET", KEYS[2], "delivery")
 local new_last_sent = redis.call("HGET", KEYS[3], "last_sent")
 
 redis.call("SADD", KEYS[1], ARGV[2])
@@ -54,3 +73,17 @@ redis.call("DEL", KEYS[2])
 redis.call("HSET", KEYS[2], "migrated", ARGV[2])
 redis.call("EXPIRE", KEYS[2], 36000)
 redis.call("HSET", KEYS[3], "filter", old_filter, "last_sent", max_stream_id(old_last_sent, new_last_sent))
+redis.call("HSET", KEYS[3], "filter_version", redis.call("INCR", KEYS[9]))
+
+if old_delivery then
+    redis.call("HSET", KEYS[3], "delivery", old_delivery)
+end
+
+-- a pending digest is moved to the new chat
+local digest_due = redis.call("ZSCORE", KEYS[8], ARGV[1])
+if digest_due then
+    redis.call("ZUNIONSTORE", KEYS[7], 2, KEYS[7], KEYS[6])
+    redis.call("DEL", KEYS[6])
+    redis.call("ZREM", KEYS[8], ARGV[1])
+    redis.call("ZADD", KEYS[8], "NX", digest_due, ARGV[2])
+end
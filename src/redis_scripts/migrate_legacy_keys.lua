@@ -0,0 +1,8 @@
+-- KEYS[1], KEYS[3], ... = legacy keys
+-- KEYS[2], KEYS[4], ... = corresponding keys of the instance
+
+for i = 1, #KEYS, 2 do
+    if redis.call("EXISTS", KEYS[i]) == 1 and redis.call("EXISTS", KEYS[i + 1]) == 0 then
+        redis.call("RENAME", KEYS[i], KEYS[i + 1])
+    end
+end
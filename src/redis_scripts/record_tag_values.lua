@@ -0,0 +1,20 @@
+-- KEYS[2i-1] = tag_values_key(tag) for the i-th value
+-- KEYS[2i] = tag_values_seen_key(tag) for the i-th value
+-- ARGV[1] = current timestamp
+-- ARGV[2] = timestamp before which values are forgotten
+-- ARGV[2+i] = the i-th value
+
+for i = 1, #KEYS / 2 do
+    local value = ARGV[2 + i]
+    redis.call("ZINCRBY", KEYS[2 * i - 1], 1, value)
+    redis.call("ZADD", KEYS[2 * i], ARGV[1], value)
+end
+
+-- forget values that haven't been seen for a long time
+for i = 1, #KEYS, 2 do
+    local expired = redis.call("ZRANGEBYSCORE", KEYS[i + 1], "-inf", "(" .. ARGV[2])
+    if #expired > 0 then
+        redis.call("ZREM", KEYS[i], unpack(expired))
+        redis.call("ZREM", KEYS[i + 1], unpack(expired))
+    end
+end
@@ -1,11 +1,35 @@
+-- KEYS[1] = SCHEDULED_MESSAGES_KEY
+-- KEYS[2] = known items key
+-- KEYS[3] = fulltext_key(...) for the message's full text
+-- KEYS[4..] = tracked_consultation_key(...) for each consultation to track (optional)
+-- ARGV[1] = item id
+-- ARGV[2] = message
+-- ARGV[3] = full text, empty if there is none
+-- ARGV[4] = full text ttl in seconds
+-- ARGV[5] = tracked paper (optional)
+-- ARGV[6] = tracking ttl in seconds (optional)
+
 local broadcasts_key = KEYS[1]
-local known_volfdnrs_key = KEYS[2]
-local volfdnr = ARGV[1]
+local known_items_key = KEYS[2]
+local fulltext_key = KEYS[3]
+local item_id = ARGV[1]
 local message = ARGV[2]
+local fulltext = ARGV[3]
 
--- Add volfdnr to known items
-if redis.call("SADD", known_volfdnrs_key, volfdnr) == 0 then
+-- Add item to known items
+if redis.call("SADD", known_items_key, item_id) == 0 then
     return nil  -- Abort if item was already processed
 end
 
-return redis.call("XADD", broadcasts_key, "*", "message", message, "volfdnr", volfdnr)
+if fulltext ~= "" then
+    redis.call("SET", fulltext_key, fulltext, "EX", ARGV[4])
+end
+
+local message_id = redis.call("XADD", broadcasts_key, "*", "message", message, "item", item_id)
+
+for i = 4, #KEYS do
+    redis.call("HSET", KEYS[i], "message", message_id, "paper", ARGV[5])
+    redis.call("EXPIRE", KEYS[i], ARGV[6])
+end
+
+return message_id
@@ -0,0 +1,24 @@
+-- KEYS[1] = SCHEDULED_MESSAGES_KEY
+-- KEYS[2] = digest_key(chat_id)
+-- KEYS[3] = DIGEST_CHATS_KEY
+-- ARGV[1] = chat_id
+-- ARGV[2] = due timestamp of the next digest, if further items have been added meanwhile
+-- ARGV[3] = number of messages n
+-- ARGV[4..3+n] = digest messages
+-- ARGV[4+n..] = digest items included in the messages
+
+local n = tonumber(ARGV[3])
+
+for i = 4, 3 + n do
+    redis.call("XADD", KEYS[1], "*", "message", ARGV[i])
+end
+
+for i = 4 + n, #ARGV do
+    redis.call("ZREM", KEYS[2], ARGV[i])
+end
+
+if redis.call("ZCARD", KEYS[2]) == 0 then
+    redis.call("ZREM", KEYS[3], ARGV[1])
+else
+    redis.call("ZADD", KEYS[3], ARGV[2], ARGV[1])
+end
@@ -0,0 +1,20 @@
+-- KEYS[1] = REGISTERED_CHATS_KEY
+-- KEYS[2] = registered_chat_key(chat_id)
+-- KEYS[3] = DIGEST_CHATS_KEY
+-- KEYS[4] = FILTER_VERSION_KEY
+-- ARGV[1] = chat_id
+-- ARGV[2] = delivery mode
+-- ARGV[3] = new due timestamp of a pending digest
+
+if redis.call("SISMEMBER", KEYS[1], ARGV[1]) == 0 then
+    return 0
+end
+
+-- the broadcaster caches the delivery mode along with the filters
+redis.call("HSET", KEYS[2], "delivery", ARGV[2], "filter_version", redis.call("INCR", KEYS[4]))
+
+if redis.call("ZSCORE", KEYS[3], ARGV[1]) then
+    redis.call("ZADD", KEYS[3], ARGV[3], ARGV[1])
+end
+
+return 1
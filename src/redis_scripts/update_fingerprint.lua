@@ -0,0 +1,26 @@
+-- KEYS[1] = PAPER_FINGERPRINTS_KEY
+-- KEYS[2] = SCHEDULED_MESSAGES_KEY
+-- KEYS[3] = fulltext_key(...) for the message's full text (optional)
+-- ARGV[1] = volfdnr
+-- ARGV[2] = expected current fingerprint, empty if there is none
+-- ARGV[3] = new fingerprint
+-- ARGV[4] = message announcing the changes (optional)
+-- ARGV[5] = full text, empty if there is none (optional)
+-- ARGV[6] = full text ttl in seconds (optional)
+
+local current = redis.call("HGET", KEYS[1], ARGV[1])
+
+if (current or "") ~= ARGV[2] then
+    return 0  -- fingerprint was changed in the meantime
+end
+
+redis.call("HSET", KEYS[1], ARGV[1], ARGV[3])
+
+if ARGV[4] then
+    if ARGV[5] ~= "" then
+        redis.call("SET", KEYS[3], ARGV[5], "EX", ARGV[6])
+    end
+    redis.call("XADD", KEYS[2], "*", "message", ARGV[4], "item", ARGV[1])
+end
+
+return 1
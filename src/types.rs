@@ -1,12 +1,237 @@
+use std::borrow::Cow;
 use std::fmt::Display;
 
+use bot_utils::Recipient;
+use chrono::Weekday;
 use frankenstein::methods::SendMessageParams;
 use serde::{Deserialize, Serialize};
 
+use crate::database::StreamId;
+use crate::locale::Locale;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub request: SendMessageParams,
     pub tags: Vec<(Tag, String)>,
+    #[serde(default)]
+    pub kind: MessageKind,
+    #[serde(default)]
+    pub audience: Audience,
+    /// name of the Allris instance the message originates from
+    #[serde(default)]
+    pub instance: Option<String>,
+    /// id of the full text, which is stored separately from the stream instead of as
+    /// [`Tag::Volltext`], see [`Message::take_fulltext`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fulltext: Option<String>,
+}
+
+impl Message {
+    /// Removes the full text from the tags
+    pub fn take_fulltext(&mut self) -> Option<String> {
+        let i = self
+            .tags
+            .iter()
+            .position(|(tag, _)| *tag == Tag::Volltext)?;
+        Some(self.tags.remove(i).1)
+    }
+
+    /// Adds the separately stored full text to the tags again
+    pub fn restore_fulltext(&mut self, text: String) {
+        self.fulltext = None;
+        self.tags.push((Tag::Volltext, text));
+    }
+}
+
+/// Who is supposed to receive a message
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Audience {
+    /// all chats with a matching filter
+    #[default]
+    Subscribers,
+    /// all chats that have received the referenced, earlier message
+    RecipientsOf(StreamId),
+    /// only the given chat, if one of its filters matches
+    Chat(Recipient),
+    /// only the given chat, regardless of its filters
+    Direct(Recipient),
+    /// an announcement of the owner to the registered chats, regardless of their filters
+    Announcement(AnnouncementTarget),
+}
+
+/// The registered chats an announcement is sent to
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AnnouncementTarget {
+    All,
+    Private,
+    /// groups, channels and forum topics
+    Groups,
+}
+
+impl AnnouncementTarget {
+    pub fn includes(self, recipient: Recipient) -> bool {
+        match self {
+            AnnouncementTarget::All => true,
+            AnnouncementTarget::Private => ChatKind::of(recipient) == ChatKind::Private,
+            AnnouncementTarget::Groups => ChatKind::of(recipient) != ChatKind::Private,
+        }
+    }
+}
+
+/// The kinds of chats, as far as they can be told apart by their ids
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChatKind {
+    Private,
+    /// a basic group
+    Group,
+    /// a supergroup or a channel, which share the id format
+    Supergroup,
+    /// a topic of a forum
+    Topic,
+}
+
+impl ChatKind {
+    pub const KINDS: &[Self] = &[
+        ChatKind::Private,
+        ChatKind::Group,
+        ChatKind::Supergroup,
+        ChatKind::Topic,
+    ];
+
+    pub fn of(recipient: Recipient) -> Self {
+        match recipient.chat_id {
+            _ if recipient.thread_id.is_some() => ChatKind::Topic,
+            1.. => ChatKind::Private,
+            ..=-1_000_000_000_000 => ChatKind::Supergroup,
+            _ => ChatKind::Group,
+        }
+    }
+
+    pub fn emoji(self) -> &'static str {
+        match self {
+            ChatKind::Private => "👤",
+            ChatKind::Group => "👥",
+            ChatKind::Supergroup => "📢",
+            ChatKind::Topic => "🗂",
+        }
+    }
+
+    /// The plural, e.g. "Gruppen"
+    pub fn label(self, locale: Locale) -> &'static str {
+        match self {
+            ChatKind::Private => tr!(locale, "chat_kind.private"),
+            ChatKind::Group => tr!(locale, "chat_kind.group"),
+            ChatKind::Supergroup => tr!(locale, "chat_kind.supergroup"),
+            ChatKind::Topic => tr!(locale, "chat_kind.topic"),
+        }
+    }
+}
+
+/// A paper whose consultations are watched for results
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrackedPaper {
+    pub volfdnr: String,
+    pub title: String,
+    pub reference: Option<String>,
+}
+
+/// What a notification message is about
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MessageKind {
+    /// a newly published paper (Vorlage)
+    #[default]
+    Paper,
+    /// changes to an already announced paper
+    PaperUpdate,
+    /// a newly scheduled meeting or its published agenda
+    Meeting,
+    /// the result of a paper's consultation in a meeting
+    Decision,
+    /// a summary of earlier notifications, see [`DeliveryMode`]
+    Digest,
+    /// a message of the owner, see [`Audience::Announcement`]
+    Announcement,
+}
+
+/// How the notifications for a chat are delivered. Times are local to Germany.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeliveryMode {
+    /// every notification as soon as possible
+    #[default]
+    Instant,
+    /// a digest at the start of every hour
+    Hourly,
+    /// a digest every day at the given hour
+    Daily { hour: u32 },
+    /// a digest every week on the given day, at the given hour
+    Weekly { weekday: Weekday, hour: u32 },
+}
+
+impl DeliveryMode {
+    /// Describes the mode as an adverbial, e.g. "täglich um 8 Uhr"
+    pub fn describe(&self, locale: Locale) -> String {
+        match self {
+            Self::Instant => tr!(locale, "delivery.instant").to_string(),
+            Self::Hourly => tr!(locale, "delivery.hourly").to_string(),
+            Self::Daily { hour } => tr!(locale, "delivery.daily", hour = hour),
+            Self::Weekly { weekday, hour } => {
+                let weekday = weekday_name(*weekday, locale);
+                tr!(locale, "delivery.weekly", weekday = weekday, hour = hour)
+            }
+        }
+    }
+}
+
+pub fn weekday_name(weekday: Weekday, locale: Locale) -> &'static str {
+    locale.text(&format!("weekday.{}", weekday.number_from_monday()))
+}
+
+/// German names of the weekdays, starting with Monday, as used in notifications
+pub const WEEKDAYS: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+
+/// The kinds of notifications a rule applies to
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Scope {
+    #[default]
+    Papers,
+    Meetings,
+    All,
+}
+
+impl Scope {
+    pub fn label(&self, locale: Locale) -> &'static str {
+        match self {
+            Scope::Papers => tr!(locale, "scope.papers"),
+            Scope::Meetings => tr!(locale, "scope.meetings"),
+            Scope::All => tr!(locale, "scope.all"),
+        }
+    }
+
+    /// the scope that follows this one when cycling through all options
+    pub fn next(self) -> Self {
+        match self {
+            Scope::Papers => Scope::Meetings,
+            Scope::Meetings => Scope::All,
+            Scope::All => Scope::Papers,
+        }
+    }
+
+    pub fn covers(&self, kind: MessageKind) -> bool {
+        matches!(
+            (self, kind),
+            (Scope::All, _)
+                | (Scope::Papers, MessageKind::Paper | MessageKind::PaperUpdate)
+                | (Scope::Meetings, MessageKind::Meeting)
+        )
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -18,6 +243,7 @@ pub enum Tag {
     Verfasser,
     Federführend,
     Beteiligt,
+    Volltext,
 }
 
 impl Tag {
@@ -29,65 +255,127 @@ impl Tag {
         Tag::Federführend,
         Tag::Gremium,
         Tag::Verfasser,
+        Tag::Volltext,
     ];
 
-    pub fn label(&self) -> &'static str {
+    pub fn label(&self, locale: Locale) -> &'static str {
+        locale.text(&format!("tag.{}", self.key()))
+    }
+
+    /// The name of the tag in the textual filter syntax
+    pub fn key(&self) -> &'static str {
         match self {
-            Tag::Title => "Titel",
-            Tag::Dsnr => "Drucksachen-Nummer",
-            Tag::Art => "Art der Vorlage",
-            Tag::Gremium => "Gremium",
-            Tag::Verfasser => "Antrag- oder Fragesteller:in",
-            Tag::Federführend => "Federführendes Amt",
-            Tag::Beteiligt => "Beteiligtes Amt",
+            Tag::Title => "titel",
+            Tag::Dsnr => "dsnr",
+            Tag::Art => "art",
+            Tag::Gremium => "gremium",
+            Tag::Verfasser => "verfasser",
+            Tag::Federführend => "federführend",
+            Tag::Beteiligt => "beteiligt",
+            Tag::Volltext => "volltext",
         }
     }
 
-    pub fn description(&self) -> Option<&'static str> {
+    pub fn description(&self, locale: Locale) -> Option<&'static str> {
+        locale.get(&format!("tag.{}.description", self.key()))
+    }
+
+    /// Whether the values seen in notifications are recorded, to be offered as input.
+    /// Titles, numbers and full texts are mostly unique, so they aren't.
+    pub fn has_known_values(&self) -> bool {
+        matches!(
+            self,
+            Tag::Art | Tag::Gremium | Tag::Verfasser | Tag::Federführend | Tag::Beteiligt
+        )
+    }
+
+    /// Finds the tag by its name in the textual filter syntax, ignoring the case
+    pub fn from_key(key: &str) -> Option<Self> {
+        let key = key.to_lowercase();
+        Tag::TAGS
+            .iter()
+            .find(|tag| tag.key() == key || (key == "federfuehrend" && **tag == Tag::Federführend))
+            .copied()
+    }
+}
+
+/// How the pattern of a condition is compared with a value. Apart from regexes,
+/// the case is ignored.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MatchMode {
+    /// the value contains the text
+    #[default]
+    Contains,
+    /// the value contains the text as a whole word
+    Word,
+    /// the value is equal to the text
+    Exact,
+    /// the value starts with the text
+    Prefix,
+    Regex,
+}
+
+impl MatchMode {
+    /// Conditions from before match modes were introduced are regexes
+    pub fn legacy() -> Self {
+        MatchMode::Regex
+    }
+
+    /// The key of the mode in the message catalogue
+    fn key(&self) -> &'static str {
         match self {
-            Tag::Title => None,
-            Tag::Dsnr => None,
-            Tag::Art => None,
-            Tag::Gremium => Some("Gremien, die zur Beratung der Vorlage vorgesehen sind"),
-            Tag::Verfasser => {
-                Some("Personen oder Fraktionen, die den Antrag oder die Frage gestellt haben")
-            }
-            Tag::Federführend => None,
-            Tag::Beteiligt => Some(
-                "jedes an der Vorlage beteiligte Amt; das schließt auch das federführende Amt mit ein",
-            ),
+            MatchMode::Contains => "contains",
+            MatchMode::Word => "word",
+            MatchMode::Exact => "exact",
+            MatchMode::Prefix => "prefix",
+            MatchMode::Regex => "regex",
         }
     }
 
-    pub fn examples(&self) -> &'static [&'static str] {
+    pub fn label(&self, locale: Locale) -> &'static str {
+        locale.text(&format!("mode.{}", self.key()))
+    }
+
+    /// Explains how the mode compares, to complete a sentence
+    pub fn description(&self, locale: Locale) -> &'static str {
+        locale.text(&format!("mode.{}.description", self.key()))
+    }
+
+    /// the mode that follows this one when cycling through all options
+    pub fn next(self) -> Self {
         match self {
-            Tag::Title => &[
-                "Koalitionsantrag: Änderung Bebauungsplan 8423-16",
-                "Friedhofsentwicklungsplanung - Bad Godesberg",
-            ],
-            Tag::Dsnr => &["252807", "242248-02 AA"],
-            Tag::Art => &[
-                "Beschlussvorlage",
-                "Stellungnahme der Verwaltung",
-                "Anregungen und Beschwerden",
-            ],
-            Tag::Gremium => &[
-                "Rat",
-                "Bezirksvertretung Beuel",
-                "Schulausschuss",
-                "Städtebau- und Gestaltungsbeirat",
-            ],
-            Tag::Verfasser => &[
-                "SPD-Fraktion im Rat der Stadt Bonn",
-                "Patrick Tollasz",
-                "CDU Bezirksfraktion Bad Godesberg",
-            ],
-            Tag::Federführend | Tag::Beteiligt => &[
-                "Dezernat II",
-                "52 Sport- und Bäderamt",
-                "OB-22 Stabsstelle Bürgerbeteiligung",
-                "61-3 Stadtverkehr",
-            ],
+            MatchMode::Contains => MatchMode::Word,
+            MatchMode::Word => MatchMode::Exact,
+            MatchMode::Exact => MatchMode::Prefix,
+            MatchMode::Prefix => MatchMode::Regex,
+            MatchMode::Regex => MatchMode::Contains,
+        }
+    }
+
+    /// The regex that implements the mode for the given text
+    pub fn regex<'a>(&self, pattern: &'a str) -> Cow<'a, str> {
+        let escaped = regex::escape(pattern);
+        let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+        match self {
+            MatchMode::Contains => format!("(?i){escaped}").into(),
+            MatchMode::Word => {
+                // \b would never match next to a character that isn't part of a word
+                let start = if is_word_char(pattern.chars().next()) {
+                    "\\b"
+                } else {
+                    ""
+                };
+                let end = if is_word_char(pattern.chars().last()) {
+                    "\\b"
+                } else {
+                    ""
+                };
+                format!("(?i){start}{escaped}{end}").into()
+            }
+            MatchMode::Exact => format!("(?i)^{escaped}$").into(),
+            MatchMode::Prefix => format!("(?i)^{escaped}").into(),
+            MatchMode::Regex => pattern.into(),
         }
     }
 }
@@ -97,35 +385,251 @@ pub struct Condition {
     pub tag: Tag,
     pub pattern: String,
     pub negate: bool,
+    #[serde(default = "MatchMode::legacy")]
+    pub mode: MatchMode,
 }
 
-impl Display for Condition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} passt {}zu \"{}\"",
-            self.tag.label(),
-            if self.negate { "nicht " } else { "" },
-            self.pattern.as_str()
+impl Condition {
+    /// The regex that is matched against the values
+    pub fn regex(&self) -> Cow<'_, str> {
+        self.mode.regex(&self.pattern)
+    }
+
+    /// Describes the condition as a sentence, e.g. `Gremium enthält "Rat"`
+    pub fn describe(&self, locale: Locale) -> String {
+        let key = format!(
+            "condition.{}{}",
+            self.mode.key(),
+            if self.negate { ".not" } else { "" }
+        );
+        tr!(
+            locale,
+            &key,
+            label = self.tag.label(locale),
+            pattern = self.pattern
         )
     }
 }
 
+/// A boolean expression over conditions. See [`crate::filter_syntax`] for its textual form.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct Filter {
-    pub conditions: Vec<Condition>,
+pub enum Expr {
+    Condition(Condition),
+    /// matches if all of the expressions match, so an empty list matches everything
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Default for Expr {
+    fn default() -> Self {
+        Expr::And(vec![])
+    }
 }
 
-impl Display for Filter {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.conditions.is_empty() {
-            writeln!(f, "Alle Vorlagen")?;
-        } else {
-            for condition in &self.conditions {
-                writeln!(f, "• {condition}")?;
+impl Expr {
+    /// whether the expression matches everything
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Expr::And(exprs) if exprs.is_empty())
+    }
+
+    /// All conditions of the expression
+    pub fn conditions(&self) -> Vec<&Condition> {
+        let mut conditions = vec![];
+        self.collect_conditions(&mut conditions, false);
+        conditions
+    }
+
+    /// The conditions that have to match for the expression to match, i.e. those that
+    /// are neither negated themselves nor part of a negated expression
+    pub fn positive_conditions(&self) -> Vec<&Condition> {
+        let mut conditions = vec![];
+        self.collect_conditions(&mut conditions, true);
+        conditions
+    }
+
+    /// The condition at the given position, counted like in [`Expr::conditions`]
+    pub fn condition_mut(&mut self, index: usize) -> Option<&mut Condition> {
+        self.find_condition(&mut { index })
+    }
+
+    fn find_condition(&mut self, index: &mut usize) -> Option<&mut Condition> {
+        match self {
+            Expr::Condition(condition) => {
+                if *index == 0 {
+                    return Some(condition);
+                }
+                *index -= 1;
+                None
             }
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                exprs.iter_mut().find_map(|expr| expr.find_condition(index))
+            }
+            Expr::Not(expr) => expr.find_condition(index),
         }
+    }
+
+    /// Removes the condition at the given position, along with any expression that is
+    /// left without conditions
+    pub fn remove_condition(&mut self, index: usize) -> Option<Condition> {
+        let (condition, empty) = self.take_condition(&mut { index })?;
+        if empty {
+            *self = Expr::default();
+        }
+        Some(condition)
+    }
+
+    /// Returns the removed condition, and whether this expression is empty now
+    fn take_condition(&mut self, index: &mut usize) -> Option<(Condition, bool)> {
+        match self {
+            Expr::Condition(condition) => {
+                if *index == 0 {
+                    return Some((condition.clone(), true));
+                }
+                *index -= 1;
+                None
+            }
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                for i in 0..exprs.len() {
+                    if let Some((condition, empty)) = exprs[i].take_condition(index) {
+                        if empty {
+                            exprs.remove(i);
+                        }
+                        let empty = exprs.is_empty();
+                        // a single remaining expression doesn't need to be combined
+                        if exprs.len() == 1 {
+                            let remaining = exprs.remove(0);
+                            *self = remaining;
+                        }
+                        return Some((condition, empty));
+                    }
+                }
+                None
+            }
+            Expr::Not(expr) => expr.take_condition(index),
+        }
+    }
+
+    /// Adds a condition that has to match in addition to the expression
+    pub fn push_condition(&mut self, condition: Condition) {
+        match self {
+            Expr::And(exprs) => exprs.push(Expr::Condition(condition)),
+            expr => {
+                let previous = std::mem::take(expr);
+                *expr = Expr::And(vec![previous, Expr::Condition(condition)]);
+            }
+        }
+    }
+
+    fn collect_conditions<'a>(&'a self, conditions: &mut Vec<&'a Condition>, positive: bool) {
+        match self {
+            Expr::Condition(condition) => {
+                if !(positive && condition.negate) {
+                    conditions.push(condition);
+                }
+            }
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                for expr in exprs {
+                    expr.collect_conditions(conditions, positive);
+                }
+            }
+            Expr::Not(expr) => {
+                if !positive {
+                    expr.collect_conditions(conditions, positive);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "StoredFilter")]
+pub struct Filter {
+    pub expression: Expr,
+    pub scope: Scope,
+    /// whether changes to already announced papers are included
+    pub updates: bool,
+    /// names of the Allris instances the rule is limited to, empty for all instances
+    pub instances: Vec<String>,
+    /// whether passages of the full text that match the rule are included in notifications
+    pub snippets: bool,
+}
+
+/// A filter as stored in the database. Filters from before expressions were supported
+/// have a list of conditions that all have to match instead.
+#[derive(Deserialize)]
+struct StoredFilter {
+    expression: Option<Expr>,
+    #[serde(default)]
+    conditions: Vec<Condition>,
+    #[serde(default)]
+    scope: Scope,
+    #[serde(default)]
+    updates: bool,
+    #[serde(default)]
+    instances: Vec<String>,
+    #[serde(default)]
+    snippets: bool,
+}
+
+impl From<StoredFilter> for Filter {
+    fn from(stored: StoredFilter) -> Self {
+        let expression = stored.expression.unwrap_or_else(|| {
+            Expr::And(stored.conditions.into_iter().map(Expr::Condition).collect())
+        });
+
+        Filter {
+            expression,
+            scope: stored.scope,
+            updates: stored.updates,
+            instances: stored.instances,
+            snippets: stored.snippets,
+        }
+    }
+}
+
+impl Filter {
+    /// Lists the settings and conditions of the rule, one per line
+    pub fn describe(&self, locale: Locale) -> impl Display + '_ {
+        std::fmt::from_fn(move |f| {
+            let scope = self.scope.label(locale);
+            if self.expression.is_empty() {
+                writeln!(f, "{}", tr!(locale, "filter.all", scope = scope))?;
+            } else if self.scope != Scope::default() {
+                writeln!(f, "{}", tr!(locale, "filter.scope", scope = scope))?;
+            }
+
+            if !self.instances.is_empty() {
+                let instances = self.instances.join(", ");
+                writeln!(
+                    f,
+                    "{}",
+                    tr!(locale, "filter.instances", instances = instances)
+                )?;
+            }
+
+            if self.updates {
+                writeln!(f, "{}", tr!(locale, "filter.updates"))?;
+            }
+
+            if self.snippets {
+                writeln!(f, "{}", tr!(locale, "filter.snippets"))?;
+            }
+
+            // the parts of a conjunction are listed one by one, anything more complex as an
+            // expression
+            let parts = match &self.expression {
+                Expr::And(exprs) => exprs.as_slice(),
+                expr => std::slice::from_ref(expr),
+            };
+            for part in parts {
+                match part {
+                    Expr::Condition(condition) => writeln!(f, "• {}", condition.describe(locale))?,
+                    expr => writeln!(f, "• {expr}")?,
+                }
+            }
 
-        Ok(())
+            Ok(())
+        })
     }
 }